@@ -0,0 +1,66 @@
+//headless simulation throughput and wall collision cost; run with `cargo bench`
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ggez::graphics::Rect;
+use rust_pacman::collision::{self, WallGrid};
+use rust_pacman::env::{PacmanEnv, ACTIONS};
+
+const TICKS: u64 = 600;
+const CELL_SIZE: f32 = 30.0;
+
+//a fixed input pattern that turns every second so pacman keeps moving through the maze
+fn action(tick: u64) -> rust_pacman::Direction {
+    ACTIONS[(tick / 60 % 4 + 1) as usize]
+}
+
+fn ticks_per_second(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ticks");
+    group.throughput(Throughput::Elements(TICKS));
+    for ghosts in [1, 3, 8, 32] {
+        group.bench_with_input(BenchmarkId::new("ghosts", ghosts), &ghosts, |b, &ghosts| {
+            let mut env = PacmanEnv::new().unwrap();
+            b.iter(|| {
+                env.reset(42);
+                env.set_ghost_count(ghosts);
+                for tick in 0..TICKS {
+                    if env.advance(action(tick)) {
+                        env.reset(42);
+                        env.set_ghost_count(ghosts);
+                    }
+                }
+            });
+        });
+    }
+    group.finish();
+}
+
+//a square maze of `size` cells: a solid border with a pillar on every other cell inside,
+//which has roughly the wall density of the classic map
+fn maze_walls(size: usize) -> WallGrid {
+    let mut walls = Vec::new();
+    for y in 0..size {
+        for x in 0..size {
+            let border = x == 0 || y == 0 || x == size - 1 || y == size - 1;
+            if border || (x % 2 == 0 && y % 2 == 0) {
+                walls.push(Rect::new(x as f32 * CELL_SIZE, y as f32 * CELL_SIZE, CELL_SIZE, CELL_SIZE));
+            }
+        }
+    }
+    WallGrid::new(&walls)
+}
+
+fn wall_collision(c: &mut Criterion) {
+    let mut group = c.benchmark_group("wall_collision");
+    for size in [20, 40, 80] {
+        let walls = maze_walls(size);
+        //an open cell in the middle; the cost shouldn't grow with the maze
+        let center = ((size / 2) | 1) as f32 * CELL_SIZE;
+        let probe = Rect::new(center + 2.5, center + 2.5, 25.0, 25.0);
+        group.bench_with_input(BenchmarkId::new("maze", size), &walls, |b, walls| {
+            b.iter(|| collision::hits_wall(black_box(walls), black_box(&probe)));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, ticks_per_second, wall_collision);
+criterion_main!(benches);
@@ -0,0 +1,17 @@
+//regenerates the C header for the `ffi` feature; nothing to do otherwise
+fn main() {
+    #[cfg(feature = "ffi")]
+    {
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+            .expect("invalid cbindgen.toml");
+        cbindgen::Builder::new()
+            .with_crate(&crate_dir)
+            .with_config(config)
+            .generate()
+            .expect("failed to generate the C header")
+            .write_to_file(format!("{}/include/rust_pacman.h", crate_dir));
+    }
+}
@@ -0,0 +1,22 @@
+//malformed maps must come back as errors, never panics or out-of-bounds indexing
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rust_pacman::map;
+
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data);
+    if let Ok(map) = map::parse(&text) {
+        assert!(map.width <= map::MAX_MAP_SIZE && map.height <= map::MAX_MAP_SIZE);
+        for row in 0..map.height {
+            for col in 0..map.width {
+                assert!(map.cell(col, row).is_some());
+            }
+        }
+        assert_eq!(map.cell(map.width, 0), None);
+        let (col, row) = map.pacman_start();
+        assert_eq!(map.cell(col, row), Some('P'));
+        for (col, row) in map.ghost_spawns() {
+            assert_eq!(map.cell(col, row), Some('G'));
+        }
+    }
+});
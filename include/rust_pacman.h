@@ -0,0 +1,103 @@
+/*
+ * A panic inside the game never unwinds into C. pacman_create returns NULL, and a game that
+ * panicked counts as ended from then on: pacman_step returns true and both pacman_step and
+ * pacman_reset fill their PacmanState with game_over set. Only pacman_destroy is still useful
+ * on such a game.
+ */
+
+#ifndef RUST_PACMAN_H
+#define RUST_PACMAN_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define PACMAN_ACTION_NOOP 0
+
+#define PACMAN_ACTION_UP 1
+
+#define PACMAN_ACTION_DOWN 2
+
+#define PACMAN_ACTION_LEFT 3
+
+#define PACMAN_ACTION_RIGHT 4
+
+/**
+ * Ghosts beyond this are left out of `PacmanState`.
+ */
+#define PACMAN_MAX_GHOSTS 4
+
+/**
+ * Opaque handle, owned by the caller from `pacman_create` until `pacman_destroy`.
+ */
+typedef struct PacmanGame PacmanGame;
+
+typedef struct PacmanGhost {
+  float x;
+  float y;
+  bool vulnerable;
+} PacmanGhost;
+
+/**
+ * Snapshot of the game after a step. Positions are in tile units.
+ */
+typedef struct PacmanState {
+  float pacman_x;
+  float pacman_y;
+  uint32_t ghost_count;
+  PacmanGhost ghosts[PACMAN_MAX_GHOSTS];
+  uint32_t dots_remaining;
+  float power_pellet_timer;
+  uint32_t score;
+  int32_t lives;
+  float reward;
+  bool game_over;
+} PacmanState;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates a game seeded with `seed`, or returns null if it could not be created,
+ * including when creating it panicked.
+ */
+PacmanGame *pacman_create(uint64_t seed);
+
+/**
+ * Starts a new game with `seed` and fills `out` if it isn't null. If the game has
+ * panicked, now or in an earlier call, `out` is filled with `game_over` set and the
+ * game stays ended.
+ *
+ * # Safety
+ * `game` must come from `pacman_create` and not have been destroyed; `out` must be null
+ * or point to a writable `PacmanState`.
+ */
+void pacman_reset(PacmanGame *game, uint64_t seed, PacmanState *out);
+
+/**
+ * Advances one tick, fills `out` if it isn't null and returns whether the game has
+ * ended. Unknown actions are treated as `PACMAN_ACTION_NOOP`. A game that has panicked
+ * returns true with `game_over` set in `out`, here and on every later step.
+ *
+ * # Safety
+ * Same requirements as `pacman_reset`.
+ */
+bool pacman_step(PacmanGame *game, uint32_t action, PacmanState *out);
+
+/**
+ * Frees a game. Null is ignored.
+ *
+ * # Safety
+ * `game` must be null or come from `pacman_create`, and must not be used afterwards.
+ */
+void pacman_destroy(PacmanGame *game);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RUST_PACMAN_H */
@@ -0,0 +1,78 @@
+//the accessibility option: a high-contrast palette with outlined pacman and ghosts, and events
+//announced as captions, spoken too in builds with the `tts` feature
+use ggez::graphics::Color;
+
+//score announcements come every this many points
+pub const SCORE_MILESTONE: u32 = 10_000;
+
+//the colours the board is drawn in
+pub struct Palette {
+    pub walls: Color,
+    pub dots: Color,
+    pub pacman: Color,
+    pub frightened: Color,
+    //what frightened ghosts flash to as their fright runs out
+    pub frightened_flash: Color,
+    //drawn around pacman and the ghosts, with its width
+    pub outline: Option<(Color, f32)>,
+}
+
+impl Palette {
+    pub fn classic() -> Palette {
+        Palette {
+            walls: Color::new(0.0, 0.0, 1.0, 1.0),
+            dots: Color::WHITE,
+            pacman: Color::YELLOW,
+            frightened: Color::BLUE,
+            frightened_flash: Color::WHITE,
+            outline: None,
+        }
+    }
+
+    //white walls, and nothing that moves drawn in a colour close to the walls or the dots
+    pub fn high_contrast() -> Palette {
+        Palette {
+            walls: Color::WHITE,
+            dots: Color::new(1.0, 0.85, 0.0, 1.0),
+            pacman: Color::YELLOW,
+            frightened: Color::new(0.0, 1.0, 1.0, 1.0),
+            //white would match the walls
+            frightened_flash: Color::new(1.0, 0.5, 0.0, 1.0),
+            outline: Some((Color::new(1.0, 0.0, 1.0, 1.0), 3.0)),
+        }
+    }
+}
+
+//speaks announcements when it can. Every announcement is captioned on screen whatever happens
+//here, so a build without speech or a system without a voice still shows them
+pub struct Announcer {
+    #[cfg(feature = "tts")]
+    tts: Option<tts::Tts>,
+}
+
+impl Announcer {
+    pub fn new() -> Announcer {
+        Announcer {
+            #[cfg(feature = "tts")]
+            tts: match tts::Tts::default() {
+                Ok(tts) => Some(tts),
+                Err(e) => {
+                    eprintln!("Announcements won't be spoken: {}", e);
+                    None
+                }
+            },
+        }
+    }
+
+    //a newer announcement cuts off one still being spoken
+    pub fn say(&mut self, text: &str) {
+        #[cfg(feature = "tts")]
+        if let Some(tts) = &mut self.tts {
+            if let Err(e) = tts.speak(text, true) {
+                eprintln!("Could not speak announcement: {}", e);
+            }
+        }
+        #[cfg(not(feature = "tts"))]
+        let _ = text;
+    }
+}
@@ -0,0 +1,149 @@
+//the cheat menu, on F2 in debug builds or with `--dev`. Every row runs a console command, so
+//whatever the menu does can also be typed or scripted
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+
+//switched on from the console or the cheat menu, and off again when a game starts
+#[derive(Clone, Copy, Default)]
+pub struct Cheats {
+    //nothing costs pacman a life
+    pub invincible: bool,
+    //the ghosts stop where they are
+    pub ghosts_frozen: bool,
+    //a command changed this game, so it stays out of the history
+    pub used: bool,
+}
+
+//(label, command) for each row
+const ROWS: [(&str, &str); 4] = [
+    ("Invincible", "god"),
+    ("Ghost AI", "ai"),
+    ("Clear level", "clear"),
+    ("Refresh power pellet", "power"),
+];
+
+//what the caller should do after a key press
+pub enum CheatAction {
+    None,
+    Close,
+    Run(&'static str),
+}
+
+pub struct CheatMenu {
+    selected: usize,
+    //what the last command printed, and whether it failed
+    message: Option<(String, bool)>,
+}
+
+impl CheatMenu {
+    pub fn new() -> Self {
+        CheatMenu {
+            selected: 0,
+            message: None,
+        }
+    }
+
+    pub fn handle_key(&mut self, keycode: KeyCode) -> CheatAction {
+        match keycode {
+            KeyCode::Escape | KeyCode::F2 => CheatAction::Close,
+            KeyCode::Up => {
+                self.selected = (self.selected + ROWS.len() - 1) % ROWS.len();
+                CheatAction::None
+            }
+            KeyCode::Down => {
+                self.selected = (self.selected + 1) % ROWS.len();
+                CheatAction::None
+            }
+            KeyCode::Return | KeyCode::Space => CheatAction::Run(ROWS[self.selected].1),
+            _ => CheatAction::None,
+        }
+    }
+
+    pub fn set_result(&mut self, result: Result<String, String>) {
+        self.message = Some(match result {
+            Ok(output) => (output, false),
+            Err(e) => (e, true),
+        });
+    }
+
+    pub fn draw(&self, ctx: &mut Context, cheats: &Cheats) -> GameResult {
+        let (w, h) = graphics::drawable_size(ctx);
+
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, 0.0, w, h),
+            Color::new(0.0, 0.0, 0.0, 0.7),
+        )?;
+        graphics::draw(ctx, &background, DrawParam::default())?;
+
+        let menu_width = 380.0;
+        let menu_height = 290.0;
+        let menu_x = (w - menu_width) / 2.0;
+        let menu_y = (h - menu_height) / 2.0;
+        let menu_bg = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(menu_x, menu_y, menu_width, menu_height),
+            Color::new(0.2, 0.2, 0.2, 1.0),
+        )?;
+        graphics::draw(ctx, &menu_bg, DrawParam::default())?;
+
+        let title = graphics::Text::new("Cheats");
+        let title_dims = title.dimensions(ctx);
+        graphics::draw(
+            ctx,
+            &title,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 {
+                    x: menu_x + (menu_width - title_dims.w * 2.0) / 2.0,
+                    y: menu_y + 20.0,
+                })
+                .color(Color::YELLOW)
+                .scale([2.0, 2.0]),
+        )?;
+
+        let on_off = |on: bool| if on { "On" } else { "Off" };
+        for (i, (label, _)) in ROWS.iter().enumerate() {
+            let row = match i {
+                0 => format!("{:<12}{}", label, on_off(cheats.invincible)),
+                1 => format!("{:<12}{}", label, on_off(!cheats.ghosts_frozen)),
+                _ => label.to_string(),
+            };
+            let color = if i == self.selected { Color::YELLOW } else { Color::WHITE };
+            let text = graphics::Text::new(row);
+            graphics::draw(
+                ctx,
+                &text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 {
+                        x: menu_x + 40.0,
+                        y: menu_y + 80.0 + i as f32 * 30.0,
+                    })
+                    .color(color),
+            )?;
+        }
+
+        if let Some((message, error)) = &self.message {
+            let text = graphics::Text::new(message.as_str());
+            graphics::draw(
+                ctx,
+                &text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 { x: menu_x + 20.0, y: menu_y + 215.0 })
+                    .color(if *error { Color::RED } else { Color::GREEN }),
+            )?;
+        }
+
+        let hint = graphics::Text::new("Enter: toggle / run   Esc: back");
+        graphics::draw(
+            ctx,
+            &hint,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: menu_x + 20.0, y: menu_y + 250.0 })
+                .color(Color::WHITE),
+        )?;
+        Ok(())
+    }
+}
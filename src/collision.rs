@@ -0,0 +1,252 @@
+//wall collision queries shared by pacman and the ghosts
+use ggez::graphics::Rect;
+
+use crate::fixed::Fixed;
+use crate::pathfinding::TileGraph;
+use crate::{CELL, CELL_SIZE};
+
+//the maze's walls as a grid of cells, so a query only looks at the few cells a box covers
+//instead of every wall. Walls have to be whole cells, which is what the maze text produces
+#[derive(Clone, Default)]
+pub struct WallGrid {
+    width: usize,
+    height: usize,
+    cells: Vec<bool>,
+    //kept for drawing
+    rects: Vec<Rect>,
+    //the maze's size in cells when its edges join up, left to right and top to bottom
+    wrap: Option<(usize, usize)>,
+    //the side tunnels, for a maze that doesn't wrap everywhere
+    tunnels: Tunnels,
+    //the open cells, for the ghosts' pathfinding
+    paths: TileGraph,
+}
+
+impl WallGrid {
+    pub fn new(walls: &[Rect]) -> WallGrid {
+        WallGrid::with_void(walls, &[])
+    }
+
+    //`void` cells, as (column, row), block movement like walls but aren't drawn
+    pub fn with_void(walls: &[Rect], void: &[(usize, usize)]) -> WallGrid {
+        let cell_of = |wall: &Rect| ((wall.x / CELL_SIZE).round() as i64, (wall.y / CELL_SIZE).round() as i64);
+        let on_grid: Vec<(usize, usize)> = walls
+            .iter()
+            .map(cell_of)
+            .filter(|&(col, row)| col >= 0 && row >= 0)
+            .map(|(col, row)| (col as usize, row as usize))
+            .chain(void.iter().copied())
+            .collect();
+        let width = on_grid.iter().map(|&(col, _)| col + 1).max().unwrap_or(0);
+        let height = on_grid.iter().map(|&(_, row)| row + 1).max().unwrap_or(0);
+        let mut cells = vec![false; width * height];
+        for (col, row) in on_grid {
+            cells[row * width + col] = true;
+        }
+        let mut grid = WallGrid {
+            width,
+            height,
+            cells,
+            rects: walls.to_vec(),
+            wrap: None,
+            tunnels: Tunnels::default(),
+            paths: TileGraph::default(),
+        };
+        grid.paths = TileGraph::new(&grid);
+        grid
+    }
+
+    //makes a maze of `width` x `height` cells wrap around at all four edges, like a torus
+    pub fn set_wrap(&mut self, width: usize, height: usize) {
+        self.wrap = Some((width, height));
+        self.paths = TileGraph::new(self);
+    }
+
+    //joins up the rows and columns `tunnels` leads through
+    pub fn set_tunnels(&mut self, tunnels: Tunnels) {
+        self.tunnels = tunnels;
+        self.paths = TileGraph::new(self);
+    }
+
+    pub fn tunnels(&self) -> &Tunnels {
+        &self.tunnels
+    }
+
+    //the grid's size in cells, all of the maze when it wraps or has a tunnel
+    pub fn size(&self) -> (usize, usize) {
+        match self.wrap {
+            Some(size) => size,
+            None if self.tunnels.leads_anywhere() => (self.tunnels.width, self.tunnels.height),
+            None => (self.width, self.height),
+        }
+    }
+
+    pub fn paths(&self) -> &TileGraph {
+        &self.paths
+    }
+
+    //whether a cell is on the grid at all. Every cell is on a wrapping one, somewhere, and so is
+    //every cell a tunnel leads on to
+    pub fn contains(&self, col: i64, row: i64) -> bool {
+        let (col, row) = self.wrap_cell(col, row);
+        let (width, height) = self.size();
+        self.wrap.is_some() || (col >= 0 && row >= 0 && (col as usize) < width && (row as usize) < height)
+    }
+
+    //anything outside the grid is open, unless the maze wraps or a tunnel leads there and it's
+    //the cell on the far side
+    pub fn is_wall(&self, col: i64, row: i64) -> bool {
+        let (col, row) = self.wrap_cell(col, row);
+        if col < 0 || row < 0 || col as usize >= self.width || row as usize >= self.height {
+            return false;
+        }
+        self.cells[row as usize * self.width + col as usize]
+    }
+
+    //a cell past an edge the maze joins up across, as the cell on the far side
+    fn wrap_cell(&self, col: i64, row: i64) -> (i64, i64) {
+        match self.wrap {
+            Some((width, height)) => (col.rem_euclid(width as i64), row.rem_euclid(height as i64)),
+            None => self.tunnels.wrap_cell(col, row),
+        }
+    }
+
+    //whether ghosts slow down on the cell
+    pub fn in_tunnel(&self, (col, row): (i64, i64)) -> bool {
+        self.tunnels.contains(col, row)
+    }
+
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    //the maze's size in pixels, if it wraps
+    pub fn wrap_size(&self) -> Option<(Fixed, Fixed)> {
+        self.wrap.map(|(width, height)| (CELL * width as i32, CELL * height as i32))
+    }
+
+    //the shortest way along each axis from one point to another, across an edge when that's
+    //nearer on a wrapping maze, or through a tunnel both points are in line with
+    pub fn delta(&self, from: (Fixed, Fixed), to: (Fixed, Fixed)) -> (Fixed, Fixed) {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        if let Some((width, height)) = self.wrap_size() {
+            return (shortest(dx, width), shortest(dy, height));
+        }
+        let (width, height) = (CELL * self.tunnels.width as i32, CELL * self.tunnels.height as i32);
+        let (col, row) = (from.0.div_floor(CELL) as i64, from.1.div_floor(CELL) as i64);
+        let same_row = row == to.1.div_floor(CELL) as i64;
+        let same_col = col == to.0.div_floor(CELL) as i64;
+        (
+            if same_row && self.tunnels.joins_row(row) { shortest(dx, width) } else { dx },
+            if same_col && self.tunnels.joins_col(col) { shortest(dy, height) } else { dy },
+        )
+    }
+
+    //a box of `size` whose center has crossed an edge of a wrapping maze, or the end of a tunnel,
+    //moved in from the far side. Anything else stays where it is
+    pub fn wrap_box(&self, x: Fixed, y: Fixed, size: Fixed) -> (Fixed, Fixed) {
+        let (col, row) = ((x + size.half()).div_floor(CELL) as i64, (y + size.half()).div_floor(CELL) as i64);
+        let (to_col, to_row) = self.wrap_cell(col, row);
+        (x + CELL * (to_col - col) as i32, y + CELL * (to_row - row) as i32)
+    }
+
+    //`wrap_box` for a point in pixels: how far it has to move to come back in on the far side
+    pub fn wrap_shift(&self, x: f32, y: f32) -> (f32, f32) {
+        let (col, row) = ((x / CELL_SIZE).floor() as i64, (y / CELL_SIZE).floor() as i64);
+        let (to_col, to_row) = self.wrap_cell(col, row);
+        ((to_col - col) as f32 * CELL_SIZE, (to_row - row) as f32 * CELL_SIZE)
+    }
+}
+
+//the maze's 'T' cells. A tunnel cell on the left or right edge joins its row up across those
+//edges, so leaving one side comes back in on the other, and one on the top or bottom edge does
+//the same for its column
+#[derive(Clone, Default)]
+pub struct Tunnels {
+    width: usize,
+    height: usize,
+    cells: Vec<bool>,
+    rows: Vec<bool>,
+    cols: Vec<bool>,
+}
+
+impl Tunnels {
+    //a maze of `width` x `height` cells with tunnels on `cells`, as (column, row)
+    pub fn new(width: usize, height: usize, cells: &[(usize, usize)]) -> Tunnels {
+        let mut tunnels =
+            Tunnels { width, height, cells: vec![false; width * height], rows: vec![false; height], cols: vec![false; width] };
+        for &(col, row) in cells.iter().filter(|&&(col, row)| col < width && row < height) {
+            tunnels.cells[row * width + col] = true;
+            if col == 0 || col == width - 1 {
+                tunnels.rows[row] = true;
+            }
+            if row == 0 || row == height - 1 {
+                tunnels.cols[col] = true;
+            }
+        }
+        tunnels
+    }
+
+    //whether any row or column joins up
+    pub fn leads_anywhere(&self) -> bool {
+        self.rows.contains(&true) || self.cols.contains(&true)
+    }
+
+    pub fn contains(&self, col: i64, row: i64) -> bool {
+        self.index(col, row).is_some_and(|index| self.cells[index])
+    }
+
+    fn joins_row(&self, row: i64) -> bool {
+        row >= 0 && self.rows.get(row as usize).copied().unwrap_or(false)
+    }
+
+    fn joins_col(&self, col: i64) -> bool {
+        col >= 0 && self.cols.get(col as usize).copied().unwrap_or(false)
+    }
+
+    //a cell past the end of a tunnel as the cell on the far side. Any other stays as it is
+    pub fn wrap_cell(&self, col: i64, row: i64) -> (i64, i64) {
+        let row = if self.joins_col(col) { row.rem_euclid(self.height as i64) } else { row };
+        let col = if self.joins_row(row) { col.rem_euclid(self.width as i64) } else { col };
+        (col, row)
+    }
+
+    fn index(&self, col: i64, row: i64) -> Option<usize> {
+        if col < 0 || row < 0 || col as usize >= self.width || row as usize >= self.height {
+            return None;
+        }
+        Some(row as usize * self.width + col as usize)
+    }
+}
+
+//`delta` or the way round the other side of an axis `span` long, whichever is shorter
+fn shortest(delta: Fixed, span: Fixed) -> Fixed {
+    if delta.abs() * 2 <= span {
+        delta
+    } else if delta > Fixed::ZERO {
+        delta - span
+    } else {
+        delta + span
+    }
+}
+
+//whether `rect` overlaps any wall. Like Rect::overlaps, touching an edge counts
+pub fn hits_wall(walls: &WallGrid, rect: &Rect) -> bool {
+    box_hits_wall(
+        walls,
+        Fixed::from_f32(rect.x),
+        Fixed::from_f32(rect.y),
+        Fixed::from_f32(rect.w),
+        Fixed::from_f32(rect.h),
+    )
+}
+
+//`hits_wall` for a box in the simulation's fixed point
+pub fn box_hits_wall(walls: &WallGrid, x: Fixed, y: Fixed, w: Fixed, h: Fixed) -> bool {
+    let cell = Fixed::from_int(CELL_SIZE as i32);
+    //cells whose closed span [n * CELL_SIZE, (n + 1) * CELL_SIZE] meets the box's
+    let span = |start: Fixed, end: Fixed| ((start - cell).div_ceil(cell) as i64, end.div_floor(cell) as i64);
+    let (first_col, last_col) = span(x, x + w);
+    let (first_row, last_row) = span(y, y + h);
+    (first_row..=last_row).any(|row| (first_col..=last_col).any(|col| walls.is_wall(col, row)))
+}
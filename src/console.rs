@@ -0,0 +1,176 @@
+//the developer console, toggled with the key left of 1. A line is split on whitespace and the
+//first word picks a command from the registry, so built-in debug commands and mod commands are
+//looked up the same way
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+use std::collections::{BTreeMap, VecDeque};
+
+//lines of output kept on screen
+const MAX_LINES: usize = 12;
+const MAX_INPUT: usize = 60;
+//how much of the window the console covers, from the top
+const HEIGHT_FRACTION: f32 = 0.45;
+
+type Handler<T> = Box<dyn Fn(&mut T, &[&str]) -> Result<String, String>>;
+
+struct Command<T> {
+    help: String,
+    handler: Handler<T>,
+}
+
+//commands by name, each run against a `T`
+pub struct CommandRegistry<T> {
+    commands: BTreeMap<String, Command<T>>,
+}
+
+impl<T> Default for CommandRegistry<T> {
+    fn default() -> Self {
+        CommandRegistry { commands: BTreeMap::new() }
+    }
+}
+
+impl<T> CommandRegistry<T> {
+    //a later registration under the same name replaces the earlier one
+    pub fn register<F>(&mut self, name: &str, help: &str, handler: F)
+    where
+        F: Fn(&mut T, &[&str]) -> Result<String, String> + 'static,
+    {
+        let command = Command {
+            help: help.to_string(),
+            handler: Box::new(handler),
+        };
+        self.commands.insert(name.to_lowercase(), command);
+    }
+
+    //runs one line of input against `target`, `help` lists what's registered
+    pub fn run(&self, target: &mut T, line: &str) -> Result<String, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (name, args) = match words.split_first() {
+            Some((name, args)) => (name.to_lowercase(), args),
+            None => return Ok(String::new()),
+        };
+        if name == "help" {
+            let lines: Vec<String> = self
+                .commands
+                .iter()
+                .map(|(name, command)| format!("{:<8}{}", name, command.help))
+                .collect();
+            return Ok(lines.join("\n"));
+        }
+        match self.commands.get(&name) {
+            Some(command) => (command.handler)(target, args),
+            None => Err(format!("Unknown command '{}', try help", name)),
+        }
+    }
+}
+
+//what the caller should do after a key press
+pub enum ConsoleAction {
+    None,
+    Close,
+    Run(String),
+}
+
+pub struct Console {
+    pub open: bool,
+    input: String,
+    //output, oldest first, errors flagged so they draw in red
+    lines: VecDeque<(String, bool)>,
+    //lines entered before, for Up to bring back
+    entered: Vec<String>,
+    recall: usize,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Console {
+            open: false,
+            input: String::new(),
+            lines: VecDeque::new(),
+            entered: Vec::new(),
+            recall: 0,
+        }
+    }
+
+    pub fn handle_key(&mut self, keycode: KeyCode) -> ConsoleAction {
+        match keycode {
+            KeyCode::Escape | KeyCode::Grave => return ConsoleAction::Close,
+            KeyCode::Back => {
+                self.input.pop();
+            }
+            KeyCode::Up if self.recall > 0 => {
+                self.recall -= 1;
+                self.input = self.entered[self.recall].clone();
+            }
+            KeyCode::Down if self.recall < self.entered.len() => {
+                self.recall += 1;
+                self.input = self.entered.get(self.recall).cloned().unwrap_or_default();
+            }
+            KeyCode::Return | KeyCode::NumpadEnter => {
+                let line = std::mem::take(&mut self.input);
+                if line.trim().is_empty() {
+                    return ConsoleAction::None;
+                }
+                self.print(format!("> {}", line), false);
+                self.entered.push(line.clone());
+                self.recall = self.entered.len();
+                return ConsoleAction::Run(line);
+            }
+            _ => {}
+        }
+        ConsoleAction::None
+    }
+
+    pub fn text_input(&mut self, character: char) {
+        //the toggle key types itself too
+        if character.is_control() || character == '`' || character == '~' || self.input.len() >= MAX_INPUT {
+            return;
+        }
+        self.input.push(character);
+    }
+
+    //adds output, one entry per line
+    pub fn print(&mut self, text: String, error: bool) {
+        for line in text.lines() {
+            self.lines.push_back((line.to_string(), error));
+        }
+        while self.lines.len() > MAX_LINES {
+            self.lines.pop_front();
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context) -> GameResult {
+        let (w, h) = graphics::drawable_size(ctx);
+        let height = h * HEIGHT_FRACTION;
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, 0.0, w, height),
+            Color::new(0.0, 0.0, 0.0, 0.85),
+        )?;
+        graphics::draw(ctx, &background, DrawParam::default())?;
+
+        //newest output sits just above the prompt
+        let prompt_y = height - 25.0;
+        let mut y = prompt_y;
+        for (line, error) in self.lines.iter().rev() {
+            y -= 18.0;
+            if y < 5.0 {
+                break;
+            }
+            let color = if *error { Color::RED } else { Color::WHITE };
+            let text = graphics::Text::new(line.as_str());
+            graphics::draw(ctx, &text, DrawParam::default().dest(ggez::mint::Point2 { x: 10.0, y }).color(color))?;
+        }
+
+        let prompt = graphics::Text::new(format!("] {}_", self.input));
+        graphics::draw(
+            ctx,
+            &prompt,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: 10.0, y: prompt_y })
+                .color(Color::YELLOW),
+        )
+    }
+}
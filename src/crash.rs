@@ -0,0 +1,130 @@
+//panic handling: the game keeps a recent snapshot and input history here, and the panic
+//hook writes them to a crash file that the next launch offers to resume from
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::panic;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::paths;
+use crate::replay::InputChange;
+use crate::save::Snapshot;
+use crate::Direction;
+
+//how many of the latest direction changes go into the crash file
+const INPUT_HISTORY: usize = 200;
+pub const ISSUES_URL: &str = "https://github.com/riyazrabbani/Rust-Pacman/issues";
+
+#[derive(Serialize, Deserialize)]
+pub struct CrashReport {
+    pub game_version: String,
+    pub time: String,
+    pub message: String,
+    pub seed: u64,
+    pub tick: u32,
+    pub recent_inputs: Vec<InputChange>,
+    //None when the crash happened outside of a game
+    pub snapshot: Option<Snapshot>,
+}
+
+struct CrashState {
+    seed: u64,
+    tick: u32,
+    snapshot: Option<Snapshot>,
+    inputs: VecDeque<InputChange>,
+}
+
+static STATE: Mutex<CrashState> = Mutex::new(CrashState {
+    seed: 0,
+    tick: 0,
+    snapshot: None,
+    inputs: VecDeque::new(),
+});
+
+fn crash_file() -> PathBuf {
+    paths::data_dir().join("crash.json")
+}
+
+//keeps the default message on stderr and adds the crash file
+pub fn install_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        //the lock may be held by the panicking thread, in which case there is nothing safe to dump
+        let mut state = match STATE.try_lock() {
+            Ok(state) => state,
+            Err(_) => return,
+        };
+        let report = CrashReport {
+            game_version: env!("CARGO_PKG_VERSION").to_string(),
+            time: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            message: info.to_string(),
+            seed: state.seed,
+            tick: state.tick,
+            recent_inputs: state.inputs.iter().copied().collect(),
+            snapshot: state.snapshot.take(),
+        };
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => match fs::write(crash_file(), json) {
+                Ok(()) => eprintln!("Crash details written to {}", crash_file().display()),
+                Err(e) => eprintln!("Could not write crash file: {}", e),
+            },
+            Err(e) => eprintln!("Could not serialize crash details: {}", e),
+        }
+    }));
+}
+
+//the latest resumable state of the running game
+pub fn record_state(seed: u64, tick: u32, snapshot: Snapshot) {
+    if let Ok(mut state) = STATE.lock() {
+        state.seed = seed;
+        state.tick = tick;
+        state.snapshot = Some(snapshot);
+    }
+}
+
+pub fn record_input(tick: u32, direction: Direction) {
+    if let Ok(mut state) = STATE.lock() {
+        if state.inputs.back().is_none_or(|last| last.direction != direction) {
+            if state.inputs.len() == INPUT_HISTORY {
+                state.inputs.pop_front();
+            }
+            state.inputs.push_back(InputChange { tick, direction });
+        }
+    }
+}
+
+//nothing to resume once a game has ended
+pub fn clear_state() {
+    if let Ok(mut state) = STATE.lock() {
+        state.snapshot = None;
+        state.inputs.clear();
+    }
+}
+
+//the crash left behind by the previous run, if any
+pub fn pending_report() -> Option<CrashReport> {
+    let contents = fs::read_to_string(crash_file()).ok()?;
+    match serde_json::from_str(&contents) {
+        Ok(report) => Some(report),
+        Err(e) => {
+            eprintln!("Ignoring unreadable crash file: {}", e);
+            let _ = fs::remove_file(crash_file());
+            None
+        }
+    }
+}
+
+pub fn discard_report() {
+    let _ = fs::remove_file(crash_file());
+}
+
+//moves the crash file somewhere it won't be offered again and returns its new path
+pub fn keep_report() -> Result<PathBuf, String> {
+    let dir = paths::data_dir().join("crash-reports");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("crash-{}.json", chrono::Local::now().format("%Y-%m-%d_%H-%M-%S")));
+    fs::rename(crash_file(), &path).map_err(|e| e.to_string())?;
+    Ok(path)
+}
@@ -0,0 +1,171 @@
+//shown at launch after a crash, offering to resume the game or keep the crash report
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+
+use crate::crash::{self, CrashReport};
+use crate::save::Snapshot;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Item {
+    Resume,
+    Report,
+    Dismiss,
+}
+
+//what the caller should do after a key press
+pub enum CrashAction {
+    None,
+    Resume(Box<Snapshot>),
+    Close,
+}
+
+pub struct CrashDialog {
+    report: CrashReport,
+    items: Vec<(Item, &'static str)>,
+    selected: usize,
+    message: Option<String>,
+}
+
+impl CrashDialog {
+    pub fn new(report: CrashReport) -> Self {
+        let mut items = Vec::new();
+        if report.snapshot.is_some() {
+            items.push((Item::Resume, "Resume game"));
+        }
+        items.push((Item::Report, "Keep crash report"));
+        items.push((Item::Dismiss, "Dismiss"));
+        CrashDialog {
+            report,
+            items,
+            selected: 0,
+            message: None,
+        }
+    }
+
+    pub fn handle_key(&mut self, keycode: KeyCode) -> CrashAction {
+        let count = self.items.len();
+        match keycode {
+            KeyCode::Up => self.selected = (self.selected + count - 1) % count,
+            KeyCode::Down => self.selected = (self.selected + 1) % count,
+            KeyCode::Escape => {
+                crash::discard_report();
+                return CrashAction::Close;
+            }
+            KeyCode::Return => match self.items[self.selected].0 {
+                Item::Resume => {
+                    crash::discard_report();
+                    if let Some(snapshot) = self.report.snapshot.take() {
+                        return CrashAction::Resume(Box::new(snapshot));
+                    }
+                    return CrashAction::Close;
+                }
+                Item::Report => {
+                    self.message = Some(match crash::keep_report() {
+                        Ok(path) => format!("Saved to {}\nPlease attach it to an issue at {}", path.display(), crash::ISSUES_URL),
+                        Err(e) => format!("Could not keep the report: {}", e),
+                    });
+                    //the file has moved, so there is nothing left to report from here
+                    self.items.retain(|&(item, _)| item != Item::Report);
+                    self.selected = 0;
+                }
+                Item::Dismiss => {
+                    crash::discard_report();
+                    return CrashAction::Close;
+                }
+            },
+            _ => {}
+        }
+        CrashAction::None
+    }
+
+    pub fn draw(&self, ctx: &mut Context) -> GameResult {
+        let (w, h) = graphics::drawable_size(ctx);
+
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, 0.0, w, h),
+            Color::new(0.0, 0.0, 0.0, 0.85),
+        )?;
+        graphics::draw(ctx, &background, DrawParam::default())?;
+
+        let menu_width = 440.0;
+        let menu_height = 300.0;
+        let menu_x = (w - menu_width) / 2.0;
+        let menu_y = (h - menu_height) / 2.0;
+        let menu_bg = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(menu_x, menu_y, menu_width, menu_height),
+            Color::new(0.2, 0.2, 0.2, 1.0),
+        )?;
+        graphics::draw(ctx, &menu_bg, DrawParam::default())?;
+
+        let title = graphics::Text::new("Sorry, the game crashed");
+        let title_dims = title.dimensions(ctx);
+        graphics::draw(
+            ctx,
+            &title,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 {
+                    x: menu_x + (menu_width - title_dims.w * 1.5) / 2.0,
+                    y: menu_y + 20.0,
+                })
+                .color(Color::RED)
+                .scale([1.5, 1.5]),
+        )?;
+
+        let details = match &self.report.snapshot {
+            Some(snapshot) => format!(
+                "Last time ({}) with {} points and {} lives left.",
+                self.report.time, snapshot.score, snapshot.lives
+            ),
+            None => format!("Last time ({}), outside of a game.", self.report.time),
+        };
+        let mut details_text = graphics::Text::new(details);
+        details_text.set_bounds(
+            ggez::mint::Point2 { x: menu_width - 40.0, y: f32::INFINITY },
+            graphics::Align::Center,
+        );
+        graphics::draw(
+            ctx,
+            &details_text,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: menu_x + 20.0, y: menu_y + 60.0 })
+                .color(Color::WHITE),
+        )?;
+
+        for (i, &(_, label)) in self.items.iter().enumerate() {
+            let text = graphics::Text::new(label);
+            let dims = text.dimensions(ctx);
+            let color = if i == self.selected { Color::YELLOW } else { Color::WHITE };
+            graphics::draw(
+                ctx,
+                &text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 {
+                        x: menu_x + (menu_width - dims.w) / 2.0,
+                        y: menu_y + 110.0 + i as f32 * 35.0,
+                    })
+                    .color(color),
+            )?;
+        }
+
+        if let Some(message) = &self.message {
+            let mut text = graphics::Text::new(message.as_str());
+            text.set_bounds(
+                ggez::mint::Point2 { x: menu_width - 40.0, y: f32::INFINITY },
+                graphics::Align::Center,
+            );
+            graphics::draw(
+                ctx,
+                &text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 { x: menu_x + 20.0, y: menu_y + 220.0 })
+                    .color(Color::GREEN),
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+//cutscenes as timelines: cues at fixed ticks from the start, played at the simulation's tick rate
+//so they last the same whatever the frame rate. What a cue looks like is up to the scene that
+//draws it, the timeline only says which ones have come up
+pub struct Timeline<C> {
+    //in tick order
+    cues: Vec<(u32, C)>,
+    length: u32,
+    tick: u32,
+}
+
+impl<C> Timeline<C> {
+    //a timeline that ends `length` ticks in
+    pub fn new(length: u32) -> Self {
+        Timeline {
+            cues: Vec::new(),
+            length,
+            tick: 0,
+        }
+    }
+
+    //adds `cue` at `tick`; cues on the same tick come up in the order they were added
+    pub fn cue(mut self, tick: u32, cue: C) -> Self {
+        let index = self.cues.partition_point(|&(at, _)| at <= tick);
+        self.cues.insert(index, (tick, cue));
+        self
+    }
+
+    pub fn advance(&mut self) {
+        self.tick = (self.tick + 1).min(self.length);
+    }
+
+    //the cues that have come up so far, each with how many ticks ago
+    pub fn fired(&self) -> impl Iterator<Item = (&C, u32)> {
+        let now = self.tick;
+        self.cues
+            .iter()
+            .take_while(move |&&(at, _)| at <= now)
+            .map(move |(at, cue)| (cue, now - at))
+    }
+
+    pub fn finished(&self) -> bool {
+        self.tick >= self.length
+    }
+}
@@ -0,0 +1,95 @@
+//the dots left in the maze, bucketed by the cell they sit in so pacman only ever checks the few
+//cells he touches, however many dots a maze has
+use ggez::mint::Point2;
+
+use crate::fixed::Fixed;
+use crate::CELL_SIZE;
+
+pub struct DotGrid {
+    width: usize,
+    height: usize,
+    //row-major, so iterating visits dots in the order a map lists them
+    cells: Vec<Vec<Point2<f32>>>,
+    count: usize,
+}
+
+impl DotGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        DotGrid { width, height, cells: vec![Vec::new(); width * height], count: 0 }
+    }
+
+    fn cell_index(&self, col: i64, row: i64) -> Option<usize> {
+        if col < 0 || row < 0 || col as usize >= self.width || row as usize >= self.height {
+            return None;
+        }
+        Some(row as usize * self.width + col as usize)
+    }
+
+    //dots outside the grid can never be reached, so they are dropped
+    pub fn push(&mut self, dot: Point2<f32>) {
+        let col = (dot.x / CELL_SIZE).floor() as i64;
+        let row = (dot.y / CELL_SIZE).floor() as i64;
+        if let Some(index) = self.cell_index(col, row) {
+            self.cells[index].push(dot);
+            self.count += 1;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+        self.count = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn iter(&self) -> std::iter::Flatten<std::slice::Iter<'_, Vec<Point2<f32>>>> {
+        self.cells.iter().flatten()
+    }
+
+    //removes every dot closer than `reach` to `center`, handing each to `eaten`. Only the cells
+    //the circle overlaps are looked at, at most four when `reach` is under half a cell
+    pub fn eat_within(&mut self, center: (Fixed, Fixed), reach: Fixed, mut eaten: impl FnMut(Point2<f32>)) {
+        let cell = Fixed::from_int(CELL_SIZE as i32);
+        let first_col = (center.0 - reach).div_floor(cell) as i64;
+        let last_col = (center.0 + reach).div_floor(cell) as i64;
+        let first_row = (center.1 - reach).div_floor(cell) as i64;
+        let last_row = (center.1 + reach).div_floor(cell) as i64;
+        for row in first_row..=last_row {
+            for col in first_col..=last_col {
+                let index = match self.cell_index(col, row) {
+                    Some(index) => index,
+                    None => continue,
+                };
+                let before = self.cells[index].len();
+                self.cells[index].retain(|dot| {
+                    let distance_squared =
+                        (Fixed::from_f32(dot.x) - center.0).squared() + (Fixed::from_f32(dot.y) - center.1).squared();
+                    if distance_squared < reach.squared() {
+                        eaten(*dot);
+                        false
+                    } else {
+                        true
+                    }
+                });
+                self.count -= before - self.cells[index].len();
+            }
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a DotGrid {
+    type Item = &'a Point2<f32>;
+    type IntoIter = std::iter::Flatten<std::slice::Iter<'a, Vec<Point2<f32>>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -0,0 +1,212 @@
+//short-lived visual effects: particle bursts, floating scores and toasts. They only decorate the
+//game, so they roll their own rng instead of the game's and never feed back into a tick
+use ggez::graphics::{self, spritebatch::SpriteBatch, Color, DrawParam, Image, Text};
+use ggez::{Context, GameResult};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use std::time::Instant;
+
+use crate::memento::TOAST_DURATION;
+use crate::pool::{Pool, PoolStats};
+use crate::sprites;
+
+const PARTICLE_CAPACITY: usize = 256;
+const TEXT_CAPACITY: usize = 16;
+const TOAST_CAPACITY: usize = 4;
+//seconds each lasts
+const PARTICLE_LIFE: f32 = 0.6;
+const TEXT_LIFE: f32 = 1.0;
+const PARTICLE_SIZE: f32 = 4.0;
+//pixels per second
+const PARTICLE_SPEED: f32 = 90.0;
+const TEXT_RISE: f32 = 30.0;
+
+#[derive(Default)]
+struct Particle {
+    x: f32,
+    y: f32,
+    vx: f32,
+    vy: f32,
+    age: f32,
+    color: (f32, f32, f32),
+}
+
+#[derive(Default)]
+struct FloatingText {
+    x: f32,
+    y: f32,
+    age: f32,
+    color: (f32, f32, f32),
+    //the text is only laid out again when a slot is reused for a different number
+    value: u32,
+    text: Option<Text>,
+}
+
+#[derive(Default)]
+struct Toast {
+    message: String,
+    text: Option<Text>,
+    shown_at: Option<Instant>,
+}
+
+pub struct Effects {
+    particles: Pool<Particle>,
+    texts: Pool<FloatingText>,
+    toasts: Pool<Toast>,
+    rng: Pcg32,
+}
+
+impl Effects {
+    pub fn new() -> Self {
+        Effects {
+            particles: Pool::new(PARTICLE_CAPACITY),
+            texts: Pool::new(TEXT_CAPACITY),
+            toasts: Pool::new(TOAST_CAPACITY),
+            rng: Pcg32::seed_from_u64(0),
+        }
+    }
+
+    //`count` particles flying out from (x, y)
+    pub fn burst(&mut self, x: f32, y: f32, color: Color, count: usize) {
+        for _ in 0..count {
+            let angle = self.rng.gen_range(0.0..std::f32::consts::TAU);
+            let speed = PARTICLE_SPEED * self.rng.gen_range(0.5..1.0);
+            self.particles.spawn(|particle| {
+                *particle = Particle {
+                    x,
+                    y,
+                    vx: angle.cos() * speed,
+                    vy: angle.sin() * speed,
+                    age: 0.0,
+                    color: (color.r, color.g, color.b),
+                };
+            });
+        }
+    }
+
+    //points earned, rising from (x, y) in `color`
+    pub fn score_popup(&mut self, x: f32, y: f32, value: u32, color: Color) {
+        self.texts.spawn(|popup| {
+            popup.x = x;
+            popup.y = y;
+            popup.age = 0.0;
+            popup.color = (color.r, color.g, color.b);
+            if popup.text.is_none() || popup.value != value {
+                popup.value = value;
+                popup.text = Some(Text::new(value.to_string()));
+            }
+        });
+    }
+
+    //a short notice at the bottom of the screen
+    pub fn toast(&mut self, message: &str) {
+        self.toasts.spawn(|toast| {
+            if toast.text.is_none() || toast.message != message {
+                toast.message.clear();
+                toast.message.push_str(message);
+                toast.text = Some(Text::new(message));
+            }
+            toast.shown_at = Some(Instant::now());
+        });
+    }
+
+    //advances the particles and popups by one tick
+    pub fn tick(&mut self, dt: f32) {
+        self.particles.retain(|particle| {
+            particle.age += dt;
+            particle.x += particle.vx * dt;
+            particle.y += particle.vy * dt;
+            particle.age < PARTICLE_LIFE
+        });
+        self.texts.retain(|popup| {
+            popup.age += dt;
+            popup.y -= TEXT_RISE * dt;
+            popup.age < TEXT_LIFE
+        });
+    }
+
+    //a new game starts without the last one's effects
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.texts.clear();
+    }
+
+    //particles and popups, drawn over the board
+    pub fn draw_world(&self, ctx: &mut Context, circle: &Image) -> GameResult {
+        if self.particles.stats().live > 0 {
+            let mut batch = SpriteBatch::new(circle.clone());
+            for particle in self.particles.iter() {
+                let (r, g, b) = particle.color;
+                let alpha = 1.0 - particle.age / PARTICLE_LIFE;
+                let half = PARTICLE_SIZE / 2.0;
+                batch.add(sprites::circle_param(particle.x - half, particle.y - half, PARTICLE_SIZE, Color::new(r, g, b, alpha)));
+            }
+            graphics::draw(ctx, &batch, DrawParam::default())?;
+        }
+        for popup in self.texts.iter() {
+            if let Some(text) = &popup.text {
+                let dims = text.dimensions(ctx);
+                let alpha = 1.0 - popup.age / TEXT_LIFE;
+                let (r, g, b) = popup.color;
+                graphics::draw(
+                    ctx,
+                    text,
+                    DrawParam::default()
+                        .dest(ggez::mint::Point2 { x: popup.x - dims.w / 2.0, y: popup.y - dims.h / 2.0 })
+                        .color(Color::new(r, g, b, alpha)),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    //the newest toast still showing
+    pub fn draw_toasts(&mut self, ctx: &mut Context) -> GameResult {
+        self.toasts
+            .retain(|toast| toast.shown_at.is_some_and(|at| at.elapsed().as_secs_f32() < TOAST_DURATION));
+        let toast = match self.toasts.iter().max_by_key(|toast| toast.shown_at) {
+            Some(toast) => toast,
+            None => return Ok(()),
+        };
+        let toast_text = match &toast.text {
+            Some(text) => text,
+            None => return Ok(()),
+        };
+        let (w, h) = graphics::drawable_size(ctx);
+        let toast_dims = toast_text.dimensions(ctx);
+        let toast_bg = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(
+                (w - toast_dims.w) / 2.0 - 10.0,
+                h - toast_dims.h - 30.0,
+                toast_dims.w + 20.0,
+                toast_dims.h + 10.0,
+            ),
+            Color::new(0.2, 0.2, 0.2, 0.9),
+        )?;
+        graphics::draw(ctx, &toast_bg, DrawParam::default())?;
+        graphics::draw(
+            ctx,
+            toast_text,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: (w - toast_dims.w) / 2.0, y: h - toast_dims.h - 25.0 })
+                .color(Color::GREEN),
+        )
+    }
+
+    //pool usage for the debug overlay
+    pub fn stats_text(&self) -> String {
+        let line = |name: &str, stats: PoolStats| {
+            format!(
+                "{:<10}{:>4}/{:<4} peak {:<4} slots {:<4} dropped {}\n",
+                name, stats.live, stats.capacity, stats.peak, stats.allocated, stats.dropped
+            )
+        };
+        let mut text = String::from("effect pools\n");
+        text.push_str(&line("particles", self.particles.stats()));
+        text.push_str(&line("popups", self.texts.stats()));
+        text.push_str(&line("toasts", self.toasts.stats()));
+        text
+    }
+}
@@ -0,0 +1,501 @@
+//extra items and hazards that live in the maze next to the dots and ghosts. New kinds
+//implement `EntityPlugin` and register a factory under a name, after which mods can
+//spawn them by that name without MainState knowing anything about them
+use ggez::graphics::{self, Color, DrawParam, Rect};
+use ggez::{Context, GameResult};
+use rand::Rng;
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+use crate::collision::WallGrid;
+use crate::inventory::PowerUp;
+use crate::CELL_SIZE;
+
+const ITEM_SIZE: f32 = 14.0;
+const HAZARD_SIZE: f32 = 18.0;
+//items that aren't picked up in time disappear
+const ITEM_LIFETIME: f32 = 10.0;
+const FRUIT_SIZE: f32 = 16.0;
+//a fruit sitting still is gone a little sooner than other items, as in the arcade
+const FRUIT_LIFETIME: f32 = 9.5;
+//pixels a second a wandering fruit covers, and the cells it visits before it leaves
+const FRUIT_SPEED: f32 = 60.0;
+const FRUIT_WAYPOINTS: usize = 30;
+
+//what an entity sees of the game when it updates
+pub struct World<'a> {
+    pub dt: f32,
+    pub walls: &'a WallGrid,
+    pub rng: &'a mut Pcg32,
+}
+
+//what touching an entity does to pacman
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EntityEffect {
+    AddScore(u32),
+    LoseLife,
+    SlowPacman(f32),
+    //goes into pacman's inventory for later
+    Store(PowerUp),
+    //scores the fruit's points and goes into the HUD's fruit history
+    EatFruit(FruitKind),
+}
+
+pub trait EntityPlugin {
+    fn update(&mut self, world: &mut World);
+    fn draw(&self, ctx: &mut Context) -> GameResult;
+    //called every tick pacman overlaps the entity's bounds
+    fn collide(&mut self) -> Option<EntityEffect>;
+    fn bounds(&self) -> Rect;
+    //entities that return false are removed after the tick
+    fn alive(&self) -> bool;
+    //what a snapshot keeps of the entity, for saves, crash dumps and versus rollbacks. Kinds
+    //that return None are left out and gone after a restore
+    fn save(&self) -> Option<SavedEntity> {
+        None
+    }
+}
+
+//a live built-in entity as a snapshot stores it, with everything it needs to carry on exactly
+//where it was. Colors are (r, g, b, a)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SavedEntity {
+    Pickup { x: f32, y: f32, color: (f32, f32, f32, f32), points: u32, time_left: f32 },
+    PowerUp { x: f32, y: f32, item: PowerUp, time_left: f32 },
+    Hazard { x: f32, y: f32, color: (f32, f32, f32, f32), effect: EntityEffect },
+    Fruit { x: f32, y: f32, kind: FruitKind, wander: bool, route: Option<VecDeque<(f32, f32)>>, time_left: f32 },
+}
+
+impl SavedEntity {
+    pub fn restore(&self) -> Box<dyn EntityPlugin> {
+        let color = |(r, g, b, a): (f32, f32, f32, f32)| Color::new(r, g, b, a);
+        match self.clone() {
+            SavedEntity::Pickup { x, y, color: rgba, points, time_left } => {
+                Box::new(Pickup { time_left, ..Pickup::new(x, y, color(rgba), points) })
+            }
+            SavedEntity::PowerUp { x, y, item, time_left } => {
+                Box::new(PowerUpItem { time_left, ..PowerUpItem::new(x, y, item) })
+            }
+            SavedEntity::Hazard { x, y, color: rgba, effect } => Box::new(Hazard::new(x, y, color(rgba), effect)),
+            SavedEntity::Fruit { x, y, kind, wander, route, time_left } => {
+                Box::new(Fruit { route, time_left, ..Fruit::new(x, y, kind, wander) })
+            }
+        }
+    }
+}
+
+type Factory = Box<dyn Fn(f32, f32) -> Box<dyn EntityPlugin>>;
+
+//named entity kinds that can be spawned at a pixel position
+pub struct EntityRegistry {
+    factories: HashMap<String, Factory>,
+}
+
+impl EntityRegistry {
+    pub fn new() -> EntityRegistry {
+        let mut registry = EntityRegistry { factories: HashMap::new() };
+        registry.register("bonus", |x, y| Box::new(Pickup::new(x, y, Color::new(1.0, 0.4, 0.7, 1.0), 100)));
+        registry.register("trap", |x, y| Box::new(Hazard::new(x, y, Color::RED, EntityEffect::LoseLife)));
+        registry.register("fruit", |x, y| Box::new(Fruit::new(x, y, FruitKind::Cherry, false)));
+        registry.register("bomb", |x, y| Box::new(PowerUpItem::new(x, y, PowerUp::Bomb)));
+        registry.register("freeze", |x, y| Box::new(PowerUpItem::new(x, y, PowerUp::Freeze)));
+        registry
+    }
+
+    //a kind registered again replaces the earlier one
+    pub fn register<F>(&mut self, name: &str, factory: F)
+    where
+        F: Fn(f32, f32) -> Box<dyn EntityPlugin> + 'static,
+    {
+        self.factories.insert(name.to_string(), Box::new(factory));
+    }
+
+    //spawns `name` centered in a grid cell
+    pub fn spawn(&self, name: &str, col: usize, row: usize) -> Option<Box<dyn EntityPlugin>> {
+        let factory = self.factories.get(name)?;
+        Some(factory(
+            col as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+            row as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+        ))
+    }
+}
+
+//collectible that gives points once and expires after a while
+pub struct Pickup {
+    x: f32,
+    y: f32,
+    color: Color,
+    points: u32,
+    time_left: f32,
+    collected: bool,
+}
+
+impl Pickup {
+    pub fn new(x: f32, y: f32, color: Color, points: u32) -> Pickup {
+        Pickup { x, y, color, points, time_left: ITEM_LIFETIME, collected: false }
+    }
+}
+
+impl EntityPlugin for Pickup {
+    fn update(&mut self, world: &mut World) {
+        self.time_left -= world.dt;
+    }
+
+    fn draw(&self, ctx: &mut Context) -> GameResult {
+        let mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), self.bounds(), self.color)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+
+    fn collide(&mut self) -> Option<EntityEffect> {
+        if self.collected {
+            return None;
+        }
+        self.collected = true;
+        Some(EntityEffect::AddScore(self.points))
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect::new(self.x - ITEM_SIZE / 2.0, self.y - ITEM_SIZE / 2.0, ITEM_SIZE, ITEM_SIZE)
+    }
+
+    fn alive(&self) -> bool {
+        !self.collected && self.time_left > 0.0
+    }
+
+    fn save(&self) -> Option<SavedEntity> {
+        let color = self.color;
+        Some(SavedEntity::Pickup {
+            x: self.x,
+            y: self.y,
+            color: (color.r, color.g, color.b, color.a),
+            points: self.points,
+            time_left: self.time_left,
+        })
+    }
+}
+
+//a power-up lying in the maze, kept for later once pacman picks it up. Expires like a pickup
+pub struct PowerUpItem {
+    x: f32,
+    y: f32,
+    item: PowerUp,
+    time_left: f32,
+    collected: bool,
+}
+
+impl PowerUpItem {
+    pub fn new(x: f32, y: f32, item: PowerUp) -> PowerUpItem {
+        PowerUpItem { x, y, item, time_left: ITEM_LIFETIME, collected: false }
+    }
+}
+
+impl EntityPlugin for PowerUpItem {
+    fn update(&mut self, world: &mut World) {
+        self.time_left -= world.dt;
+    }
+
+    fn draw(&self, ctx: &mut Context) -> GameResult {
+        let mesh = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::fill(),
+            ggez::mint::Point2 { x: self.x, y: self.y },
+            ITEM_SIZE / 2.0,
+            0.1,
+            self.item.color(),
+        )?;
+        graphics::draw(ctx, &mesh, DrawParam::default())?;
+        let ring = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::stroke(2.0),
+            ggez::mint::Point2 { x: self.x, y: self.y },
+            ITEM_SIZE / 2.0 + 2.0,
+            0.1,
+            Color::WHITE,
+        )?;
+        graphics::draw(ctx, &ring, DrawParam::default())
+    }
+
+    fn collide(&mut self) -> Option<EntityEffect> {
+        if self.collected {
+            return None;
+        }
+        self.collected = true;
+        Some(EntityEffect::Store(self.item))
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect::new(self.x - ITEM_SIZE / 2.0, self.y - ITEM_SIZE / 2.0, ITEM_SIZE, ITEM_SIZE)
+    }
+
+    fn alive(&self) -> bool {
+        !self.collected && self.time_left > 0.0
+    }
+
+    fn save(&self) -> Option<SavedEntity> {
+        Some(SavedEntity::PowerUp { x: self.x, y: self.y, item: self.item, time_left: self.time_left })
+    }
+}
+
+//stationary hazard that applies its effect once and is then used up
+pub struct Hazard {
+    x: f32,
+    y: f32,
+    color: Color,
+    effect: EntityEffect,
+    triggered: bool,
+}
+
+impl Hazard {
+    pub fn new(x: f32, y: f32, color: Color, effect: EntityEffect) -> Hazard {
+        Hazard { x, y, color, effect, triggered: false }
+    }
+}
+
+impl EntityPlugin for Hazard {
+    fn update(&mut self, _world: &mut World) {}
+
+    fn draw(&self, ctx: &mut Context) -> GameResult {
+        let half = HAZARD_SIZE / 2.0;
+        let points = [
+            ggez::mint::Point2 { x: self.x, y: self.y - half },
+            ggez::mint::Point2 { x: self.x + half, y: self.y + half },
+            ggez::mint::Point2 { x: self.x - half, y: self.y + half },
+        ];
+        let mesh = graphics::Mesh::new_polygon(ctx, graphics::DrawMode::fill(), &points, self.color)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+
+    fn collide(&mut self) -> Option<EntityEffect> {
+        if self.triggered {
+            return None;
+        }
+        self.triggered = true;
+        Some(self.effect)
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect::new(self.x - HAZARD_SIZE / 2.0, self.y - HAZARD_SIZE / 2.0, HAZARD_SIZE, HAZARD_SIZE)
+    }
+
+    fn alive(&self) -> bool {
+        !self.triggered
+    }
+
+    fn save(&self) -> Option<SavedEntity> {
+        let color = self.color;
+        Some(SavedEntity::Hazard {
+            x: self.x,
+            y: self.y,
+            color: (color.r, color.g, color.b, color.a),
+            effect: self.effect,
+        })
+    }
+}
+
+//the arcade's bonus fruit, one kind a level and worth more the further in the level is
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum FruitKind {
+    Cherry,
+    Strawberry,
+    Orange,
+    Apple,
+    Melon,
+    Galaxian,
+    Bell,
+    Key,
+}
+
+impl FruitKind {
+    //cherry on the first level, then a new kind every level or two, and keys from the 13th on
+    pub fn for_level(level: u32) -> FruitKind {
+        match level {
+            0..=1 => FruitKind::Cherry,
+            2 => FruitKind::Strawberry,
+            3..=4 => FruitKind::Orange,
+            5..=6 => FruitKind::Apple,
+            7..=8 => FruitKind::Melon,
+            9..=10 => FruitKind::Galaxian,
+            11..=12 => FruitKind::Bell,
+            _ => FruitKind::Key,
+        }
+    }
+
+    pub fn points(self) -> u32 {
+        match self {
+            FruitKind::Cherry => 100,
+            FruitKind::Strawberry => 300,
+            FruitKind::Orange => 500,
+            FruitKind::Apple => 700,
+            FruitKind::Melon => 1000,
+            FruitKind::Galaxian => 2000,
+            FruitKind::Bell => 3000,
+            FruitKind::Key => 5000,
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            FruitKind::Cherry | FruitKind::Apple => Color::RED,
+            FruitKind::Strawberry => Color::new(1.0, 0.2, 0.4, 1.0),
+            FruitKind::Orange => Color::new(1.0, 0.6, 0.0, 1.0),
+            FruitKind::Melon => Color::new(0.4, 0.9, 0.3, 1.0),
+            FruitKind::Galaxian => Color::new(0.2, 0.4, 1.0, 1.0),
+            FruitKind::Bell => Color::YELLOW,
+            FruitKind::Key => Color::new(0.4, 0.9, 1.0, 1.0),
+        }
+    }
+}
+
+//a round body in the kind's colour on a green stalk, centered on (x, y) and `size` across. The
+//maze and the HUD's fruit history both draw fruit this way
+pub fn draw_fruit(ctx: &mut Context, kind: FruitKind, x: f32, y: f32, size: f32) -> GameResult {
+    let radius = size / 2.0;
+    let body = graphics::Mesh::new_circle(
+        ctx,
+        graphics::DrawMode::fill(),
+        ggez::mint::Point2 { x, y: y + 2.0 },
+        radius - 2.0,
+        0.1,
+        kind.color(),
+    )?;
+    graphics::draw(ctx, &body, DrawParam::default())?;
+    let stalk = graphics::Mesh::new_line(
+        ctx,
+        &[
+            ggez::mint::Point2 { x, y: y - radius + 6.0 },
+            ggez::mint::Point2 { x: x + 4.0, y: y - radius },
+        ],
+        2.0,
+        Color::GREEN,
+    )?;
+    graphics::draw(ctx, &stalk, DrawParam::default())
+}
+
+//bonus fruit. It either sits still until it times out, or wanders a random route through the
+//maze, Ms. Pac-Man style, and leaves at the end of it
+pub struct Fruit {
+    x: f32,
+    y: f32,
+    kind: FruitKind,
+    wander: bool,
+    //cell centers still to visit, planned on the first update from the game's rng
+    route: Option<VecDeque<(f32, f32)>>,
+    time_left: f32,
+    collected: bool,
+}
+
+impl Fruit {
+    pub fn new(x: f32, y: f32, kind: FruitKind, wander: bool) -> Fruit {
+        Fruit { x, y, kind, wander, route: None, time_left: FRUIT_LIFETIME, collected: false }
+    }
+
+    //on a wrapping maze, or through a tunnel, the route runs on past the edges, so once the fruit
+    //crosses one it and the rest of its route are moved in from the far side
+    fn wrap(&mut self, walls: &WallGrid) {
+        let (dx, dy) = walls.wrap_shift(self.x, self.y);
+        if dx == 0.0 && dy == 0.0 {
+            return;
+        }
+        self.x += dx;
+        self.y += dy;
+        for point in self.route.iter_mut().flatten() {
+            point.0 += dx;
+            point.1 += dy;
+        }
+    }
+
+    //a walk from the fruit's cell that never turns back unless it hits a dead end
+    fn plan_route(&self, walls: &WallGrid, rng: &mut Pcg32) -> VecDeque<(f32, f32)> {
+        let mut cell = ((self.x / CELL_SIZE) as i64, (self.y / CELL_SIZE) as i64);
+        let mut previous = cell;
+        let mut route = VecDeque::with_capacity(FRUIT_WAYPOINTS);
+        for _ in 0..FRUIT_WAYPOINTS {
+            let open: Vec<(i64, i64)> = [(0, -1), (0, 1), (-1, 0), (1, 0)]
+                .iter()
+                .map(|&(dx, dy)| (cell.0 + dx, cell.1 + dy))
+                .filter(|&(col, row)| !walls.is_wall(col, row))
+                .collect();
+            let onward: Vec<(i64, i64)> = open.iter().copied().filter(|&next| next != previous).collect();
+            let choices = if onward.is_empty() { open } else { onward };
+            if choices.is_empty() {
+                break;
+            }
+            previous = cell;
+            cell = choices[rng.gen_range(0..choices.len())];
+            route.push_back((
+                cell.0 as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+                cell.1 as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+            ));
+            //through a gap in the edge of the maze, and gone
+            if !walls.contains(cell.0, cell.1) {
+                break;
+            }
+        }
+        route
+    }
+}
+
+impl EntityPlugin for Fruit {
+    fn update(&mut self, world: &mut World) {
+        if !self.wander {
+            self.time_left -= world.dt;
+            return;
+        }
+        if self.route.is_none() {
+            self.route = Some(self.plan_route(world.walls, world.rng));
+        }
+        self.wrap(world.walls);
+        let route = match &mut self.route {
+            Some(route) => route,
+            None => return,
+        };
+        //heads straight for the next cell center; consecutive ones are always neighbours
+        let mut travel = FRUIT_SPEED * world.dt;
+        while let Some(&(x, y)) = route.front() {
+            let distance = ((x - self.x).powi(2) + (y - self.y).powi(2)).sqrt();
+            if distance > travel {
+                self.x += (x - self.x) / distance * travel;
+                self.y += (y - self.y) / distance * travel;
+                return;
+            }
+            self.x = x;
+            self.y = y;
+            travel -= distance;
+            route.pop_front();
+        }
+        //the route is walked, so the fruit leaves
+        self.time_left = 0.0;
+    }
+
+    fn draw(&self, ctx: &mut Context) -> GameResult {
+        draw_fruit(ctx, self.kind, self.x, self.y, FRUIT_SIZE)
+    }
+
+    fn collide(&mut self) -> Option<EntityEffect> {
+        if self.collected {
+            return None;
+        }
+        self.collected = true;
+        Some(EntityEffect::EatFruit(self.kind))
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect::new(self.x - FRUIT_SIZE / 2.0, self.y - FRUIT_SIZE / 2.0, FRUIT_SIZE, FRUIT_SIZE)
+    }
+
+    fn alive(&self) -> bool {
+        !self.collected && self.time_left > 0.0
+    }
+
+    fn save(&self) -> Option<SavedEntity> {
+        Some(SavedEntity::Fruit {
+            x: self.x,
+            y: self.y,
+            kind: self.kind,
+            wander: self.wander,
+            route: self.route.clone(),
+            time_left: self.time_left,
+        })
+    }
+}
@@ -0,0 +1,169 @@
+//gym-style environment over the headless simulation, so the game can be used as a
+//reinforcement-learning benchmark:
+//
+//    let mut env = PacmanEnv::new()?;
+//    let mut obs = env.reset(42);
+//    loop {
+//        let (next, reward, done) = env.step(Direction::Left);
+//        if done { break; }
+//        obs = next;
+//    }
+use ggez::GameResult;
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+
+use crate::map::Map;
+use crate::observer::Observer;
+use crate::raster::{self, Frame};
+use crate::settings::Settings;
+use crate::stats::GameStats;
+use crate::{Direction, GameScreen, MainState, CELL_SIZE};
+
+//tile codes used in `Observation::tiles`
+pub const TILE_EMPTY: u8 = 0;
+pub const TILE_WALL: u8 = 1;
+pub const TILE_DOT: u8 = 2;
+pub const TILE_POWER_PELLET: u8 = 3;
+
+//discrete action ids used by the Python and C bindings
+pub const ACTIONS: [Direction; 5] = [
+    Direction::None,
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GhostObservation {
+    pub x: f32,
+    pub y: f32,
+    pub vulnerable: bool,
+}
+
+//positions are in tile units, so (1.5, 2.0) is halfway between columns 1 and 2 of row 2
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+    pub width: usize,
+    pub height: usize,
+    //row-major, `width * height` tile codes
+    pub tiles: Vec<u8>,
+    pub pacman: (f32, f32),
+    pub ghosts: Vec<GhostObservation>,
+    pub power_pellet_timer: f32,
+    pub score: u32,
+    pub lives: i32,
+    //from 1, one up for every maze cleared
+    pub level: u32,
+}
+
+pub struct PacmanEnv {
+    state: MainState,
+}
+
+impl PacmanEnv {
+    pub fn new() -> GameResult<PacmanEnv> {
+        let mut state = MainState::new(Settings::default(), Map::classic())?;
+        state.screen = GameScreen::Playing;
+        state.record_history = false;
+        Ok(PacmanEnv { state })
+    }
+
+    //starts a new game; the same seed always plays out the same way for the same actions
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.state.reset_game();
+        self.state.seed = seed;
+        self.state.rng = Pcg32::seed_from_u64(seed);
+        self.state.recorder = None;
+        self.state.stats = GameStats::new(seed);
+        self.observation()
+    }
+
+    //advances one tick with `action` as the requested direction and returns the new
+    //observation, the score gained this tick, and whether the game has ended
+    pub fn step(&mut self, action: Direction) -> (Observation, f32, bool) {
+        let score_before = self.state.score;
+        let done = self.advance(action);
+        let reward = self.state.score.saturating_sub(score_before) as f32;
+        (self.observation(), reward, done)
+    }
+
+    //`step` without building an observation, for fast rollouts; returns whether the game
+    //has ended
+    pub fn advance(&mut self, action: Direction) -> bool {
+        if !self.state.game_over() {
+            self.state.requested_direction = action;
+            self.state.step();
+        }
+        self.state.game_over()
+    }
+
+    //`observer` is told about every tick from now on
+    pub fn add_observer(&mut self, observer: Box<dyn Observer>) {
+        self.state.observers.push(observer);
+    }
+
+    //changes how many ghosts chase pacman, copying the first ghost for extra ones
+    pub fn set_ghost_count(&mut self, count: usize) {
+        let ghosts = &mut self.state.ghosts;
+        if let Some(template) = ghosts.first().cloned() {
+            ghosts.resize(count, template);
+        }
+    }
+
+    //the board as RGB pixels, without needing a window or GPU
+    pub fn render(&self) -> Frame {
+        raster::render(&self.state)
+    }
+
+    pub fn observation(&self) -> Observation {
+        observe(&self.state)
+    }
+}
+
+//what the environment would observe of `state`, also used to drive the title screen's demo
+pub(crate) fn observe(state: &MainState) -> Observation {
+    let (width, height) = (state.map.width, state.map.height);
+
+    //void can't be walked through either, so it reads as wall
+    let mut tiles = vec![TILE_EMPTY; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            if !state.layout.is_open(x as i64, y as i64) {
+                tiles[y * width + x] = TILE_WALL;
+            }
+        }
+    }
+    let mut mark = |px: f32, py: f32, code: u8| {
+        let (x, y) = ((px / CELL_SIZE) as usize, (py / CELL_SIZE) as usize);
+        if x < width && y < height {
+            tiles[y * width + x] = code;
+        }
+    };
+    for dot in &state.dots {
+        mark(dot.x, dot.y, TILE_DOT);
+    }
+    for pellet in &state.power_pellets {
+        mark(pellet.x, pellet.y, TILE_POWER_PELLET);
+    }
+
+    Observation {
+        width,
+        height,
+        tiles,
+        pacman: (state.pacman_x.to_f32() / CELL_SIZE, state.pacman_y.to_f32() / CELL_SIZE),
+        ghosts: state
+            .ghosts
+            .iter()
+            .map(|ghost| GhostObservation {
+                x: ghost.x.to_f32() / CELL_SIZE,
+                y: ghost.y.to_f32() / CELL_SIZE,
+                vulnerable: ghost.is_vulnerable(),
+            })
+            .collect(),
+        power_pellet_timer: if state.power_pellet_active { state.power_pellet_timer } else { 0.0 },
+        score: state.score,
+        lives: state.lives,
+        level: state.level,
+    }
+}
@@ -0,0 +1,34 @@
+//event bus for effects injected into the game from outside the simulation
+use std::sync::mpsc::{self, Receiver, Sender};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Effect {
+    //ghosts wander randomly for a while
+    Scatter,
+    //pacman moves at half speed for a while
+    SlowPacman,
+    //the level's fruit comes out on pacman's start cell
+    SpawnFruit,
+}
+
+//anything holding a sender (possibly on another thread) can queue effects;
+//the game drains them at the start of each tick
+pub struct EventBus {
+    sender: Sender<Effect>,
+    receiver: Receiver<Effect>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        EventBus { sender, receiver }
+    }
+
+    pub fn injector(&self) -> Sender<Effect> {
+        self.sender.clone()
+    }
+
+    pub fn drain(&self) -> Vec<Effect> {
+        self.receiver.try_iter().collect()
+    }
+}
@@ -0,0 +1,178 @@
+//C interface to the headless simulation, built with `--features ffi`; the header is
+//generated into include/rust_pacman.h by build.rs. Exported items use `///` comments
+//because cbindgen copies those into the header
+//
+//    PacmanGame *game = pacman_create(42);
+//    PacmanState state;
+//    while (!pacman_step(game, PACMAN_ACTION_LEFT, &state)) { ... }
+//    pacman_destroy(game);
+//
+//A panic must not unwind into C, so every entry point catches it: creating returns null, and a
+//game that panicked counts as ended from then on. Only pacman_destroy is still useful on it
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::env::{PacmanEnv, ACTIONS, TILE_DOT};
+
+pub const PACMAN_ACTION_NOOP: u32 = 0;
+pub const PACMAN_ACTION_UP: u32 = 1;
+pub const PACMAN_ACTION_DOWN: u32 = 2;
+pub const PACMAN_ACTION_LEFT: u32 = 3;
+pub const PACMAN_ACTION_RIGHT: u32 = 4;
+
+/// Ghosts beyond this are left out of `PacmanState`.
+pub const PACMAN_MAX_GHOSTS: usize = 4;
+
+/// Opaque handle, owned by the caller from `pacman_create` until `pacman_destroy`.
+pub struct PacmanGame {
+    env: PacmanEnv,
+    //a call panicked part way through, so the env can't be trusted any more
+    failed: bool,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct PacmanGhost {
+    pub x: f32,
+    pub y: f32,
+    pub vulnerable: bool,
+}
+
+/// Snapshot of the game after a step. Positions are in tile units.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct PacmanState {
+    pub pacman_x: f32,
+    pub pacman_y: f32,
+    pub ghost_count: u32,
+    pub ghosts: [PacmanGhost; PACMAN_MAX_GHOSTS],
+    pub dots_remaining: u32,
+    pub power_pellet_timer: f32,
+    pub score: u32,
+    pub lives: i32,
+    pub reward: f32,
+    pub game_over: bool,
+}
+
+/// Creates a game seeded with `seed`, or returns null if it could not be created,
+/// including when creating it panicked.
+#[no_mangle]
+pub extern "C" fn pacman_create(seed: u64) -> *mut PacmanGame {
+    let created = panic::catch_unwind(|| {
+        PacmanEnv::new().map(|mut env| {
+            env.reset(seed);
+            env
+        })
+    });
+    match created {
+        Ok(Ok(env)) => Box::into_raw(Box::new(PacmanGame { env, failed: false })),
+        Ok(Err(e)) => {
+            eprintln!("Failed to create game: {}", e);
+            ptr::null_mut()
+        }
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Starts a new game with `seed` and fills `out` if it isn't null. If the game has
+/// panicked, now or in an earlier call, `out` is filled with `game_over` set and the
+/// game stays ended.
+///
+/// # Safety
+/// `game` must come from `pacman_create` and not have been destroyed; `out` must be null
+/// or point to a writable `PacmanState`.
+#[no_mangle]
+pub unsafe extern "C" fn pacman_reset(game: *mut PacmanGame, seed: u64, out: *mut PacmanState) {
+    let game = match game.as_mut() {
+        Some(game) => game,
+        None => return,
+    };
+    if !game.failed {
+        let reset = panic::catch_unwind(AssertUnwindSafe(|| {
+            game.env.reset(seed);
+            write_state(game, 0.0, false, out);
+        }));
+        game.failed = reset.is_err();
+    }
+    if game.failed {
+        write_failed(out);
+    }
+}
+
+/// Advances one tick, fills `out` if it isn't null and returns whether the game has
+/// ended. Unknown actions are treated as `PACMAN_ACTION_NOOP`. A game that has panicked
+/// returns true with `game_over` set in `out`, here and on every later step.
+///
+/// # Safety
+/// Same requirements as `pacman_reset`.
+#[no_mangle]
+pub unsafe extern "C" fn pacman_step(game: *mut PacmanGame, action: u32, out: *mut PacmanState) -> bool {
+    let game = match game.as_mut() {
+        Some(game) => game,
+        None => return true,
+    };
+    if game.failed {
+        write_failed(out);
+        return true;
+    }
+    let direction = ACTIONS.get(action as usize).copied().unwrap_or(ACTIONS[0]);
+    let stepped = panic::catch_unwind(AssertUnwindSafe(|| {
+        let (_, reward, done) = game.env.step(direction);
+        write_state(game, reward, done, out);
+        done
+    }));
+    match stepped {
+        Ok(done) => done,
+        Err(_) => {
+            game.failed = true;
+            write_failed(out);
+            true
+        }
+    }
+}
+
+/// Frees a game. Null is ignored.
+///
+/// # Safety
+/// `game` must be null or come from `pacman_create`, and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn pacman_destroy(game: *mut PacmanGame) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+//what a game that panicked reports: nothing but that it's over
+unsafe fn write_failed(out: *mut PacmanState) {
+    if let Some(out) = out.as_mut() {
+        *out = PacmanState { game_over: true, ..PacmanState::default() };
+    }
+}
+
+unsafe fn write_state(game: &PacmanGame, reward: f32, game_over: bool, out: *mut PacmanState) {
+    let out = match out.as_mut() {
+        Some(out) => out,
+        None => return,
+    };
+    let observation = game.env.observation();
+    let mut state = PacmanState {
+        pacman_x: observation.pacman.0,
+        pacman_y: observation.pacman.1,
+        ghost_count: observation.ghosts.len().min(PACMAN_MAX_GHOSTS) as u32,
+        dots_remaining: observation.tiles.iter().filter(|&&tile| tile == TILE_DOT).count() as u32,
+        power_pellet_timer: observation.power_pellet_timer,
+        score: observation.score,
+        lives: observation.lives,
+        reward,
+        game_over,
+        ..PacmanState::default()
+    };
+    for (slot, ghost) in state.ghosts.iter_mut().zip(&observation.ghosts) {
+        *slot = PacmanGhost {
+            x: ghost.x,
+            y: ghost.y,
+            vulnerable: ghost.vulnerable,
+        };
+    }
+    *out = state;
+}
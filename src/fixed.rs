@@ -0,0 +1,133 @@
+//fixed-point numbers for the simulation, so positions and speeds add up to the same bits on every
+//platform and compiler. A pixel is 256 units. Files still store plain pixel floats, which convert
+//both ways exactly for anything the simulation produces
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+pub const FRACTION_BITS: u32 = 8;
+//units per pixel, for turning f32 constants into fixed ones in const items:
+//`Fixed::from_raw((SPEED * fixed::SCALE) as i32)`
+pub const SCALE: f32 = (1 << FRACTION_BITS) as f32;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(i32);
+
+impl Fixed {
+    pub const ZERO: Fixed = Fixed(0);
+
+    pub const fn from_raw(raw: i32) -> Fixed {
+        Fixed(raw)
+    }
+
+    pub const fn raw(self) -> i32 {
+        self.0
+    }
+
+    //whole pixels
+    pub const fn from_int(pixels: i32) -> Fixed {
+        Fixed(pixels << FRACTION_BITS)
+    }
+
+    //the nearest fixed value, exact for floats with at most 8 binary fraction digits
+    pub fn from_f32(pixels: f32) -> Fixed {
+        Fixed((pixels * SCALE).round() as i32)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / SCALE
+    }
+
+    //multiplied by a float factor, like a speed modifier from a mod. The product is rounded
+    //once, so it comes out the same everywhere
+    pub fn scale(self, factor: f32) -> Fixed {
+        Fixed::from_f32(self.to_f32() * factor)
+    }
+
+    pub fn abs(self) -> Fixed {
+        Fixed(self.0.abs())
+    }
+
+    pub fn half(self) -> Fixed {
+        Fixed(self.0 / 2)
+    }
+
+    //the square in raw units, wide enough that distances across any maze don't overflow
+    pub fn squared(self) -> i64 {
+        let raw = self.0 as i64;
+        raw * raw
+    }
+
+    //how many whole `cell`s fit below this value, rounding towards negative infinity
+    pub fn div_floor(self, cell: Fixed) -> i32 {
+        self.0.div_euclid(cell.0)
+    }
+
+    //the smallest n with n * `cell` at or above this value
+    pub fn div_ceil(self, cell: Fixed) -> i32 {
+        -(-self.0).div_euclid(cell.0)
+    }
+
+    //the nearest whole number of `cell`s, halves rounding up
+    pub fn div_round(self, cell: Fixed) -> i32 {
+        (self.0 + cell.0 / 2).div_euclid(cell.0)
+    }
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+    fn add(self, other: Fixed) -> Fixed {
+        Fixed(self.0 + other.0)
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+    fn sub(self, other: Fixed) -> Fixed {
+        Fixed(self.0 - other.0)
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Fixed;
+    fn neg(self) -> Fixed {
+        Fixed(-self.0)
+    }
+}
+
+impl Mul<i32> for Fixed {
+    type Output = Fixed;
+    fn mul(self, times: i32) -> Fixed {
+        Fixed(self.0 * times)
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, other: Fixed) {
+        self.0 += other.0;
+    }
+}
+
+impl SubAssign for Fixed {
+    fn sub_assign(&mut self, other: Fixed) {
+        self.0 -= other.0;
+    }
+}
+
+impl fmt::Debug for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_f32())
+    }
+}
+
+impl Serialize for Fixed {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f32(self.to_f32())
+    }
+}
+
+impl<'de> Deserialize<'de> for Fixed {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Fixed, D::Error> {
+        f32::deserialize(deserializer).map(Fixed::from_f32)
+    }
+}
@@ -0,0 +1,62 @@
+//turns a replay into video frames without a window: every simulation tick is rendered with the
+//software rasterizer, so the output is identical on every machine
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+use crate::raster;
+use crate::MainState;
+
+pub enum FrameSink {
+    //numbered PNGs in a folder
+    Png(PathBuf),
+    //raw RGB24 frames back to back on stdout, for piping into ffmpeg
+    Raw,
+}
+
+impl FrameSink {
+    //`-` means stdout, anything else is a folder
+    pub fn from_arg(arg: &str) -> FrameSink {
+        if arg == "-" {
+            FrameSink::Raw
+        } else {
+            FrameSink::Png(PathBuf::from(arg))
+        }
+    }
+}
+
+//plays the replay already started on `state` to the end, writing one frame per tick.
+//Returns the number of frames written
+pub fn export(state: &mut MainState, sink: &FrameSink) -> Result<u32, String> {
+    if let FrameSink::Png(dir) = sink {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+    }
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    let mut frames = 0;
+    loop {
+        let frame = raster::render(state);
+        frames += 1;
+        match sink {
+            FrameSink::Png(dir) => {
+                let path = dir.join(format!("frame-{:06}.png", frames));
+                image::save_buffer(
+                    &path,
+                    &frame.pixels,
+                    frame.width as u32,
+                    frame.height as u32,
+                    image::ColorType::Rgb8,
+                )
+                .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+            }
+            FrameSink::Raw => out.write_all(&frame.pixels).map_err(|e| e.to_string())?,
+        }
+
+        if state.game_over() || state.playback.as_ref().is_none_or(|player| player.finished()) {
+            break;
+        }
+        state.step();
+    }
+    out.flush().map_err(|e| e.to_string())?;
+    Ok(frames)
+}
@@ -0,0 +1,188 @@
+//the box shown when a game ends: the final score and the game's share code, this game's stats on
+//request, and buttons to play again, go back to the title screen or quit. A game that cleared a
+//maze offers New Game+ instead of playing again
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+
+use crate::stats::GameStats;
+use crate::widgets::ButtonGrid;
+use crate::TICKS_PER_SECOND;
+
+const MENU_WIDTH: f32 = 300.0;
+const MENU_HEIGHT: f32 = 260.0;
+//where the buttons start, from the top of the box
+const BUTTONS_TOP: f32 = 150.0;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GameOverAction {
+    None,
+    PlayAgain,
+    NewGamePlus,
+    MainMenu,
+    Exit,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Button {
+    PlayAgain,
+    NewGamePlus,
+    MainMenu,
+    ViewStats,
+    Exit,
+}
+
+pub struct GameOverMenu {
+    buttons: ButtonGrid<Button>,
+    //the stats replace the score line until closed
+    showing_stats: bool,
+    //mazes cleared before the game ended, when New Game+ is on offer
+    cleared: Option<u32>,
+    //for someone else to play the same game, if it can be shared
+    share_code: Option<String>,
+}
+
+impl GameOverMenu {
+    pub fn new() -> Self {
+        GameOverMenu::with_first((Button::PlayAgain, "Play Again"), None)
+    }
+
+    //after a game that cleared `levels` mazes, with New Game+ in place of Play Again
+    pub fn cleared(levels: u32) -> Self {
+        GameOverMenu::with_first((Button::NewGamePlus, "New Game+"), Some(levels))
+    }
+
+    fn with_first(first: (Button, &'static str), cleared: Option<u32>) -> Self {
+        GameOverMenu {
+            buttons: ButtonGrid::new(
+                vec![
+                    first,
+                    (Button::MainMenu, "Main Menu"),
+                    (Button::ViewStats, "View Stats"),
+                    (Button::Exit, "Exit"),
+                ],
+                2,
+            ),
+            showing_stats: false,
+            cleared,
+            share_code: None,
+        }
+    }
+
+    pub fn with_share_code(mut self, code: Option<String>) -> Self {
+        self.share_code = code;
+        self
+    }
+
+    //the box's top-left corner in a window of size (w, h)
+    fn origin(w: f32, h: f32) -> (f32, f32) {
+        ((w - MENU_WIDTH) / 2.0, (h - MENU_HEIGHT) / 2.0)
+    }
+
+    fn buttons_origin(&self, w: f32, h: f32) -> (f32, f32) {
+        let (menu_x, menu_y) = GameOverMenu::origin(w, h);
+        (menu_x + (MENU_WIDTH - self.buttons.size().0) / 2.0, menu_y + BUTTONS_TOP)
+    }
+
+    fn press(&mut self, button: Button) -> GameOverAction {
+        match button {
+            Button::PlayAgain => GameOverAction::PlayAgain,
+            Button::NewGamePlus => GameOverAction::NewGamePlus,
+            Button::MainMenu => GameOverAction::MainMenu,
+            Button::ViewStats => {
+                self.showing_stats = !self.showing_stats;
+                GameOverAction::None
+            }
+            Button::Exit => GameOverAction::Exit,
+        }
+    }
+
+    pub fn handle_key(&mut self, keycode: KeyCode) -> GameOverAction {
+        if keycode == KeyCode::Escape && self.showing_stats {
+            self.showing_stats = false;
+            return GameOverAction::None;
+        }
+        match self.buttons.handle_key(keycode) {
+            Some(button) => self.press(button),
+            None => GameOverAction::None,
+        }
+    }
+
+    pub fn click(&mut self, ctx: &Context, x: f32, y: f32) -> GameOverAction {
+        let (w, h) = graphics::drawable_size(ctx);
+        match self.buttons.click(self.buttons_origin(w, h), x, y) {
+            Some(button) => self.press(button),
+            None => GameOverAction::None,
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, score: u32, stats: &GameStats) -> GameResult {
+        let (w, h) = graphics::drawable_size(ctx);
+
+        //dim the game behind the box
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, 0.0, w, h),
+            Color::new(0.0, 0.0, 0.0, 0.7),
+        )?;
+        graphics::draw(ctx, &background, DrawParam::default())?;
+
+        let (menu_x, menu_y) = GameOverMenu::origin(w, h);
+        let menu_bg = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(menu_x, menu_y, MENU_WIDTH, MENU_HEIGHT),
+            Color::new(0.2, 0.2, 0.2, 1.0),
+        )?;
+        graphics::draw(ctx, &menu_bg, DrawParam::default())?;
+
+        let (title, title_color) = match self.cleared {
+            Some(1) => (graphics::Text::new("1 MAZE CLEARED"), Color::YELLOW),
+            Some(levels) => (graphics::Text::new(format!("{} MAZES CLEARED", levels)), Color::YELLOW),
+            None => (graphics::Text::new("GAME OVER!"), Color::RED),
+        };
+        let title_dims = title.dimensions(ctx);
+        graphics::draw(
+            ctx,
+            &title,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 {
+                    x: menu_x + (MENU_WIDTH - title_dims.w * 2.0) / 2.0,
+                    y: menu_y + 20.0,
+                })
+                .color(title_color)
+                .scale([2.0, 2.0]),
+        )?;
+
+        let lines = if self.showing_stats {
+            vec![
+                format!("Time: {:.0} s", stats.ticks as f32 / TICKS_PER_SECOND as f32),
+                format!("Dots: {}   Power pellets: {}", stats.dots_eaten, stats.power_pellets_eaten),
+                format!("Ghosts eaten: {}   Deaths: {}", stats.ghosts_eaten, stats.deaths),
+            ]
+        } else {
+            let mut lines = vec![format!("Final Score: {}", score)];
+            if let Some(code) = &self.share_code {
+                lines.push(format!("Share code: {}", code));
+            }
+            lines
+        };
+        for (i, line) in lines.iter().enumerate() {
+            let text = graphics::Text::new(line.as_str());
+            let dims = text.dimensions(ctx);
+            graphics::draw(
+                ctx,
+                &text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 {
+                        x: menu_x + (MENU_WIDTH - dims.w) / 2.0,
+                        y: menu_y + 70.0 + i as f32 * 22.0,
+                    })
+                    .color(Color::WHITE),
+            )?;
+        }
+
+        self.buttons.draw(ctx, self.buttons_origin(w, h))
+    }
+}
@@ -0,0 +1,63 @@
+//the arcade's pacing: every ghost scatters to its own corner for a few seconds, then chases
+//pacman for longer, back and forth on the maze's schedule until the last phase, when they chase
+//for good. The clock stands still while a power pellet is on, and the ghosts turn round at
+//every switch, which is the player's cue
+use serde::{Deserialize, Serialize};
+
+use crate::TICKS_PER_SECOND;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum GhostMode {
+    Scatter,
+    #[default]
+    Chase,
+}
+
+//where the ghosts are on the schedule. Phases alternate starting with scatter
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct GhostModeTimer {
+    //the phases' lengths in ticks
+    phases: Vec<u32>,
+    phase: usize,
+    ticks_left: u32,
+}
+
+impl GhostModeTimer {
+    //a timer at the start of `schedule`, given in seconds
+    pub fn new(schedule: &[f32]) -> Self {
+        let phases: Vec<u32> = schedule.iter().map(|seconds| (seconds * TICKS_PER_SECOND as f32).round() as u32).collect();
+        let ticks_left = phases.first().copied().unwrap_or(0);
+        GhostModeTimer { phases, phase: 0, ticks_left }
+    }
+
+    pub fn mode(&self) -> GhostMode {
+        if self.phase < self.phases.len() && self.phase.is_multiple_of(2) {
+            GhostMode::Scatter
+        } else {
+            GhostMode::Chase
+        }
+    }
+
+    //seconds until the next switch, or None once the ghosts chase for good
+    pub fn seconds_left(&self) -> Option<f32> {
+        if self.phase < self.phases.len() {
+            Some(self.ticks_left as f32 / TICKS_PER_SECOND as f32)
+        } else {
+            None
+        }
+    }
+
+    //one tick on. True when it switched mode, skipping phases of no length
+    pub fn tick(&mut self) -> bool {
+        if self.phase >= self.phases.len() {
+            return false;
+        }
+        let before = self.mode();
+        self.ticks_left = self.ticks_left.saturating_sub(1);
+        while self.ticks_left == 0 && self.phase < self.phases.len() {
+            self.phase += 1;
+            self.ticks_left = self.phases.get(self.phase).copied().unwrap_or(0);
+        }
+        self.mode() != before
+    }
+}
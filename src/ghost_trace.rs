@@ -0,0 +1,221 @@
+//the ghost trace: in dev mode every tick of the current level records where each ghost was, which
+//way it was going and what it was after, and while paused the scrubber steps back and forth
+//through them. For working out what the AI was thinking when a player reports it doing something
+//odd
+use ggez::graphics::{self, Color, DrawParam, Rect, Text};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+use std::collections::VecDeque;
+
+use crate::fixed::Fixed;
+use crate::spawn::ROSTER;
+use crate::{Direction, CELL, GHOST, GHOST_SIZE, TICKS_PER_SECOND};
+
+//ten minutes of a level. Older ticks are dropped
+const MAX_TICKS: usize = 10 * 60 * TICKS_PER_SECOND as usize;
+//the trail behind each ghost: this many ticks back, a dot every few of them
+const TRAIL_TICKS: usize = 2 * TICKS_PER_SECOND as usize;
+const TRAIL_SPACING: usize = 4;
+const TIMELINE_MARGIN: f32 = 20.0;
+const TIMELINE_HEIGHT: f32 = 6.0;
+
+//what a ghost was up to on a tick
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Activity {
+    //still in the pen, or waiting out a respawn
+    Waiting,
+    Chasing,
+    Scattering,
+    Frightened,
+    Confused,
+    //only the eyes, heading home
+    Eyes,
+    //a player steering it in a versus game
+    Steered,
+}
+
+impl Activity {
+    fn label(self) -> &'static str {
+        match self {
+            Activity::Waiting => "waiting",
+            Activity::Chasing => "chasing",
+            Activity::Scattering => "scattering",
+            Activity::Frightened => "frightened",
+            Activity::Confused => "confused",
+            Activity::Eyes => "eyes going home",
+            Activity::Steered => "steered by a player",
+        }
+    }
+}
+
+//one ghost on one tick. Positions are top-left corners, like the ghost's own
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TracedGhost {
+    pub x: Fixed,
+    pub y: Fixed,
+    pub direction: Direction,
+    pub target: (Fixed, Fixed),
+    pub activity: Activity,
+}
+
+#[derive(Default)]
+pub struct GhostTrace {
+    level: u32,
+    //the level's tick the oldest frame was recorded on
+    first_tick: u32,
+    //a frame per tick, each with every ghost in roster order
+    frames: VecDeque<Vec<TracedGhost>>,
+}
+
+impl GhostTrace {
+    //adds the ghosts as they were on `tick` of `level`. A new level, or a tick that doesn't follow
+    //on from the last one, as after loading a save, starts the trace over
+    pub fn record(&mut self, level: u32, tick: u32, ghosts: impl Iterator<Item = TracedGhost>) {
+        let next = self.first_tick + self.frames.len() as u32;
+        if !self.frames.is_empty() && level == self.level && tick + 1 == next {
+            //the clock stops once the game is over
+            return;
+        }
+        if level != self.level || tick != next || self.frames.is_empty() {
+            self.level = level;
+            self.first_tick = tick;
+            self.frames.clear();
+        }
+        //the oldest frame makes room, and lends the newest its vector
+        let mut frame = if self.frames.len() == MAX_TICKS {
+            self.first_tick += 1;
+            self.frames.pop_front().unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        frame.clear();
+        frame.extend(ghosts);
+        self.frames.push_back(frame);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    //the newest frame, where the scrubber opens
+    pub fn latest(&self) -> usize {
+        self.frames.len().saturating_sub(1)
+    }
+
+    //the frame the scrubber moves to from `cursor` on a key press, or None when it closes
+    pub fn scrub(&self, cursor: usize, keycode: KeyCode) -> Option<usize> {
+        let second = TICKS_PER_SECOND as usize;
+        let cursor = match keycode {
+            KeyCode::Escape | KeyCode::T => return None,
+            KeyCode::Left => cursor.saturating_sub(1),
+            KeyCode::Right => cursor + 1,
+            KeyCode::Down => cursor.saturating_sub(second),
+            KeyCode::Up => cursor + second,
+            KeyCode::Home => 0,
+            KeyCode::End => self.latest(),
+            _ => cursor,
+        };
+        Some(cursor.min(self.latest()))
+    }
+
+    //every ghost where it was on the frame at `cursor`, the way it had come and a line to what it
+    //was after. Drawn in maze coordinates
+    pub fn draw_board(&self, ctx: &mut Context, cursor: usize) -> GameResult {
+        let frame = match self.frames.get(cursor) {
+            Some(frame) if !frame.is_empty() => frame,
+            _ => return Ok(()),
+        };
+        let center =
+            |x: Fixed, y: Fixed| ggez::mint::Point2 { x: (x + GHOST.half()).to_f32(), y: (y + GHOST.half()).to_f32() };
+        let mut builder = graphics::MeshBuilder::new();
+        for (index, ghost) in frame.iter().enumerate() {
+            let color = ROSTER[index % ROSTER.len()].color;
+            let faded = Color::new(color.r, color.g, color.b, 0.5);
+            for back in (TRAIL_SPACING..=TRAIL_TICKS.min(cursor)).step_by(TRAIL_SPACING) {
+                if let Some(past) = self.frames.get(cursor - back).and_then(|past| past.get(index)) {
+                    builder.circle(graphics::DrawMode::fill(), center(past.x, past.y), 2.0, 0.5, faded)?;
+                }
+            }
+            let here = center(ghost.x, ghost.y);
+            let target = center(ghost.target.0, ghost.target.1);
+            let aiming = !matches!(ghost.activity, Activity::Waiting | Activity::Steered);
+            if aiming && (here.x != target.x || here.y != target.y) {
+                builder.line(&[here, target], 1.5, faded)?;
+                builder.circle(graphics::DrawMode::stroke(1.5), target, 6.0, 0.5, color)?;
+            }
+            builder.circle(graphics::DrawMode::stroke(2.0), here, GHOST_SIZE / 2.0, 0.5, color)?;
+        }
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+
+    //the timeline along the bottom of the window with the frame at `cursor` marked, and what
+    //each ghost was doing then. A ghost that picked a new target on that tick says so
+    pub fn draw_timeline(&self, ctx: &mut Context, cursor: usize) -> GameResult {
+        let frame = match self.frames.get(cursor) {
+            Some(frame) => frame,
+            None => return Ok(()),
+        };
+        let (w, h) = graphics::drawable_size(ctx);
+        let mut lines = vec![format!(
+            "Ghost trace, level {} tick {} ({:.2}s)",
+            self.level,
+            self.first_tick + cursor as u32,
+            (self.first_tick + cursor as u32) as f32 / TICKS_PER_SECOND as f32
+        )];
+        for (index, ghost) in frame.iter().enumerate() {
+            let spec = &ROSTER[index % ROSTER.len()];
+            let cell =
+                |(x, y): (Fixed, Fixed)| ((x + GHOST.half()).div_floor(CELL), (y + GHOST.half()).div_floor(CELL));
+            let (col, row) = cell((ghost.x, ghost.y));
+            let (target_col, target_row) = cell(ghost.target);
+            let retargeted = cursor > 0
+                && self.frames[cursor - 1].get(index).is_some_and(|before| before.target != ghost.target);
+            lines.push(format!(
+                "{}: {} at ({}, {}), going {:?}, after ({}, {}){}",
+                spec.name,
+                ghost.activity.label(),
+                col,
+                row,
+                ghost.direction,
+                target_col,
+                target_row,
+                if retargeted { ", new target" } else { "" }
+            ));
+        }
+        lines.push("Left/Right: a tick   Up/Down: a second   Home/End   T: close".to_string());
+
+        let top = h - TIMELINE_MARGIN * 2.0 - 20.0 * lines.len() as f32;
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Rect::new(0.0, top - 10.0, w, h - top + 10.0),
+            Color::new(0.0, 0.0, 0.0, 0.8),
+        )?;
+        graphics::draw(ctx, &background, DrawParam::default())?;
+        for (i, line) in lines.iter().enumerate() {
+            let color = match i {
+                0 => Color::YELLOW,
+                i if i <= frame.len() => ROSTER[(i - 1) % ROSTER.len()].color,
+                _ => Color::WHITE,
+            };
+            graphics::draw(
+                ctx,
+                &Text::new(line.as_str()),
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 { x: TIMELINE_MARGIN, y: top + 20.0 * i as f32 })
+                    .color(color),
+            )?;
+        }
+
+        let bar =
+            Rect::new(TIMELINE_MARGIN, h - TIMELINE_MARGIN - TIMELINE_HEIGHT, w - 2.0 * TIMELINE_MARGIN, TIMELINE_HEIGHT);
+        let share = if self.latest() == 0 { 1.0 } else { cursor as f32 / self.latest() as f32 };
+        let marker = Rect::new(bar.x + bar.w * share - 2.0, bar.y - 4.0, 4.0, bar.h + 8.0);
+        let mut builder = graphics::MeshBuilder::new();
+        builder.rectangle(graphics::DrawMode::fill(), bar, Color::new(1.0, 1.0, 1.0, 0.3))?;
+        builder.rectangle(graphics::DrawMode::fill(), marker, Color::YELLOW)?;
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+}
@@ -0,0 +1,106 @@
+//how often pacman and each ghost stood on every cell of the maze, kept for the whole session so
+//map authors can see which corridors get used and which are dead weight. Drawn over the board
+//after a game, or at any time in dev mode
+use ggez::graphics::{self, Color, DrawParam, Rect};
+use ggez::{Context, GameResult};
+
+use crate::CELL_SIZE;
+
+//at most this opaque on the busiest cell
+const MAX_ALPHA: f32 = 0.75;
+
+//which counts the overlay shows
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HeatmapLayer {
+    Pacman,
+    //every ghost added together
+    Ghosts,
+    Ghost(usize),
+}
+
+pub struct Heatmap {
+    width: usize,
+    height: usize,
+    pacman: Vec<u32>,
+    //one grid per ghost, grown when a game has more of them than any before
+    ghosts: Vec<Vec<u32>>,
+}
+
+impl Heatmap {
+    pub fn new(width: usize, height: usize) -> Self {
+        Heatmap { width, height, pacman: vec![0; width * height], ghosts: Vec::new() }
+    }
+
+    //counts a tick on pacman's cell and on each ghost's; None for a ghost that isn't in play
+    pub fn record(&mut self, pacman: (usize, usize), ghosts: &[Option<(usize, usize)>]) {
+        if let Some(index) = index_in(self.width, self.height, pacman) {
+            self.pacman[index] += 1;
+        }
+        while self.ghosts.len() < ghosts.len() {
+            self.ghosts.push(vec![0; self.width * self.height]);
+        }
+        for (counts, cell) in self.ghosts.iter_mut().zip(ghosts) {
+            if let Some(index) = cell.and_then(|cell| index_in(self.width, self.height, cell)) {
+                counts[index] += 1;
+            }
+        }
+    }
+
+    //the layer after `layer`: pacman, then all the ghosts, then each ghost on its own
+    pub fn next_layer(&self, layer: HeatmapLayer) -> HeatmapLayer {
+        match layer {
+            HeatmapLayer::Pacman => HeatmapLayer::Ghosts,
+            HeatmapLayer::Ghosts if !self.ghosts.is_empty() => HeatmapLayer::Ghost(0),
+            HeatmapLayer::Ghost(ghost) if ghost + 1 < self.ghosts.len() => HeatmapLayer::Ghost(ghost + 1),
+            _ => HeatmapLayer::Pacman,
+        }
+    }
+
+    fn counts(&self, layer: HeatmapLayer) -> Vec<u32> {
+        match layer {
+            HeatmapLayer::Pacman => self.pacman.clone(),
+            HeatmapLayer::Ghosts => {
+                let mut total = vec![0; self.width * self.height];
+                for counts in &self.ghosts {
+                    for (sum, count) in total.iter_mut().zip(counts) {
+                        *sum += count;
+                    }
+                }
+                total
+            }
+            HeatmapLayer::Ghost(ghost) => self.ghosts.get(ghost).cloned().unwrap_or_default(),
+        }
+    }
+
+    //tints every visited cell in `color`, stronger the more often it was visited. Counts run
+    //from a few ticks to thousands, so the strength goes with their logarithm to keep the
+    //quiet corridors visible. Drawn in maze coordinates
+    pub fn draw(&self, ctx: &mut Context, layer: HeatmapLayer, color: Color) -> GameResult {
+        let counts = self.counts(layer);
+        let busiest = counts.iter().copied().max().unwrap_or(0);
+        if busiest == 0 {
+            return Ok(());
+        }
+        let scale = (busiest as f32).ln_1p();
+        let mut builder = graphics::MeshBuilder::new();
+        for (index, &count) in counts.iter().enumerate().filter(|(_, &count)| count > 0) {
+            let (col, row) = (index % self.width, index / self.width);
+            let strength = (count as f32).ln_1p() / scale;
+            builder.rectangle(
+                graphics::DrawMode::fill(),
+                Rect::new(col as f32 * CELL_SIZE, row as f32 * CELL_SIZE, CELL_SIZE, CELL_SIZE),
+                Color::new(color.r, color.g, color.b, strength * MAX_ALPHA),
+            )?;
+        }
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+}
+
+fn index_in(width: usize, height: usize, (col, row): (usize, usize)) -> Option<usize> {
+    if col < width && row < height {
+        Some(row * width + col)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,138 @@
+//history of completed games with sortable columns and per-map bests
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+
+use crate::scores::{GameRecord, ScoreDb, SortKey};
+
+const ROWS_SHOWN: usize = 12;
+
+pub struct HistoryScreen {
+    sort: SortKey,
+    games: Vec<GameRecord>,
+    bests: Vec<(String, u32)>,
+    error: Option<String>,
+}
+
+impl HistoryScreen {
+    pub fn new() -> Self {
+        let mut screen = HistoryScreen {
+            sort: SortKey::Date,
+            games: Vec::new(),
+            bests: Vec::new(),
+            error: None,
+        };
+        screen.reload();
+        screen
+    }
+
+    fn reload(&mut self) {
+        let result = ScoreDb::open().and_then(|db| Ok((db.games(self.sort, ROWS_SHOWN)?, db.map_bests()?)));
+        match result {
+            Ok((games, bests)) => {
+                self.games = games;
+                self.bests = bests;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(format!("Couldn't read history: {}", e)),
+        }
+    }
+
+    //Left/Right change the sort column. Returns false once the screen should close
+    pub fn handle_key(&mut self, keycode: KeyCode) -> bool {
+        let current = SortKey::ALL.iter().position(|&key| key == self.sort).unwrap_or(0);
+        match keycode {
+            KeyCode::Escape | KeyCode::Return => return false,
+            KeyCode::Left => {
+                self.sort = SortKey::ALL[(current + SortKey::ALL.len() - 1) % SortKey::ALL.len()];
+                self.reload();
+            }
+            KeyCode::Right => {
+                self.sort = SortKey::ALL[(current + 1) % SortKey::ALL.len()];
+                self.reload();
+            }
+            _ => {}
+        }
+        true
+    }
+
+    pub fn draw(&self, ctx: &mut Context) -> GameResult {
+        let (w, h) = graphics::drawable_size(ctx);
+
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, 0.0, w, h),
+            Color::new(0.0, 0.0, 0.0, 0.9),
+        )?;
+        graphics::draw(ctx, &background, DrawParam::default())?;
+
+        let title = graphics::Text::new(format!("History (by {})", self.sort.name()));
+        graphics::draw(
+            ctx,
+            &title,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: 20.0, y: 20.0 })
+                .color(Color::YELLOW)
+                .scale([1.5, 1.5]),
+        )?;
+
+        let mut lines = vec![format!("{:<17}{:<9}{:>7} {:>6}{:>5}{:>8}", "Date", "Map", "Score", "Lvl", "NG+", "Time")];
+        for game in &self.games {
+            lines.push(format!(
+                "{:<17}{:<9}{:>7}{}{:>6}{:>5}{:>7.0}s",
+                game.date,
+                game.map,
+                game.score,
+                if game.assisted { "*" } else { " " },
+                game.level,
+                game.prestige,
+                game.duration_secs
+            ));
+        }
+        if self.games.iter().any(|game| game.assisted) {
+            lines.push("* played with assists".to_string());
+        }
+        if self.games.is_empty() {
+            lines.push("No games played yet".to_string());
+        }
+        lines.push(String::new());
+        lines.push("Best per map".to_string());
+        for (map, best) in &self.bests {
+            lines.push(format!("{:<17}{:>7}", map, best));
+        }
+
+        for (i, line) in lines.iter().enumerate() {
+            let color = if i == 0 { Color::YELLOW } else { Color::WHITE };
+            let text = graphics::Text::new(line.as_str());
+            graphics::draw(
+                ctx,
+                &text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 { x: 20.0, y: 60.0 + i as f32 * 20.0 })
+                    .color(color),
+            )?;
+        }
+
+        if let Some(error) = &self.error {
+            let text = graphics::Text::new(error.as_str());
+            graphics::draw(
+                ctx,
+                &text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 { x: 20.0, y: h - 60.0 })
+                    .color(Color::RED),
+            )?;
+        }
+
+        let hint = graphics::Text::new("Left/Right: sort   Esc: back");
+        graphics::draw(
+            ctx,
+            &hint,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: 20.0, y: h - 30.0 })
+                .color(Color::WHITE),
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+//the score, lives and status lines, drawn onto a canvas that is only redone when one of them
+//changes. A steady frame just blits it instead of laying the text out again
+use ggez::graphics::{self, Canvas, Color, DrawParam, Text};
+use ggez::{Context, GameResult};
+
+use crate::entities::{self, FruitKind};
+use crate::inventory::PowerUp;
+
+const FRUIT_ICON_SIZE: f32 = 14.0;
+const FRUIT_SPACING: f32 = 18.0;
+
+//everything the HUD shows. A frame whose values match the last one reuses the canvas
+#[derive(Clone, PartialEq)]
+pub struct HudValues {
+    pub score: u32,
+    pub lives: i32,
+    pub level: u32,
+    pub threads: usize,
+    //top right: which side this machine plays in a versus game, or that it's spectating
+    pub role: Option<String>,
+    //the HUD widgets switched on in the settings, in the middle
+    pub widgets: Vec<String>,
+    //bottom right: the power-up pacman is carrying
+    pub held_item: Option<PowerUp>,
+    //bottom left: the last few fruit eaten this game, oldest first
+    pub fruit: Vec<FruitKind>,
+}
+
+pub struct HudCanvas {
+    //the values and drawable size the canvas was drawn for
+    cached: Option<(HudValues, (f32, f32), Canvas)>,
+}
+
+impl HudCanvas {
+    pub fn new() -> Self {
+        HudCanvas { cached: None }
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context, values: HudValues) -> GameResult {
+        let size = graphics::drawable_size(ctx);
+        let fresh = self
+            .cached
+            .as_ref()
+            .is_some_and(|(cached, cached_size, _)| *cached == values && *cached_size == size);
+        if !fresh {
+            let canvas = Canvas::with_window_size(ctx)?;
+            graphics::set_canvas(ctx, Some(&canvas));
+            graphics::clear(ctx, Color::new(0.0, 0.0, 0.0, 0.0));
+            let drawn = draw_values(ctx, &values, size.0);
+            graphics::set_canvas(ctx, None);
+            drawn?;
+            self.cached = Some((values, size, canvas));
+        }
+        match &self.cached {
+            Some((_, _, canvas)) => graphics::draw(ctx, canvas, DrawParam::default()),
+            None => Ok(()),
+        }
+    }
+}
+
+fn draw_values(ctx: &mut Context, values: &HudValues, width: f32) -> GameResult {
+    let left = [
+        format!("Score: {}", values.score),
+        format!("Lives: {}   Level: {}", values.lives, values.level),
+        format!("Threads: {}", values.threads),
+    ];
+    for (i, line) in left.iter().enumerate() {
+        graphics::draw(
+            ctx,
+            &Text::new(line.as_str()),
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: 10.0, y: 10.0 + 20.0 * i as f32 })
+                .color(Color::WHITE),
+        )?;
+    }
+
+    for (i, line) in values.widgets.iter().enumerate() {
+        graphics::draw(
+            ctx,
+            &Text::new(line.as_str()),
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: width * 0.4, y: 10.0 + 20.0 * i as f32 })
+                .color(Color::WHITE),
+        )?;
+    }
+
+    for (i, &kind) in values.fruit.iter().enumerate() {
+        entities::draw_fruit(ctx, kind, 18.0 + FRUIT_SPACING * i as f32, 78.0, FRUIT_ICON_SIZE)?;
+    }
+
+    if let Some(item) = values.held_item {
+        let label = Text::new(format!("{} [Space]", item.name()));
+        let label_width = label.dimensions(ctx).w;
+        let icon = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::fill(),
+            ggez::mint::Point2 { x: width - label_width - 30.0, y: 67.0 },
+            8.0,
+            0.1,
+            item.color(),
+        )?;
+        graphics::draw(ctx, &icon, DrawParam::default())?;
+        graphics::draw(
+            ctx,
+            &label,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: width - label_width - 10.0, y: 60.0 })
+                .color(Color::WHITE),
+        )?;
+    }
+
+    if let Some(role) = &values.role {
+        let role_text = Text::new(role.as_str());
+        graphics::draw(
+            ctx,
+            &role_text,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: width - role_text.dimensions(ctx).w - 10.0, y: 10.0 })
+                .color(Color::YELLOW),
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,166 @@
+//key bindings for player actions
+use ggez::input::keyboard::KeyCode;
+use serde::{Deserialize, Serialize};
+
+use crate::Direction;
+
+//directions that can be rebound, in the order the settings screen lists them
+pub const BINDABLE_DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+//one key per movement direction, saved by key name
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bindings {
+    #[serde(with = "key_serde")]
+    pub up: KeyCode,
+    #[serde(with = "key_serde")]
+    pub down: KeyCode,
+    #[serde(with = "key_serde")]
+    pub left: KeyCode,
+    #[serde(with = "key_serde")]
+    pub right: KeyCode,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            up: KeyCode::Up,
+            down: KeyCode::Down,
+            left: KeyCode::Left,
+            right: KeyCode::Right,
+        }
+    }
+}
+
+impl Bindings {
+    pub fn key_for(&self, direction: Direction) -> Option<KeyCode> {
+        match direction {
+            Direction::Up => Some(self.up),
+            Direction::Down => Some(self.down),
+            Direction::Left => Some(self.left),
+            Direction::Right => Some(self.right),
+            Direction::None => None,
+        }
+    }
+
+    pub fn set(&mut self, direction: Direction, key: KeyCode) {
+        match direction {
+            Direction::Up => self.up = key,
+            Direction::Down => self.down = key,
+            Direction::Left => self.left = key,
+            Direction::Right => self.right = key,
+            Direction::None => {}
+        }
+    }
+
+    //maps a pressed key to its movement direction
+    pub fn direction_for(&self, key: KeyCode) -> Option<Direction> {
+        BINDABLE_DIRECTIONS
+            .iter()
+            .copied()
+            .find(|&dir| self.key_for(dir) == Some(key))
+    }
+
+    //binds a key to a direction. If another direction already used that key the two
+    //swap keys so nothing is left unbound; the displaced direction is returned
+    pub fn rebind(&mut self, direction: Direction, key: KeyCode) -> Option<Direction> {
+        let displaced = self.direction_for(key).filter(|&other| other != direction);
+        if let (Some(other), Some(old_key)) = (displaced, self.key_for(direction)) {
+            self.set(other, old_key);
+        }
+        self.set(direction, key);
+        displaced
+    }
+}
+
+//display names for every key that may be bound. Keys missing here can't be captured
+const KEY_NAMES: [(KeyCode, &str); 50] = [
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::A, "A"),
+    (KeyCode::B, "B"),
+    (KeyCode::C, "C"),
+    (KeyCode::D, "D"),
+    (KeyCode::E, "E"),
+    (KeyCode::F, "F"),
+    (KeyCode::G, "G"),
+    (KeyCode::H, "H"),
+    (KeyCode::I, "I"),
+    (KeyCode::J, "J"),
+    (KeyCode::K, "K"),
+    (KeyCode::L, "L"),
+    (KeyCode::M, "M"),
+    (KeyCode::N, "N"),
+    (KeyCode::O, "O"),
+    (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"),
+    (KeyCode::R, "R"),
+    (KeyCode::S, "S"),
+    (KeyCode::T, "T"),
+    (KeyCode::U, "U"),
+    (KeyCode::V, "V"),
+    (KeyCode::W, "W"),
+    (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"),
+    (KeyCode::Z, "Z"),
+    (KeyCode::Key0, "0"),
+    (KeyCode::Key1, "1"),
+    (KeyCode::Key2, "2"),
+    (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"),
+    (KeyCode::Key5, "5"),
+    (KeyCode::Key6, "6"),
+    (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"),
+    (KeyCode::Key9, "9"),
+    (KeyCode::Numpad2, "Numpad2"),
+    (KeyCode::Numpad4, "Numpad4"),
+    (KeyCode::Numpad6, "Numpad6"),
+    (KeyCode::Numpad8, "Numpad8"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::LShift, "LShift"),
+    (KeyCode::RShift, "RShift"),
+    (KeyCode::LControl, "LControl"),
+    (KeyCode::RControl, "RControl"),
+    (KeyCode::Tab, "Tab"),
+];
+
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES.iter().find(|(k, _)| *k == key).map(|(_, name)| *name)
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES.iter().find(|(_, n)| *n == name).map(|(k, _)| *k)
+}
+
+pub fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Up => "Up",
+        Direction::Down => "Down",
+        Direction::Left => "Left",
+        Direction::Right => "Right",
+        Direction::None => "None",
+    }
+}
+
+//(de)serializes a KeyCode through the KEY_NAMES table
+mod key_serde {
+    use ggez::input::keyboard::KeyCode;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(key: &KeyCode, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(super::key_name(*key).unwrap_or("Up"))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<KeyCode, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        super::key_from_name(&name).ok_or_else(|| de::Error::custom(format!("unknown key `{}`", name)))
+    }
+}
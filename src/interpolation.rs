@@ -0,0 +1,54 @@
+//smoothing between ticks: the simulation moves everything 60 times a second whatever the frame
+//rate, so a frame drawn between two ticks puts pacman and the ghosts part of the way from where
+//they were on the tick before to where they are now. Only drawn, so it can use floats
+use crate::fixed::Fixed;
+
+//further than anything moves in one tick, in pixels
+const MAX_STEP: f32 = 8.0;
+
+#[derive(Default)]
+pub struct Interpolation {
+    //top-left corners as of the tick before the latest one
+    pacman: (Fixed, Fixed),
+    ghosts: Vec<(Fixed, Fixed)>,
+    //how far the frame being drawn is from that tick to the next, from 0 to 1
+    blend: f32,
+}
+
+impl Interpolation {
+    //where everything is before a tick runs. Called for ticks that move nothing too, such as
+    //while paused, so the next frames don't slide back over the last step
+    pub fn remember(&mut self, pacman: (Fixed, Fixed), ghosts: impl Iterator<Item = (Fixed, Fixed)>) {
+        self.pacman = pacman;
+        self.ghosts.clear();
+        self.ghosts.extend(ghosts);
+    }
+
+    //1 draws everything where the simulation has it, with no smoothing
+    pub fn set_blend(&mut self, blend: f32) {
+        self.blend = blend.clamp(0.0, 1.0);
+    }
+
+    //pacman's top-left corner for this frame, given where he is now
+    pub fn pacman(&self, now: (Fixed, Fixed)) -> (f32, f32) {
+        self.between(self.pacman, now)
+    }
+
+    //the same for the `index`th ghost
+    pub fn ghost(&self, index: usize, now: (Fixed, Fixed)) -> (f32, f32) {
+        match self.ghosts.get(index) {
+            Some(&before) => self.between(before, now),
+            None => (now.0.to_f32(), now.1.to_f32()),
+        }
+    }
+
+    //anything that moved further than a step can in a tick went through a tunnel, across a
+    //wrapping edge or back to its spawn, and is drawn where it ended up
+    fn between(&self, before: (Fixed, Fixed), now: (Fixed, Fixed)) -> (f32, f32) {
+        let (from, to) = ((before.0.to_f32(), before.1.to_f32()), (now.0.to_f32(), now.1.to_f32()));
+        if (to.0 - from.0).abs() + (to.1 - from.1).abs() > MAX_STEP {
+            return to;
+        }
+        (from.0 + (to.0 - from.0) * self.blend, from.1 + (to.1 - from.1) * self.blend)
+    }
+}
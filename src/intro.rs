@@ -0,0 +1,140 @@
+//the attract-style intro: each ghost of the roster in turn, with its name, nickname and how it
+//hunts. Plays before a new game's first level and from the main menu, and any key skips it
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::{Context, GameResult};
+
+use crate::cutscene::Timeline;
+use crate::spawn::ROSTER;
+use crate::TICKS_PER_SECOND;
+
+//when the first ghost comes on, and how far apart the rest follow
+const FIRST_GHOST: u32 = TICKS_PER_SECOND / 2;
+const GHOST_SPACING: u32 = 3 * TICKS_PER_SECOND / 2;
+//from a ghost appearing to its name, and to its behaviour
+const NAME_DELAY: u32 = TICKS_PER_SECOND / 2;
+const BEHAVIOR_DELAY: u32 = TICKS_PER_SECOND;
+//how long the whole roster stays up at the end
+const HOLD: u32 = 3 * TICKS_PER_SECOND;
+const BLINK_TICKS: u32 = TICKS_PER_SECOND / 2;
+
+enum Cue {
+    Heading,
+    Ghost(usize),
+    Name(usize),
+    Behavior(usize),
+    Prompt,
+}
+
+pub struct Intro {
+    timeline: Timeline<Cue>,
+    //opened from the menu, so closing it goes back there rather than into a game
+    pub from_menu: bool,
+}
+
+impl Intro {
+    pub fn new(from_menu: bool) -> Self {
+        let end = FIRST_GHOST + ROSTER.len() as u32 * GHOST_SPACING;
+        let mut timeline = Timeline::new(end + HOLD).cue(0, Cue::Heading);
+        for i in 0..ROSTER.len() {
+            let start = FIRST_GHOST + i as u32 * GHOST_SPACING;
+            timeline = timeline
+                .cue(start, Cue::Ghost(i))
+                .cue(start + NAME_DELAY, Cue::Name(i))
+                .cue(start + BEHAVIOR_DELAY, Cue::Behavior(i));
+        }
+        Intro {
+            timeline: timeline.cue(end, Cue::Prompt),
+            from_menu,
+        }
+    }
+
+    //one tick of the timeline
+    pub fn advance(&mut self) {
+        self.timeline.advance();
+    }
+
+    pub fn finished(&self) -> bool {
+        self.timeline.finished()
+    }
+
+    pub fn draw(&self, ctx: &mut Context) -> GameResult {
+        let (w, h) = graphics::drawable_size(ctx);
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, 0.0, w, h),
+            Color::BLACK,
+        )?;
+        graphics::draw(ctx, &background, DrawParam::default())?;
+
+        let row_y = |i: usize| h * 0.3 + i as f32 * 90.0;
+        let grey = Color::new(0.7, 0.7, 0.7, 1.0);
+        for (cue, ago) in self.timeline.fired() {
+            match *cue {
+                Cue::Heading => {
+                    let heading = graphics::Text::new("CHARACTER  /  NICKNAME");
+                    let dims = heading.dimensions(ctx);
+                    graphics::draw(
+                        ctx,
+                        &heading,
+                        DrawParam::default()
+                            .dest(ggez::mint::Point2 { x: (w - dims.w * 1.5) / 2.0, y: h * 0.15 })
+                            .color(Color::WHITE)
+                            .scale([1.5, 1.5]),
+                    )?;
+                }
+                Cue::Ghost(i) => {
+                    let ghost = graphics::Mesh::new_circle(
+                        ctx,
+                        graphics::DrawMode::fill(),
+                        ggez::mint::Point2 { x: w * 0.15, y: row_y(i) + 10.0 },
+                        20.0,
+                        0.1,
+                        ROSTER[i].color,
+                    )?;
+                    graphics::draw(ctx, &ghost, DrawParam::default())?;
+                }
+                Cue::Name(i) => {
+                    let spec = &ROSTER[i];
+                    let name = graphics::Text::new(format!(
+                        "-{:<10}\"{}\"",
+                        spec.name.to_uppercase(),
+                        spec.nickname.to_uppercase()
+                    ));
+                    graphics::draw(
+                        ctx,
+                        &name,
+                        DrawParam::default()
+                            .dest(ggez::mint::Point2 { x: w * 0.25, y: row_y(i) })
+                            .color(spec.color)
+                            .scale([1.5, 1.5]),
+                    )?;
+                }
+                Cue::Behavior(i) => {
+                    let behavior = graphics::Text::new(ROSTER[i].personality.behavior());
+                    graphics::draw(
+                        ctx,
+                        &behavior,
+                        DrawParam::default()
+                            .dest(ggez::mint::Point2 { x: w * 0.25, y: row_y(i) + 30.0 })
+                            .color(grey),
+                    )?;
+                }
+                //blinks, on first
+                Cue::Prompt if (ago / BLINK_TICKS).is_multiple_of(2) => {
+                    let prompt = graphics::Text::new("Press any key");
+                    let dims = prompt.dimensions(ctx);
+                    graphics::draw(
+                        ctx,
+                        &prompt,
+                        DrawParam::default()
+                            .dest(ggez::mint::Point2 { x: (w - dims.w) / 2.0, y: h * 0.85 })
+                            .color(Color::YELLOW),
+                    )?;
+                }
+                Cue::Prompt => {}
+            }
+        }
+        Ok(())
+    }
+}
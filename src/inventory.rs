@@ -0,0 +1,33 @@
+//power-ups pacman carries instead of using on the spot. Picking one up fills his single slot,
+//replacing whatever was in it, and Space sets it off when the player chooses
+use ggez::graphics::Color;
+use serde::{Deserialize, Serialize};
+
+//how near pacman, in cells, a bomb sends the ghosts home
+pub const BOMB_RADIUS: f32 = 3.0;
+//how long a freeze stops the ghosts
+pub const FREEZE_SECONDS: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PowerUp {
+    //ghosts close to pacman are sent back to the pen, with no points for them
+    Bomb,
+    //every ghost stops where it is for a while
+    Freeze,
+}
+
+impl PowerUp {
+    pub fn name(self) -> &'static str {
+        match self {
+            PowerUp::Bomb => "Bomb",
+            PowerUp::Freeze => "Freeze",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            PowerUp::Bomb => Color::new(1.0, 0.5, 0.0, 1.0),
+            PowerUp::Freeze => Color::new(0.6, 0.9, 1.0, 1.0),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//the worker pool independent per-entity work in a tick is spread over. Results always come back in
+//input order and jobs never touch the rng, so a tick plays out the same on any number of threads
+use rayon::prelude::*;
+
+pub struct Jobs {
+    threads: usize,
+    //started by the first job big enough to share out, so games that never have one (or run
+    //many at once, like --simulate) don't keep idle threads around
+    pool: Option<rayon::ThreadPool>,
+}
+
+impl Jobs {
+    pub fn new(threads: usize) -> Jobs {
+        Jobs { threads: threads.max(1), pool: None }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    fn pool(&mut self) -> Option<&rayon::ThreadPool> {
+        if self.pool.is_none() && self.threads > 1 {
+            match rayon::ThreadPoolBuilder::new()
+                .num_threads(self.threads)
+                .thread_name(|i| format!("sim-worker-{}", i))
+                .build()
+            {
+                Ok(pool) => self.pool = Some(pool),
+                Err(e) => {
+                    eprintln!("Warning: running the simulation on one thread: {}", e);
+                    self.threads = 1;
+                }
+            }
+        }
+        self.pool.as_ref()
+    }
+
+    //fills `out` with `f` of every item. Lists shorter than two `min_len` chunks aren't worth
+    //handing to the workers and run here. `out` is reused so steady ticks don't allocate
+    pub fn map_into<T, R, F>(&mut self, items: &[T], min_len: usize, out: &mut Vec<R>, f: F)
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&T) -> R + Sync + Send,
+    {
+        let pool = if items.len() >= min_len * 2 { self.pool() } else { None };
+        match pool {
+            Some(pool) => pool.install(|| items.par_iter().with_min_len(min_len).map(f).collect_into_vec(out)),
+            None => {
+                out.clear();
+                out.extend(items.iter().map(f));
+            }
+        }
+    }
+
+    //map_into over `items` paired up with `with`, e.g. each ghost with what an earlier job found
+    //for it. Stops at the shorter of the two
+    pub fn zip_map_into<T, U, R, F>(&mut self, items: &[T], with: &[U], min_len: usize, out: &mut Vec<R>, f: F)
+    where
+        T: Sync,
+        U: Sync,
+        R: Send,
+        F: Fn(&T, &U) -> R + Sync + Send,
+    {
+        let len = items.len().min(with.len());
+        let pool = if len >= min_len * 2 { self.pool() } else { None };
+        match pool {
+            Some(pool) => pool.install(|| {
+                items.par_iter().zip(with).with_min_len(min_len).map(|(item, with)| f(item, with)).collect_into_vec(out)
+            }),
+            None => {
+                out.clear();
+                out.extend(items.iter().zip(with).map(|(item, with)| f(item, with)));
+            }
+        }
+    }
+}
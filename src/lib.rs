@@ -0,0 +1,4544 @@
+//ggez for GUI
+use ggez::{Context, ContextBuilder, GameResult};
+use ggez::event::{self, EventHandler};
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::input::keyboard::{KeyCode, KeyMods};
+use ggez::timer;
+use ggez::winit::window::UserAttentionType;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+use std::thread;
+
+mod accessibility;
+mod cheats;
+pub mod collision;
+mod console;
+mod crash;
+mod crash_dialog;
+mod cutscene;
+mod effects;
+mod dots;
+mod entities;
+pub mod env;
+mod events;
+mod frame_export;
+mod game_over_menu;
+mod ghost_mode;
+mod ghost_trace;
+mod heatmap;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fixed;
+mod history_screen;
+mod hud;
+mod lobby;
+mod input;
+mod intro;
+mod interpolation;
+mod inventory;
+mod jobs;
+mod main_menu;
+mod maze;
+pub mod map;
+pub mod map_import;
+mod memento;
+mod mods;
+mod netplay;
+pub mod movement;
+pub mod observer;
+mod paths;
+pub mod pathfinding;
+mod pause_menu;
+mod pellets;
+mod photo;
+mod pool;
+mod practice;
+mod profiling;
+mod proximity;
+#[cfg(feature = "python")]
+mod python;
+mod race;
+mod regrow;
+pub mod raster;
+mod remote;
+mod render_scale;
+mod replay;
+pub mod rules;
+mod save;
+mod scores;
+mod settings;
+mod settings_screen;
+mod share;
+mod simulate;
+mod spawn;
+mod speed;
+mod sprites;
+mod stats;
+mod telemetry;
+mod tutorial;
+mod twitch;
+mod update_check;
+mod widgets;
+
+use accessibility::{Announcer, Palette, SCORE_MILESTONE};
+use cheats::{CheatAction, CheatMenu, Cheats};
+use collision::{Tunnels, WallGrid};
+use console::{CommandRegistry, Console, ConsoleAction};
+use crash_dialog::{CrashAction, CrashDialog};
+use dots::DotGrid;
+use effects::Effects;
+use entities::{EntityEffect, EntityPlugin, EntityRegistry, Fruit, FruitKind, Hazard, Pickup, SavedEntity, World};
+use events::{Effect, EventBus};
+use fixed::Fixed;
+use frame_export::FrameSink;
+use game_over_menu::{GameOverAction, GameOverMenu};
+use history_screen::HistoryScreen;
+use ghost_mode::{GhostMode, GhostModeTimer};
+use ghost_trace::{Activity, GhostTrace, TracedGhost};
+use heatmap::{Heatmap, HeatmapLayer};
+use hud::{HudCanvas, HudValues};
+use intro::Intro;
+use interpolation::Interpolation;
+use inventory::{PowerUp, BOMB_RADIUS, FREEZE_SECONDS};
+use jobs::Jobs;
+use lobby::{LobbyAction, LobbyScreen};
+use main_menu::{AttractAction, MainMenu, MenuAction};
+use maze::Layout;
+use mods::{Hook, ModAction, ModHost, ModView};
+use observer::{GameEvent, GameView, GhostView, Observer};
+use pause_menu::{PauseAction, PauseMenu};
+use pellets::PowerPellets;
+use photo::{PhotoAction, PhotoMode};
+use practice::{PracticeAction, PracticeOptions, PracticeScreen};
+use netplay::{Session, Spectator, MAX_ROLLBACK};
+use profiling::profile_scope;
+use race::{Race, RaceOutcome, Standing};
+use regrow::Regrowth;
+use remote::RemoteServer;
+use render_scale::RenderScale;
+use map::Map;
+use replay::{Replay, ReplayOptions, ReplayPlayer, ReplayRecorder};
+use rules::Ruleset;
+use save::Snapshot;
+use spawn::SpawnPoints;
+use speed::{GhostSpeeds, SpeedTable, Speeds};
+use scores::{GameRecord, ScoreDb};
+use settings::{Assists, Difficulty, PelletStacking, Settings};
+use telemetry::{Event, Telemetry};
+use update_check::UpdateCheck;
+use settings_screen::SettingsScreen;
+use share::Challenge;
+use stats::{GameStats, StatsFormat, StatsMark};
+use tutorial::Tutorial;
+use twitch::TwitchChat;
+use std::path::{Path, PathBuf};
+
+//constants for sizes, movement speeds, and durations
+pub const CELL_SIZE: f32 = 30.0;
+pub const PACMAN_SIZE: f32 = 25.0;
+const DOT_SIZE: f32 = 6.0;
+pub const GHOST_SIZE: f32 = 25.0;
+pub const THIN_WALL_SIZE: f32 = 30.0;
+const POWER_PELLET_SIZE: f32 = 15.0;
+const TICKS_PER_SECOND: u32 = 60;
+const TICK_DT: f32 = 1.0 / TICKS_PER_SECOND as f32;
+//the sizes above in the simulation's fixed point
+const CELL: Fixed = Fixed::from_raw((CELL_SIZE * fixed::SCALE) as i32);
+const PACMAN: Fixed = Fixed::from_raw((PACMAN_SIZE * fixed::SCALE) as i32);
+const GHOST: Fixed = Fixed::from_raw((GHOST_SIZE * fixed::SCALE) as i32);
+//ghosts wandering off pick a random target in this square, from the top-left corner
+const WANDER_AREA: Fixed = Fixed::from_int(600);
+//how many ticks a spectator may play in one frame while catching up with the match
+const SPECTATOR_CATCH_UP_TICKS: usize = 4;
+const INJECTED_EFFECT_DURATION: f32 = 8.0;
+const SLOW_SPEED_FACTOR: f32 = 0.5;
+//how long the intersection assist holds the game at a junction
+const INTERSECTION_PAUSE_TICKS: u32 = TICKS_PER_SECOND / 2;
+//for eating four ghosts on one power pellet, as the arcade's roster has. A smaller roster can't
+//earn it and a bigger one earns it on the fourth
+const ALL_GHOSTS_BONUS: u32 = 12000;
+const ALL_GHOSTS_COUNT: u32 = 4;
+//the first ghost eaten on a power pellet, each one after doubling it up to the last entry
+const GHOST_POINTS: [u32; 4] = [200, 400, 800, 1600];
+//how much faster the ghosts get with each New Game+
+const PRESTIGE_GHOST_BOOST: f32 = 0.1;
+//pixels pacman covers in one open-and-shut of his mouth, and how wide it opens, in radians
+//either side of where he faces
+const CHOMP_DISTANCE: f32 = 15.0;
+const MOUTH_ANGLE: f32 = 0.8;
+//the fewest ghosts a worker thread is handed at once
+const GHOSTS_PER_JOB: usize = 4;
+//particles thrown out when a ghost or a power pellet is eaten
+const GHOST_BURST: usize = 16;
+const PELLET_BURST: usize = 10;
+//after losing a life the board freezes for this long, showing READY! for the last part once
+//everyone is back at the start
+const LIFE_LOST_TICKS: u32 = 2 * TICKS_PER_SECOND;
+const READY_TICKS: u32 = TICKS_PER_SECOND;
+//once play resumes pacman can't be caught again for this long, so a ghost still sitting on
+//the spawn can't take a second life straight away
+const INVULNERABLE_TICKS: u32 = TICKS_PER_SECOND;
+//a cleared maze holds still this long with its walls flashing before the next level, switching
+//colour every LEVEL_FLASH_TICKS
+const LEVEL_COMPLETE_TICKS: u32 = 2 * TICKS_PER_SECOND;
+const LEVEL_FLASH_TICKS: u32 = 12;
+//after a lost life the ghosts leave the pen on the dots eaten since: the second ghost at 7, the
+//third at 17 and the fourth at 32, as in the arcade. The first goes straight away
+const GLOBAL_DOT_THRESHOLDS: [u32; 3] = [7, 17, 32];
+//pacman going this long without a dot lets the next ghost out anyway
+const PEN_IDLE_TICKS: u32 = 4 * TICKS_PER_SECOND;
+//how much longer each time a ghost is eaten again on a level keeps it in the pen, under the
+//house arrest rule
+const HOUSE_ARREST_FACTOR: f32 = 1.5;
+//band above the maze kept for the HUD, so its lines never cover a maze row
+const HUD_HEIGHT: f32 = 90.0;
+//the most recent fruit the HUD's history shows, as many as the arcade's row
+const FRUIT_HISTORY: usize = 7;
+const WINDOW_TITLE: &str = "Pac-Man";
+//a ghost passing closer than a cell to pacman without catching him slows the game to this
+//percent of its speed, zoomed in a touch, for a moment of real time. Not again for a while
+const CLOSE_CALL_SPEED_PERCENT: u32 = 35;
+const CLOSE_CALL_ZOOM: f32 = 0.06;
+const CLOSE_CALL_DURATION: Duration = Duration::from_millis(300);
+const CLOSE_CALL_COOLDOWN: Duration = Duration::from_secs(5);
+//the edge glow is this many strips of this width, fading in towards the middle
+const GLOW_STRIPS: usize = 8;
+const GLOW_STRIP_WIDTH: f32 = 4.0;
+//frightened ghosts running out of fright swap colours this often, in seconds
+const FRIGHTENED_FLASH_PERIOD: f32 = 0.2;
+//the shading over rows and columns locked against regrowth
+const LOCKED_LINE: Color = Color::new(0.0, 1.0, 0.5, 0.08);
+//the ghost house door, drawn as a pink bar this thick
+const DOOR_COLOR: Color = Color::new(1.0, 0.72, 1.0, 1.0);
+const DOOR_THICKNESS: f32 = 6.0;
+//frame rate while the window is in the background
+const UNFOCUSED_FPS: u32 = 10;
+//per-tick odds of a ghost picking a new target, and of that target being a random spot
+const RETARGET_CHANCE: f64 = 0.05;
+const CONFUSED_RETARGET_CHANCE: f64 = 0.1;
+const WANDER_CHANCE: f64 = 0.6;
+//how far an eaten ghost's eyes move in a tick on their way home, whatever the level
+const EYES_SPEED: Fixed = Fixed::from_int(2);
+
+//where a round is between life losses and cleared mazes. While a life is lost nothing moves,
+//and the tick only counts down to READY! and the restart. A cleared maze counts down the same
+//way to the next level. The last life lost ends the game for good
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum Round {
+    #[default]
+    Playing,
+    LifeLost { ticks_left: u32 },
+    LevelComplete { ticks_left: u32 },
+    GameOver,
+}
+
+//what the window shows, which decides where update, draw and the keys go. The game only runs
+//while Playing. Losing a life and clearing a maze happen inside a game, so they're rounds, kept in
+//saves and undone by versus rollbacks along with the rest of it. The settings, the console and the
+//other screens that open over any of these keep fields of their own
+enum GameScreen {
+    //the title screen, over the attract mode's demo
+    Menu(MainMenu),
+    Playing,
+    Paused(PauseMenu),
+    GameOver(GameOverMenu),
+    //the full-window menus. Settings opens over any other screen and goes back to it when closed,
+    //the rest are opened from the title screen and go back to it
+    Settings(SettingsScreen, Box<GameScreen>),
+    History(HistoryScreen),
+    Lobby(LobbyScreen),
+    Practice(PracticeScreen),
+}
+
+impl GameScreen {
+    //the screen the settings screen is open over, or this one when it isn't
+    fn beneath_settings(&self) -> &GameScreen {
+        match self {
+            GameScreen::Settings(_, beneath) => beneath,
+            screen => screen,
+        }
+    }
+
+    fn beneath_settings_mut(&mut self) -> &mut GameScreen {
+        match self {
+            GameScreen::Settings(_, beneath) => beneath,
+            screen => screen,
+        }
+    }
+}
+
+//how a ghost hunts. A chaser goes after pacman more often than the rest and a wanderer
+//less, while the three of them together wander as much as before
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum Personality {
+    Chaser,
+    #[default]
+    Drifter,
+    Wanderer,
+}
+
+impl Personality {
+    //how the intro describes it
+    pub fn behavior(self) -> &'static str {
+        match self {
+            Personality::Chaser => "chases you directly",
+            Personality::Drifter => "drifts between chasing and roaming",
+            Personality::Wanderer => "wanders off more than it hunts",
+        }
+    }
+
+    //odds of a fresh target being a random spot instead of pacman
+    fn wander_chance(self) -> f64 {
+        match self {
+            Personality::Chaser => WANDER_CHANCE - 0.2,
+            Personality::Drifter => WANDER_CHANCE,
+            Personality::Wanderer => WANDER_CHANCE + 0.2,
+        }
+    }
+}
+
+//whether a ghost is about, or was eaten and is only a pair of eyes making for the pen
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum GhostState {
+    #[default]
+    Active,
+    Eaten,
+}
+
+//derive clone, copy, and equality from direction
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    None,
+}
+
+impl Direction {
+    //how far `distance` moves you this way
+    fn offset(self, distance: Fixed) -> (Fixed, Fixed) {
+        match self {
+            Direction::Up => (Fixed::ZERO, -distance),
+            Direction::Down => (Fixed::ZERO, distance),
+            Direction::Left => (-distance, Fixed::ZERO),
+            Direction::Right => (distance, Fixed::ZERO),
+            Direction::None => (Fixed::ZERO, Fixed::ZERO),
+        }
+    }
+}
+
+//the directions a ghost found no wall in, in Up, Down, Left, Right order
+#[derive(Clone, Copy)]
+pub struct OpenDirections {
+    directions: [Direction; 4],
+    count: usize,
+}
+
+impl OpenDirections {
+    pub fn as_slice(&self) -> &[Direction] {
+        &self.directions[..self.count]
+    }
+}
+
+//position arguments, directions, colors, and timers
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Ghost {
+    x: Fixed,
+    y: Fixed,
+    direction: Direction,
+    #[serde(with = "save::color_serde")]
+    color: Color,
+    target_x: Fixed,
+    target_y: Fixed,
+    //seconds of fright left from the power pellets this ghost was in play for
+    #[serde(default)]
+    frightened_timer: f32,
+    respawn_timer: f32,
+    spawn_position: (Fixed, Fixed),
+    confused_timer: f32,
+    //Some when a player steers this ghost instead of the AI, holding their latest input
+    #[serde(default)]
+    player_direction: Option<Direction>,
+    //ticks until the AI picks a new target
+    #[serde(default)]
+    decision_timer: u32,
+    #[serde(default)]
+    personality: Personality,
+    //ticks the ghost waits in the pen at the start of a round, and how many of them are left
+    #[serde(default)]
+    release_delay: u32,
+    #[serde(default)]
+    release_ticks: u32,
+    #[serde(default)]
+    speeds: GhostSpeeds,
+    //times pacman has eaten this ghost on the current level, for the house arrest rule
+    #[serde(default)]
+    times_eaten: u32,
+    //how much keener than its personality the ghost is right now, under the maze's aggression
+    //curve
+    #[serde(default)]
+    keenness: Keenness,
+    //scattering ghosts head for this corner instead of after pacman
+    #[serde(default)]
+    mode: GhostMode,
+    #[serde(default)]
+    scatter_target: (Fixed, Fixed),
+    #[serde(default)]
+    state: GhostState,
+}
+
+//shares taken off a ghost's odds of roaming and its time between targets, from 0 to 1
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+struct Keenness {
+    wander: f32,
+    retarget: f32,
+}
+
+impl Ghost {
+    //ghost struct with following values
+    pub fn new(x: f32, y: f32, color: Color) -> Self {
+        let (x, y) = (Fixed::from_f32(x), Fixed::from_f32(y));
+        Ghost {
+            x,
+            y,
+            direction: Direction::Left,
+            color,
+            target_x: x,
+            target_y: y,
+            frightened_timer: 0.0,
+            respawn_timer: 0.0,
+            spawn_position: (x, y),
+            confused_timer: 0.0,
+            player_direction: None,
+            decision_timer: 0,
+            personality: Personality::default(),
+            release_delay: 0,
+            release_ticks: 0,
+            speeds: GhostSpeeds::default(),
+            times_eaten: 0,
+            keenness: Keenness::default(),
+            mode: GhostMode::default(),
+            scatter_target: (x, y),
+            state: GhostState::Active,
+        }
+    }
+
+    //top-left corner of the ghost's box
+    pub fn position(&self) -> (f32, f32) {
+        (self.x.to_f32(), self.y.to_f32())
+    }
+
+    //how far the ghost moves in a tick, slower while frightened and slower still in a tunnel.
+    //Eyes always hurry
+    fn step(&self, walls: &WallGrid, speed_factor: f32) -> Fixed {
+        if self.state == GhostState::Eaten {
+            return EYES_SPEED;
+        }
+        let mut step = if self.is_vulnerable() { self.speeds.frightened } else { self.speeds.normal };
+        if walls.in_tunnel(ghost_cell(self.x, self.y)) {
+            step = step.min(self.speeds.tunnel);
+        }
+        step.scale(speed_factor)
+    }
+
+    //for updating the graphics
+    pub fn update(&mut self, walls: &WallGrid, pacman_x: f32, pacman_y: f32, speed_factor: f32, rng: &mut Pcg32) {
+        let open = self.open_directions(walls, speed_factor);
+        let pacman = (Fixed::from_f32(pacman_x), Fixed::from_f32(pacman_y));
+        self.steer(&open, walls, pacman, speed_factor, rng);
+    }
+
+    //the ways the ghost could move this tick. Only reads the ghost and the walls, so the ghosts'
+    //checks can run on worker threads before each one steers
+    pub fn open_directions(&self, walls: &WallGrid, speed_factor: f32) -> OpenDirections {
+        //this runs for every ghost every tick, so the open ways are kept on the stack
+        let speed = self.step(walls, speed_factor);
+        let mut open = OpenDirections { directions: [Direction::None; 4], count: 0 };
+
+        for dir in [Direction::Up, Direction::Down, Direction::Left, Direction::Right] {
+            //potential direction
+            let (test_dx, test_dy) = dir.offset(speed);
+
+            //pushing direction based on wall
+            if !collision::box_hits_wall(walls, self.x + test_dx, self.y + test_dy, GHOST, GHOST) {
+                open.directions[open.count] = dir;
+                open.count += 1;
+            }
+        }
+        open
+    }
+
+    //picks a direction out of `open` and moves. A tick splits this up into roll, path_step and
+    //follow, so the path searches can run on the workers
+    pub fn steer(&mut self, open: &OpenDirections, walls: &WallGrid, pacman: (Fixed, Fixed), speed_factor: f32, rng: &mut Pcg32) {
+        self.roll(open, pacman, rng);
+        let step = self.path_step(open, walls);
+        self.follow(step, walls, speed_factor);
+    }
+
+    //the part of steering that rolls the dice: a new target when a decision is due and a random
+    //way for a confused ghost, plus a player's turns. Ghosts roll one at a time, in order, so the
+    //rng is drawn the same however the rest of the tick is spread out
+    pub fn roll(&mut self, open: &OpenDirections, pacman: (Fixed, Fixed), rng: &mut Pcg32) {
+        profile_scope!("ghost_ai");
+        //a player picks the direction below, the AI only rolls the dice when a decision is due
+        let mut decided = false;
+        if self.player_direction.is_none() {
+            if self.decision_timer == 0 {
+                self.decide(pacman, rng);
+                decided = true;
+            } else {
+                self.decision_timer -= 1;
+            }
+        }
+
+        //Choose direction based on target position and available paths
+        let valid_directions = open.as_slice();
+
+        //a player's ghost turns as soon as the requested way is open and otherwise keeps going
+        if let Some(requested) = self.player_direction {
+            if valid_directions.contains(&requested) {
+                self.direction = requested;
+            } else if !valid_directions.contains(&self.direction) {
+                self.direction = Direction::None;
+            }
+        } else if !valid_directions.is_empty() && self.confused_timer > 0.0 {
+            //a confused ghost wanders: a random way at each decision, or when its way is blocked
+            if decided || !valid_directions.contains(&self.direction) {
+                self.direction = valid_directions[rng.gen_range(0..valid_directions.len())];
+            }
+        }
+    }
+
+    //the way along the shortest path to the target, for eyes and for AI ghosts that aren't
+    //confused, once roll has picked the target. Only reads the ghost and the walls, so the
+    //ghosts' searches can run on worker threads
+    pub fn path_step(&self, open: &OpenDirections, walls: &WallGrid) -> Option<Direction> {
+        let valid_directions = open.as_slice();
+        if valid_directions.is_empty() {
+            return None;
+        }
+        if self.is_eyes() {
+            return Some(self.toward_target(self.spawn_position, valid_directions, walls));
+        }
+        if self.player_direction.is_some() || self.confused_timer > 0.0 {
+            return None;
+        }
+        Some(self.toward_target((self.target_x, self.target_y), valid_directions, walls))
+    }
+
+    //turns onto `step` if path_step found one and moves
+    pub fn follow(&mut self, step: Option<Direction>, walls: &WallGrid, speed_factor: f32) {
+        if let Some(step) = step {
+            self.direction = step;
+        }
+        self.advance(walls, speed_factor);
+    }
+
+    //the first step of the shortest path to `target`'s cell, once the ghost is lined up to take
+    //it. Until then, or when it's as near as it can get, it keeps going, and when it can't it
+    //heads whichever way is closest, measured across the edges too on a maze that wraps
+    fn toward_target(&self, target: (Fixed, Fixed), valid_directions: &[Direction], walls: &WallGrid) -> Direction {
+        let path = walls.paths().first_step(ghost_cell(self.x, self.y), ghost_cell(target.0, target.1));
+        match path {
+            Some(step) if valid_directions.contains(&step) => step,
+            _ if valid_directions.contains(&self.direction) => self.direction,
+            _ => *valid_directions.iter().min_by_key(|&&dir| {
+                let (test_dx, test_dy) = dir.offset(Fixed::from_int(1));
+                let (dx, dy) = walls.delta((self.x + test_dx, self.y + test_dy), target);
+                dx.squared() + dy.squared()
+            }).unwrap_or(&Direction::None),
+        }
+    }
+
+    //moves the ghost a step its current way, unless that runs into a wall
+    fn advance(&mut self, walls: &WallGrid, speed_factor: f32) {
+        let (dx, dy) = self.direction.offset(self.step(walls, speed_factor));
+
+        let new_x = self.x + dx;
+        let new_y = self.y + dy;
+
+        if !collision::box_hits_wall(walls, new_x, new_y, GHOST, GHOST) {
+            (self.x, self.y) = walls.wrap_box(new_x, new_y, GHOST);
+        }
+    }
+
+    //eyes take `step`, path_step's shortest way back to the spawn, and turn back into a ghost
+    //once they're in its cell. No dice are rolled, so they can move alongside the ghosts that steer
+    pub fn return_home(&mut self, step: Option<Direction>, walls: &WallGrid) {
+        if ghost_cell(self.x, self.y) == ghost_cell(self.spawn_position.0, self.spawn_position.1) {
+            self.arrive_home();
+            return;
+        }
+        (self.target_x, self.target_y) = self.spawn_position;
+        self.follow(step, walls, 1.0);
+    }
+
+    //Confused timer to introduce a bit of rng here. Prevents ghosts from streamlining to you.
+    //Picks the next target and how long to follow it, as often on average as the old per-tick
+    //coin flips: a new target 5% of ticks (10% while confused), random 60% of the time. Keenness
+    //cuts both down, except while confused
+    fn decide(&mut self, pacman: (Fixed, Fixed), rng: &mut Pcg32) {
+        //a scattering ghost has its corner to go to, and looks again next tick
+        if self.mode == GhostMode::Scatter && self.confused_timer <= 0.0 {
+            (self.target_x, self.target_y) = self.scatter_target;
+            return;
+        }
+        let (chance, wander, hold) = if self.confused_timer > 0.0 {
+            (CONFUSED_RETARGET_CHANCE, 1.0, 1.0)
+        } else {
+            let keenness = self.keenness;
+            (
+                RETARGET_CHANCE,
+                self.personality.wander_chance() * (1.0 - keenness.wander as f64),
+                1.0 - keenness.retarget as f64,
+            )
+        };
+        if rng.gen_bool(wander) {
+            self.target_x = Fixed::from_raw(rng.gen_range(0..WANDER_AREA.raw()));
+            self.target_y = Fixed::from_raw(rng.gen_range(0..WANDER_AREA.raw()));
+        } else {
+            self.target_x = pacman.0;
+            self.target_y = pacman.1;
+        }
+        //uniform with the same mean as the ticks until a `chance` coin first comes up heads. A
+        //geometric draw would need ln(), which isn't bit-identical across platforms
+        let mean = ((1.0 - chance) / chance * hold).round() as u32;
+        self.decision_timer = rng.gen_range(0..=2 * mean);
+    }
+
+    //follows the schedule into `mode`, turning round unless a player is steering it
+    fn switch_mode(&mut self, mode: GhostMode) {
+        self.mode = mode;
+        self.decision_timer = 0;
+        if self.player_direction.is_none() {
+            self.direction = match self.direction {
+                Direction::Up => Direction::Down,
+                Direction::Down => Direction::Up,
+                Direction::Left => Direction::Right,
+                Direction::Right => Direction::Left,
+                Direction::None => Direction::None,
+            };
+        }
+    }
+
+    //becomes confused for `seconds`, deciding afresh straight away
+    fn confuse(&mut self, seconds: f32) {
+        self.confused_timer = seconds;
+        self.decision_timer = 0;
+    }
+
+    //back to the spawn, e.g. after pacman loses a life
+    fn reset_position(&mut self) {
+        self.state = GhostState::Active;
+        self.x = self.spawn_position.0;
+        self.y = self.spawn_position.1;
+        self.frightened_timer = 0.0;
+        self.respawn_timer = 0.0;
+        self.direction = Direction::Left;
+        self.release_ticks = self.release_delay;
+        self.confuse(3.0);
+    }
+
+    //waits `ticks` in the pen before chasing, now and after every reset
+    fn hold_in_pen(&mut self, ticks: u32) {
+        self.release_delay = ticks;
+        self.release_ticks = ticks;
+    }
+
+    //eaten by pacman: only the eyes are left, and they make for the spawn. Once there the
+    //ghost waits out `seconds` in the pen, no longer frightened when it rejoins
+    fn eaten(&mut self, seconds: f32) {
+        self.state = GhostState::Eaten;
+        self.frightened_timer = 0.0;
+        self.confused_timer = 0.0;
+        //counts down from when the eyes get home
+        self.respawn_timer = seconds;
+        self.release_ticks = 0;
+    }
+
+    //the eyes made it back: a whole ghost again, on its spawn until the respawn wait is over
+    fn arrive_home(&mut self) {
+        let seconds = self.respawn_timer;
+        self.reset_position();
+        self.respawn_timer = seconds;
+        self.release_ticks = 0;
+    }
+
+    //frightened by a power pellet: for `seconds`, or with `extend` that much longer than it
+    //already was
+    fn frighten(&mut self, seconds: f32, extend: bool) {
+        if extend {
+            self.frightened_timer += seconds;
+        } else {
+            self.frightened_timer = seconds;
+        }
+    }
+
+    //blue, and eaten instead of eating pacman
+    pub fn is_vulnerable(&self) -> bool {
+        self.frightened_timer > 0.0
+    }
+
+    //whether the ghost is in the maze, as opposed to eyes on their way home or waiting out a
+    //respawn
+    fn in_play(&self) -> bool {
+        self.state == GhostState::Active && self.respawn_timer <= 0.0
+    }
+
+    //moves this tick: eyes going home and ghosts in play and out of the pen
+    fn moves(&self) -> bool {
+        self.is_eyes() || (self.in_play() && self.release_ticks == 0)
+    }
+
+    //only the eyes are left, heading for the pen
+    pub fn is_eyes(&self) -> bool {
+        self.state == GhostState::Eaten
+    }
+
+    //what the ghost is up to, for the ghost trace
+    fn activity(&self) -> Activity {
+        if self.is_eyes() {
+            Activity::Eyes
+        } else if !self.in_play() || self.release_ticks > 0 {
+            Activity::Waiting
+        } else if self.player_direction.is_some() {
+            Activity::Steered
+        } else if self.confused_timer > 0.0 {
+            Activity::Confused
+        } else if self.is_vulnerable() {
+            Activity::Frightened
+        } else if self.mode == GhostMode::Scatter {
+            Activity::Scattering
+        } else {
+            Activity::Chasing
+        }
+    }
+}
+
+//where a versus game stood at the start of a tick
+struct RollbackFrame {
+    tick: u32,
+    snapshot: Snapshot,
+    stats: StatsMark,
+}
+
+//state of the game
+struct MainState {
+    pacman_x: Fixed,
+    pacman_y: Fixed,
+    current_direction: Direction,
+    requested_direction: Direction,
+    held_directions: Vec<Direction>,
+    //the walls the ghosts move between, and the same with the ghost house's door shut, for pacman
+    //and everything else
+    walls: WallGrid,
+    pacman_walls: WallGrid,
+    layout: Layout,
+    dots: DotGrid,
+    ghosts: Vec<Ghost>,
+    spawns: SpawnPoints,
+    //the maze being played, and its hash for replays and versus games
+    map: Map,
+    map_hash: String,
+    //the maze's own rules, in place of the arcade's
+    ruleset: Ruleset,
+    //how many dots the maze starts with, for counting how many a level has eaten
+    maze_dots: usize,
+    //dots eaten this level, for the fruit. Dots growing back don't take any off, so a fruit's
+    //count is only reached once
+    level_dots_eaten: u32,
+    //Some when the maze's rules have eaten dots grow back
+    regrowth: Option<Regrowth>,
+    score: u32,
+    lives: i32,
+    //where pacman's mouth is in its chomp, from 0 to 1, and the way he last moved
+    mouth_phase: f32,
+    facing: Direction,
+    power_pellets: PowerPellets,
+    power_pellet_active: bool,
+    power_pellet_timer: f32,
+    //worker threads for the parts of a tick that can run side by side
+    jobs: Jobs,
+    //per-tick scratch for the jobs' results, kept to avoid reallocating
+    ghost_open: Vec<OpenDirections>,
+    ghost_steps: Vec<Option<Direction>>,
+    settings: Settings,
+    seed: u64,
+    rng: Pcg32,
+    recorder: Option<ReplayRecorder>,
+    playback: Option<ReplayPlayer>,
+    stats: GameStats,
+    stats_path: Option<PathBuf>,
+    screen: GameScreen,
+    //when the pause began
+    paused_at: Option<Instant>,
+    //Some while taking pictures of the paused game
+    photo: Option<PhotoMode>,
+    pause_message: Option<String>,
+    event_bus: EventBus,
+    twitch: Option<TwitchChat>,
+    remote: Option<RemoteServer>,
+    slow_timer: f32,
+    //the power-up pacman is carrying, set off with Space
+    held_item: Option<PowerUp>,
+    //Space was pressed since the last tick
+    use_item_requested: bool,
+    //while above zero a freeze power-up has the ghosts standing still
+    freeze_timer: f32,
+    //ticks left before a ghost can hurt pacman again
+    invulnerable_ticks: u32,
+    //copied from the settings when a game starts, so changing them mid-game waits for the next one
+    difficulty: Difficulty,
+    pellet_stacking: PelletStacking,
+    moving_fruit: bool,
+    //every fruit eaten this game, oldest first, for the HUD's fruit history
+    fruit_eaten: Vec<FruitKind>,
+    //Some after a lost life, counting the dots eaten since, while it decides pen releases
+    global_dots: Option<u32>,
+    //ticks since pacman last ate a dot, while the global counter is on
+    pen_idle_ticks: u32,
+    //ticks since the current level started, for the HUD's timer
+    level_ticks: u32,
+    //where the ghosts are between scattering and chasing
+    ghost_mode: GhostModeTimer,
+    //the level being played, from 1. Every cleared maze moves it on one
+    level: u32,
+    //ghosts eaten on the current power pellet
+    ghost_chain: u32,
+    //ghosts eaten since the last power pellet, whatever the stacking
+    pellet_ghosts: u32,
+    assists: Assists,
+    //the junction pacman was last held at, and the ticks left holding there
+    intersection_cell: Option<(i32, i32)>,
+    intersection_hold: u32,
+    //how many times New Game+ was taken in a row, and the score this game started from
+    prestige: u32,
+    start_score: u32,
+    round: Round,
+    //finished games go into the history database
+    record_history: bool,
+    mods: Option<ModHost>,
+    //embedders' callbacks, told about every tick
+    observers: Vec<Box<dyn Observer>>,
+    //hooks waiting to be handed to the mods at the end of the tick
+    mod_hooks: Vec<Hook>,
+    pacman_speed_factor: f32,
+    ghost_speed_factor: f32,
+    //the speed table, and this level's speeds from it
+    speed_table: SpeedTable,
+    speeds: Speeds,
+    //items and hazards added by mods
+    entities: Vec<Box<dyn EntityPlugin>>,
+    entity_registry: EntityRegistry,
+    //set when a game ends, the next draw saves the game-over memento
+    pending_memento: bool,
+    //particles, score popups and toasts
+    effects: Effects,
+    //Some while announcements are switched on
+    announcer: Option<Announcer>,
+    telemetry: Telemetry,
+    //keep the crash handler's snapshot and input history up to date
+    crash_reporting: bool,
+    crash_dialog: Option<CrashDialog>,
+    update_check: Option<UpdateCheck>,
+    //shown on the title screen once the update check finds a newer release
+    update_banner: Option<String>,
+    //set during a networked versus game
+    netplay: Option<Session>,
+    //the state at the start of each recent tick, to roll back to when a guess was wrong
+    rollback_frames: VecDeque<RollbackFrame>,
+    //set while watching someone else's versus game
+    spectating: Option<Spectator>,
+    //Some in a practice game, with the options it was started with
+    practice: Option<PracticeOptions>,
+    tutorial: Option<Tutorial>,
+    //Some in a split-screen race, with the right-hand player's game in `rival`
+    race: Option<Race>,
+    rival: Option<Box<MainState>>,
+    //the ghosts' introduction, before a new game or from the menu
+    intro: Option<Intro>,
+    //the local player's latest direction, sent to the other player each tick
+    local_direction: Direction,
+    //the walls pre-rendered at the given drawable size
+    wall_canvas: Option<(graphics::Canvas, (f32, f32))>,
+    //pacman and the ghosts are all drawn from this in one batch
+    circle_sprite: Option<graphics::Image>,
+    hud: HudCanvas,
+    //when the next frame may be shown under the FPS cap
+    next_frame: Instant,
+    //when the last close call's slow motion started
+    close_call: Option<Instant>,
+    //F3 in profiling builds shows where the frame time goes
+    profiler_overlay: bool,
+    //where pacman and the ghosts have been this session, and the layer shown over the board
+    heatmap: Heatmap,
+    heatmap_layer: Option<HeatmapLayer>,
+    //in dev mode, the ghosts' every tick of the level, and while paused the frame the scrubber
+    //is on, if it's open
+    ghost_trace: GhostTrace,
+    trace_cursor: Option<usize>,
+    //where pacman and the ghosts were a tick ago, for drawing between ticks, and dev mode's split
+    //view of raw ticks beside the smoothed motion
+    interpolation: Interpolation,
+    compare_motion: bool,
+    //the share of the window the board is drawn at, lowered while frames run over budget
+    render_scale: RenderScale,
+    console: Console,
+    //what the console can run, built in and registered by mods
+    commands: CommandRegistry<MainState>,
+    cheats: Cheats,
+    cheat_menu: Option<CheatMenu>,
+    //the cheat menu is there in debug builds, and in release ones started with `--dev`
+    dev_mode: bool,
+    //false while the window is in the background or minimized
+    focused: bool,
+    //what the window's title was last set to
+    window_title: String,
+}
+
+impl MainState {
+    pub fn new(settings: Settings, map: Map) -> GameResult<MainState> {
+        let rows = map.rows();
+        let rows: Vec<&str> = rows.iter().map(String::as_str).collect();
+        //checked once here, so a broken maze warns once and not on every reset
+        let layout = Layout::new(&rows);
+        maze::check(&layout);
+
+        let ruleset = map.rules.clone();
+
+        //add 'power' pellets in corners, if the rules have them
+        let power_pellets =
+            if ruleset.power_ups.power_pellets { PowerPellets::for_maze(&layout) } else { PowerPellets::none() };
+
+        let spawns = spawn::collect(&rows, &layout, ruleset.ghosts);
+        let ghost_mode = GhostModeTimer::new(&ruleset.scatter_chase);
+        let mut ghosts = spawns.roster();
+        for ghost in &mut ghosts {
+            ghost.mode = ghost_mode.mode();
+        }
+        let (pacman_start_x, pacman_start_y) = spawns.pacman_start();
+
+        let walls = maze_walls(&map, &layout, &ruleset, true);
+        let pacman_walls = maze_walls(&map, &layout, &ruleset, false);
+        let mut dots = DotGrid::new(map.width, map.height);
+        for (col, row) in map.dot_cells() {
+            dots.push(ggez::mint::Point2 {
+                x: col as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+                y: row as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+            });
+        }
+        let maze_dots = dots.len();
+        let map_hash = replay::map_hash(&map);
+        let heatmap = Heatmap::new(map.width, map.height);
+        //if ok, set default values for main state
+        Ok(MainState {
+            pacman_x: pacman_start_x,
+            pacman_y: pacman_start_y,
+            current_direction: Direction::None,
+            requested_direction: Direction::None,
+            held_directions: Vec::new(),
+            walls,
+            pacman_walls,
+            layout,
+            dots,
+            ghosts,
+            spawns,
+            map,
+            map_hash,
+            maze_dots,
+            level_dots_eaten: 0,
+            regrowth: None,
+            ruleset,
+            score: 0,
+            lives: 3,
+            mouth_phase: 0.0,
+            facing: Direction::Left,
+            power_pellets,
+            power_pellet_active: false,
+            power_pellet_timer: 0.0,
+            jobs: Jobs::new(thread::available_parallelism().map_or(1, |p| p.get())),
+            ghost_open: Vec::new(),
+            ghost_steps: Vec::new(),
+            settings,
+            seed: 0,
+            rng: Pcg32::seed_from_u64(0),
+            recorder: None,
+            playback: None,
+            stats: GameStats::default(),
+            stats_path: None,
+            screen: GameScreen::Menu(MainMenu::new()),
+            paused_at: None,
+            photo: None,
+            pause_message: None,
+            event_bus: EventBus::new(),
+            twitch: None,
+            remote: None,
+            slow_timer: 0.0,
+            held_item: None,
+            use_item_requested: false,
+            freeze_timer: 0.0,
+            invulnerable_ticks: 0,
+            difficulty: Difficulty::Normal,
+            pellet_stacking: PelletStacking::Refresh,
+            moving_fruit: false,
+            fruit_eaten: Vec::new(),
+            global_dots: None,
+            pen_idle_ticks: 0,
+            level_ticks: 0,
+            ghost_mode,
+            level: 1,
+            ghost_chain: 0,
+            pellet_ghosts: 0,
+            assists: Assists::default(),
+            intersection_cell: None,
+            intersection_hold: 0,
+            prestige: 0,
+            start_score: 0,
+            round: Round::Playing,
+            record_history: true,
+            mods: None,
+            observers: Vec::new(),
+            mod_hooks: Vec::new(),
+            pacman_speed_factor: 1.0,
+            ghost_speed_factor: 1.0,
+            speed_table: SpeedTable::default(),
+            speeds: SpeedTable::default().for_level(1),
+            entities: Vec::new(),
+            entity_registry: EntityRegistry::new(),
+            pending_memento: false,
+            effects: Effects::new(),
+            announcer: None,
+            next_frame: Instant::now(),
+            close_call: None,
+            profiler_overlay: false,
+            heatmap,
+            heatmap_layer: None,
+            ghost_trace: GhostTrace::default(),
+            trace_cursor: None,
+            interpolation: Interpolation::default(),
+            render_scale: RenderScale::new(),
+            compare_motion: false,
+            console: Console::new(),
+            commands: console_commands(),
+            cheats: Cheats::default(),
+            cheat_menu: None,
+            dev_mode: cfg!(debug_assertions),
+            focused: true,
+            window_title: WINDOW_TITLE.to_string(),
+            telemetry: Telemetry::disabled(),
+            crash_reporting: false,
+            crash_dialog: None,
+            update_check: None,
+            update_banner: None,
+            netplay: None,
+            rollback_frames: VecDeque::new(),
+            spectating: None,
+            practice: None,
+            tutorial: None,
+            race: None,
+            rival: None,
+            intro: None,
+            local_direction: Direction::None,
+            wall_canvas: None,
+            circle_sprite: None,
+            hud: HudCanvas::new(),
+        })
+    }
+    //reset game by enumerating over x and y and resetting particles
+    fn reset_game(&mut self) {
+        //put pacman and a fresh roster of ghosts on their spawn tiles
+        let (pacman_x, pacman_y) = self.spawns.pacman_start();
+        self.pacman_x = pacman_x;
+        self.pacman_y = pacman_y;
+        self.ghosts = self.spawns.roster();
+
+        //reset game state
+        self.score = 0;
+        self.lives = 3;
+        self.current_direction = Direction::None;
+        self.requested_direction = Direction::None;
+        self.mouth_phase = 0.0;
+        self.facing = Direction::Left;
+        self.power_pellet_active = false;
+        self.power_pellet_timer = 0.0;
+        self.slow_timer = 0.0;
+        self.held_item = None;
+        self.use_item_requested = false;
+        self.freeze_timer = 0.0;
+        self.invulnerable_ticks = 0;
+        self.round = Round::Playing;
+        self.difficulty = self.settings.difficulty;
+        self.pellet_stacking = self.settings.pellet_stacking;
+        self.fruit_eaten.clear();
+        self.ghost_chain = 0;
+        self.pellet_ghosts = 0;
+        self.global_dots = None;
+        self.pen_idle_ticks = 0;
+        self.level_ticks = 0;
+        self.level_dots_eaten = 0;
+        self.reset_ghost_mode();
+        self.level = 1;
+        self.set_moving_fruit(self.settings.moving_fruit);
+        self.assists = self.settings.assists;
+        self.intersection_cell = None;
+        self.intersection_hold = 0;
+        self.prestige = 0;
+        self.start_score = 0;
+        //after prestige goes back to 0, so the ghosts lose its speed boost
+        self.apply_speeds(1);
+        self.practice = None;
+        self.tutorial = None;
+        self.cheats = Cheats::default();
+        //a game reset under the title screen, as the demo's are, leaves it up
+        if !matches!(self.screen, GameScreen::Menu(_)) {
+            self.screen = GameScreen::Playing;
+        }
+        self.seed = rand::random();
+        self.rng = Pcg32::seed_from_u64(self.seed);
+        self.recorder = Some(ReplayRecorder::new(self.map_hash.clone()));
+        self.playback = None;
+        self.stats = GameStats::new(self.seed);
+        self.pacman_speed_factor = 1.0;
+        self.ghost_speed_factor = 1.0;
+        self.entities.clear();
+        self.effects.clear();
+        self.mod_hooks.clear();
+        self.mod_hooks.push(Hook::LevelStart(1));
+
+        self.reload_map();
+    }
+
+    //puts back the maze's dots and power pellets, and redraws the walls
+    fn reload_map(&mut self) {
+        self.power_pellets.reset();
+        //a new level may bring new walls
+        self.invalidate_walls();
+
+        //recreate dots
+        self.dots.clear();
+        let dot_cells = self.map.dot_cells();
+        for &(col, row) in &dot_cells {
+            self.dots.push(ggez::mint::Point2 {
+                x: col as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+                y: row as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+            });
+        }
+        //every row and column is open to regrowth again
+        self.regrowth = if self.ruleset.regrow {
+            Some(Regrowth::new(self.map.width, self.map.height, dot_cells))
+        } else {
+            None
+        };
+    }
+
+    //whether fruit spawned from now on wanders
+    fn set_moving_fruit(&mut self, wander: bool) {
+        self.moving_fruit = wander;
+        self.register_fruit();
+    }
+
+    //makes the "fruit" kind the current level's fruit, wandering or not. It replaces whatever was
+    //registered under the name, so it runs again every level and a mod that registers its own
+    //fruit has to do it again from on_level_start
+    fn register_fruit(&mut self) {
+        let (kind, wander) = (FruitKind::for_level(self.level), self.moving_fruit);
+        self.entity_registry.register("fruit", move |x, y| Box::new(Fruit::new(x, y, kind, wander)));
+    }
+
+    //looks up `level` in the speed table, for pacman and every ghost, scaled by the maze's rules
+    fn apply_speeds(&mut self, level: u32) {
+        self.speeds = self.speed_table.for_level(level);
+        self.speeds.pacman = self.speeds.pacman.scale(self.ruleset.pacman_speed);
+        self.speeds.pacman_frightened = self.speeds.pacman_frightened.scale(self.ruleset.pacman_speed);
+        let boost = (1.0 + PRESTIGE_GHOST_BOOST * self.prestige as f32) * self.ruleset.ghost_speed;
+        for ghost in &mut self.ghosts {
+            ghost.speeds = GhostSpeeds {
+                normal: self.speeds.ghost.normal.scale(boost),
+                frightened: self.speeds.ghost.frightened.scale(boost),
+                tunnel: self.speeds.ghost.tunnel.scale(boost),
+            };
+        }
+    }
+
+    //a fresh game that keeps the score, with faster ghosts and a life fewer per prestige,
+    //down to one
+    fn enter_prestige(&mut self, prestige: u32, score: u32) {
+        self.prestige = prestige;
+        self.start_score = score;
+        self.score = score;
+        self.lives = 3 - prestige.min(2) as i32;
+        self.apply_speeds(1);
+    }
+
+    //possibility for movement depends on the cell grid they 'snap' to
+    fn can_move(&self, direction: Direction) -> bool {
+        movement::can_move(&self.pacman_walls, self.pacman_x, self.pacman_y, direction)
+    }
+
+    //the one place pacman and the ghosts meet: eats every vulnerable ghost he touches, and
+    //costs at most one life however many of the others he touches
+    fn check_ghost_collision(&mut self) {
+        profile_scope!("collision");
+        if self.lives <= 0 || self.round != Round::Playing {
+            return;
+        }
+
+        let pacman_center = (self.pacman_x + PACMAN.half(), self.pacman_y + PACMAN.half());
+        let reach = (PACMAN + GHOST).half();
+        let mut caught = false;
+        let bonus_possible = self.ghosts.len() as u32 >= ALL_GHOSTS_COUNT;
+
+        for (index, ghost) in self.ghosts.iter_mut().enumerate() {
+            if !ghost.in_play() {
+                continue;
+            }
+            let ghost_center = (ghost.x + GHOST.half(), ghost.y + GHOST.half());
+            let (dx, dy) = self.walls.delta(pacman_center, ghost_center);
+            if dx.squared() + dy.squared() >= reach.squared() {
+                continue;
+            }
+            if ghost.is_vulnerable() {
+                let (center_x, center_y) = (ghost_center.0.to_f32(), ghost_center.1.to_f32());
+                let points = GHOST_POINTS[(self.ghost_chain as usize).min(GHOST_POINTS.len() - 1)];
+                self.effects.burst(center_x, center_y, Color::BLUE, GHOST_BURST);
+                self.effects.score_popup(center_x, center_y, points, ghost.color);
+                ghost.times_eaten += 1;
+                let mut respawn_seconds = self.difficulty.ghost_respawn_seconds();
+                //under house arrest every time after the first keeps it in the pen half as long again
+                if self.ruleset.house_arrest {
+                    respawn_seconds *= HOUSE_ARREST_FACTOR.powi(ghost.times_eaten as i32 - 1);
+                }
+                ghost.eaten(respawn_seconds);
+                self.ghost_chain += 1;
+                self.pellet_ghosts += 1;
+                self.score += points;
+                self.stats.ghosts_eaten += 1;
+                self.mod_hooks.push(Hook::GhostEaten(index));
+                if bonus_possible && self.pellet_ghosts == ALL_GHOSTS_COUNT {
+                    self.score += ALL_GHOSTS_BONUS;
+                    self.effects.score_popup(center_x, center_y - CELL_SIZE, ALL_GHOSTS_BONUS, Color::YELLOW);
+                    self.effects.toast("Achievement: four ghosts on one pellet!");
+                }
+            } else {
+                caught = true;
+            }
+        }
+
+        if caught && self.invulnerable_ticks == 0 {
+            self.lose_life();
+        }
+    }
+
+    //costs pacman a life, whatever took it, and freezes the round until everyone is back at
+    //the start
+    fn lose_life(&mut self) {
+        if self.invulnerable_ticks > 0 || self.round != Round::Playing || self.cheats.invincible {
+            return;
+        }
+        if !self.practice.is_some_and(|practice| practice.infinite_lives) {
+            self.lives -= 1;
+        }
+        self.stats.deaths += 1;
+        if self.lives <= 0 {
+            self.round = Round::GameOver;
+            //a game that got past the first maze can carry on into New Game+. Not versus games,
+            //replays or practice, which can't carry on into another game. The demo under the
+            //title screen just starts over
+            if matches!(self.screen, GameScreen::Playing) {
+                let campaign = !self.networked() && self.playback.is_none() && self.practice.is_none();
+                let menu = if campaign && self.level > 1 {
+                    GameOverMenu::cleared(self.level - 1)
+                } else {
+                    GameOverMenu::new()
+                };
+                self.screen = GameScreen::GameOver(menu.with_share_code(self.share_code()));
+            }
+            self.lives = 0;
+            return;
+        }
+        self.round = Round::LifeLost { ticks_left: LIFE_LOST_TICKS };
+    }
+
+    //counts down a lost life: the board stays frozen where pacman was caught, then everyone
+    //goes back to the start for READY!, then play resumes with pacman briefly untouchable
+    fn tick_life_lost(&mut self, ticks_left: u32) {
+        let ticks_left = ticks_left.saturating_sub(1);
+        if ticks_left == READY_TICKS {
+            self.reset_pacman_position();
+            for ghost in &mut self.ghosts {
+                ghost.reset_position();
+            }
+            self.current_direction = Direction::None;
+            self.reset_ghost_mode();
+            //the pen's timers give way to the global dot counter for the rest of the level
+            self.global_dots = Some(0);
+            self.pen_idle_ticks = 0;
+            self.release_by_dot_counter();
+        }
+        if ticks_left == 0 {
+            self.round = Round::Playing;
+            self.invulnerable_ticks = INVULNERABLE_TICKS;
+        } else {
+            self.round = Round::LifeLost { ticks_left };
+        }
+    }
+
+    //counts down a cleared maze with everything standing still, then starts the next level
+    fn tick_level_complete(&mut self, ticks_left: u32) {
+        let ticks_left = ticks_left.saturating_sub(1);
+        if ticks_left == 0 {
+            self.start_next_level();
+        } else {
+            self.round = Round::LevelComplete { ticks_left };
+        }
+    }
+
+    //a full maze again and everyone back at the start, at the next level's speeds. Score, lives
+    //and the held power-up carry over, while everything counted per level starts again
+    fn start_next_level(&mut self) {
+        self.level += 1;
+        self.level_ticks = 0;
+        self.level_dots_eaten = 0;
+        self.reset_ghost_mode();
+        self.reload_map();
+        self.reset_pacman_position();
+        for ghost in &mut self.ghosts {
+            ghost.reset_position();
+            ghost.times_eaten = 0;
+        }
+        self.apply_speeds(self.level);
+        self.register_fruit();
+        self.power_pellet_active = false;
+        self.power_pellet_timer = 0.0;
+        self.freeze_timer = 0.0;
+        self.ghost_chain = 0;
+        self.pellet_ghosts = 0;
+        self.global_dots = None;
+        self.pen_idle_ticks = 0;
+        self.invulnerable_ticks = 0;
+        self.intersection_cell = None;
+        self.intersection_hold = 0;
+        self.entities.clear();
+        self.round = Round::Playing;
+        self.mod_hooks.push(Hook::LevelStart(self.level));
+    }
+
+    //lets out every ghost in the pen whose threshold the global dot counter has reached, or the
+    //next one if pacman has gone too long without a dot. The counter switches off once the pen
+    //is empty
+    fn release_by_dot_counter(&mut self) {
+        let counter = match self.global_dots {
+            Some(counter) => counter,
+            None => return,
+        };
+        let mut idle_release = self.pen_idle_ticks >= PEN_IDLE_TICKS;
+        for (index, ghost) in self.ghosts.iter_mut().enumerate() {
+            if ghost.release_ticks == 0 {
+                continue;
+            }
+            //ghosts past the arcade's four wait for the last threshold
+            let threshold = match index {
+                0 => 0,
+                _ => GLOBAL_DOT_THRESHOLDS[(index - 1).min(GLOBAL_DOT_THRESHOLDS.len() - 1)],
+            };
+            if counter >= threshold || idle_release {
+                ghost.release_ticks = 0;
+                if counter < threshold {
+                    idle_release = false;
+                    self.pen_idle_ticks = 0;
+                }
+            }
+        }
+        if self.ghosts.iter().all(|ghost| ghost.release_ticks == 0) {
+            self.global_dots = None;
+        }
+    }
+
+    //sets off the power-up pacman is carrying, if any
+    fn use_held_item(&mut self) {
+        let item = match self.held_item.take() {
+            Some(item) => item,
+            None => return,
+        };
+        let pacman_center = (self.pacman_x + PACMAN.half(), self.pacman_y + PACMAN.half());
+        match item {
+            PowerUp::Bomb => {
+                let reach = CELL.scale(BOMB_RADIUS);
+                for ghost in self.ghosts.iter_mut().filter(|ghost| ghost.in_play()) {
+                    let ghost_center = (ghost.x + GHOST.half(), ghost.y + GHOST.half());
+                    let (dx, dy) = self.walls.delta(pacman_center, ghost_center);
+                    if dx.squared() + dy.squared() < reach.squared() {
+                        ghost.eaten(self.difficulty.ghost_respawn_seconds());
+                    }
+                }
+            }
+            PowerUp::Freeze => self.freeze_timer = FREEZE_SECONDS,
+        }
+        self.effects.burst(pacman_center.0.to_f32(), pacman_center.1.to_f32(), item.color(), GHOST_BURST);
+    }
+
+    //update every registered entity and apply whatever pacman ran into
+    fn update_entities(&mut self, dt: f32) {
+        let pacman = graphics::Rect::new(self.pacman_x.to_f32(), self.pacman_y.to_f32(), PACMAN_SIZE, PACMAN_SIZE);
+        let mut world = World {
+            dt,
+            walls: &self.pacman_walls,
+            rng: &mut self.rng,
+        };
+        let mut effects = Vec::new();
+        for entity in &mut self.entities {
+            entity.update(&mut world);
+            let bounds = entity.bounds();
+            if bounds.overlaps(&pacman) {
+                if let Some(effect) = entity.collide() {
+                    effects.push((effect, bounds.center()));
+                }
+            }
+        }
+        self.entities.retain(|entity| entity.alive());
+
+        for (effect, at) in effects {
+            if self.game_over() {
+                break;
+            }
+            match effect {
+                EntityEffect::AddScore(points) => self.score += points,
+                EntityEffect::LoseLife => self.lose_life(),
+                EntityEffect::SlowPacman(duration) => self.slow_timer = self.slow_timer.max(duration),
+                EntityEffect::Store(item) => self.held_item = Some(item),
+                EntityEffect::EatFruit(kind) => {
+                    self.score += kind.points();
+                    self.fruit_eaten.push(kind);
+                    self.effects.score_popup(at.x, at.y, kind.points(), kind.color());
+                }
+            }
+        }
+    }
+
+    //function to make pacman an entity of the current cell it resides in. Allows for easier movement without getting stuck on edges
+    fn snap_to_grid(&mut self) {
+        //round to nearest grid position
+        let (x, y) = movement::snap_to_grid(self.pacman_x, self.pacman_y);
+        self.pacman_x = x;
+        self.pacman_y = y;
+    }
+
+    fn is_at_grid_center(&self) -> bool {
+        movement::is_at_grid_center(self.pacman_x, self.pacman_y)
+    }
+
+    //resetting position and directions
+    fn reset_pacman_position(&mut self) {
+        //back to his spawn tile
+        let (x, y) = self.spawns.pacman_start();
+        self.pacman_x = x;
+        self.pacman_y = y;
+        self.current_direction = Direction::None;
+        self.requested_direction = Direction::None;
+        self.mouth_phase = 0.0;
+        self.facing = Direction::Left;
+    }
+
+    //whether the intersection assist holds this tick: for a moment after pacman reaches a
+    //junction he could turn at, until a turn is picked
+    fn hold_at_intersection(&mut self) -> bool {
+        if !self.assists.intersection_pause || self.current_direction == Direction::None || !self.is_at_grid_center() {
+            return false;
+        }
+        let cell = (
+            (self.pacman_x + PACMAN.half()).div_floor(CELL),
+            (self.pacman_y + PACMAN.half()).div_floor(CELL),
+        );
+        if self.intersection_cell != Some(cell) {
+            self.intersection_cell = Some(cell);
+            let turns = match self.current_direction {
+                Direction::Left | Direction::Right => [Direction::Up, Direction::Down],
+                _ => [Direction::Left, Direction::Right],
+            };
+            if turns.iter().any(|&turn| self.can_move(turn)) {
+                self.intersection_hold = INTERSECTION_PAUSE_TICKS;
+            }
+        }
+        if self.intersection_hold == 0 || self.requested_direction != self.current_direction {
+            self.intersection_hold = 0;
+            return false;
+        }
+        self.intersection_hold -= 1;
+        true
+    }
+
+    //advance the simulation by one fixed step
+    fn tick(&mut self) {
+        profile_scope!("tick");
+        let dt = TICK_DT;
+        self.effects.tick(dt);
+        if !self.game_over() {
+            self.level_ticks += 1;
+        }
+
+        //a replay also says when the held power-up went off
+        let use_item = match &self.playback {
+            Some(player) => player.uses_item(),
+            None => std::mem::take(&mut self.use_item_requested),
+        };
+        //a replay supplies the input, otherwise a held key keeps reasserting itself at
+        //intersections so long corridors don't need re-pressing
+        if let Some(player) = &mut self.playback {
+            if let Some(direction) = player.next_input() {
+                self.requested_direction = direction;
+            }
+        } else if !self.networked() && self.is_at_grid_center() {
+            if let Some(&held) = self.held_directions.last() {
+                self.requested_direction = held;
+            }
+        }
+        if let Some(recorder) = &mut self.recorder {
+            if use_item {
+                recorder.record_item_use();
+            }
+            recorder.record(self.requested_direction);
+        }
+
+        match self.round {
+            Round::LifeLost { ticks_left } => {
+                self.tick_life_lost(ticks_left);
+                return;
+            }
+            Round::LevelComplete { ticks_left } => {
+                self.tick_level_complete(ticks_left);
+                return;
+            }
+            Round::Playing | Round::GameOver => {}
+        }
+        if use_item {
+            self.use_held_item();
+        }
+
+        //the intersection assist freezes everything while pacman waits at a junction
+        if self.hold_at_intersection() {
+            return;
+        }
+
+        //effects injected from outside the game, e.g. chat votes. Replays can't reproduce
+        //them, so they stop the recording and are ignored while watching one
+        for effect in self.event_bus.drain() {
+            if self.playback.is_some() || self.networked() {
+                continue;
+            }
+            //the maze can turn power-ups off
+            let enabled = match effect {
+                Effect::Scatter => self.ruleset.power_ups.scatter,
+                Effect::SlowPacman => self.ruleset.power_ups.slow_pacman,
+                Effect::SpawnFruit => self.ruleset.power_ups.fruit,
+            };
+            if !enabled {
+                continue;
+            }
+            if self.recorder.take().is_some() {
+                eprintln!("An external effect was applied, this game won't be saved as a replay");
+            }
+            match effect {
+                Effect::Scatter => {
+                    for ghost in &mut self.ghosts {
+                        ghost.confuse(INJECTED_EFFECT_DURATION);
+                    }
+                }
+                Effect::SlowPacman => self.slow_timer = INJECTED_EFFECT_DURATION,
+                //where the maze's own fruit come out
+                Effect::SpawnFruit => {
+                    let (col, row) = self.spawns.pacman_tile();
+                    if let Some(fruit) = self.entity_registry.spawn("fruit", col, row) {
+                        self.entities.push(fruit);
+                    }
+                }
+            }
+        }
+
+        //update power pellet timer, unless practice froze it
+        let pellets_frozen = self.practice.is_some_and(|practice| practice.freeze_pellets);
+        if self.power_pellet_active && !pellets_frozen {
+            self.power_pellet_timer -= dt;
+            if self.power_pellet_timer <= 0.0 {
+                self.power_pellet_active = false;
+                self.ghost_chain = 0;
+            }
+        }
+
+        self.freeze_timer = (self.freeze_timer - dt).max(0.0);
+        if self.slow_timer > 0.0 {
+            self.slow_timer -= dt;
+        }
+        self.invulnerable_ticks = self.invulnerable_ticks.saturating_sub(1);
+
+        //update ghost timers. Pen releases wait for dots instead while the global counter is on
+        let counting_dots = self.global_dots.is_some();
+        for ghost in &mut self.ghosts {
+            if ghost.confused_timer > 0.0 {
+                ghost.confused_timer -= dt;
+            }
+            if ghost.respawn_timer > 0.0 && !ghost.is_eyes() {
+                ghost.respawn_timer -= dt;
+            }
+            if ghost.frightened_timer > 0.0 && !pellets_frozen {
+                ghost.frightened_timer = (ghost.frightened_timer - dt).max(0.0);
+            }
+            if !counting_dots {
+                ghost.release_ticks = ghost.release_ticks.saturating_sub(1);
+            }
+        }
+
+        //check power pellet collection
+        let pacman_center = (self.pacman_x + PACMAN.half(), self.pacman_y + PACMAN.half());
+        let pellet_reach = PACMAN.half() + Fixed::from_f32(POWER_PELLET_SIZE / 2.0);
+        let pellet_seconds = self.ruleset.pellet_seconds;
+        self.power_pellets.eat_within(pacman_center, pellet_reach, |pellet| {
+            let extend = self.pellet_stacking == PelletStacking::Extend && self.power_pellet_active;
+            match self.pellet_stacking {
+                PelletStacking::Extend if self.power_pellet_active => {
+                    self.power_pellet_timer += pellet_seconds;
+                }
+                _ => {
+                    self.power_pellet_timer = pellet_seconds;
+                    self.ghost_chain = 0;
+                }
+            }
+            self.power_pellet_active = true;
+            self.pellet_ghosts = 0;
+            self.stats.power_pellets_eaten += 1;
+            self.effects.burst(pellet.x, pellet.y, Color::WHITE, PELLET_BURST);
+            self.mod_hooks.push(Hook::PelletEaten {
+                col: (pellet.x / CELL_SIZE) as usize,
+                row: (pellet.y / CELL_SIZE) as usize,
+                power: true,
+            });
+            //eyes on their way home sit this pellet out, and come back unfrightened
+            for ghost in self.ghosts.iter_mut().filter(|ghost| ghost.in_play()) {
+                ghost.frighten(pellet_seconds, extend);
+            }
+        });
+
+        if self.game_over() {
+            return;
+        }
+
+        //if at grid center, allow direction change if the new direction is valid
+        if self.is_at_grid_center()
+            && self.requested_direction != self.current_direction
+            && self.can_move(self.requested_direction)
+        {
+            //speeds that aren't whole pixels stop a little off center, so line up before turning
+            self.snap_to_grid();
+            self.current_direction = self.requested_direction;
+            if self.current_direction != Direction::None {
+                self.facing = self.current_direction;
+            }
+        }
+
+        //move in current direction
+        let slow = if self.slow_timer > 0.0 { SLOW_SPEED_FACTOR } else { 1.0 };
+        let step = if self.power_pellet_active { self.speeds.pacman_frightened } else { self.speeds.pacman };
+        let (dx, dy) = self.current_direction.offset(step.scale(slow * self.pacman_speed_factor));
+
+        //update movement
+        let new_x = self.pacman_x + dx;
+        let new_y = self.pacman_y + dy;
+
+        if !collision::box_hits_wall(&self.pacman_walls, new_x, new_y, PACMAN, PACMAN) {
+            (self.pacman_x, self.pacman_y) = self.pacman_walls.wrap_box(new_x, new_y, PACMAN);
+            //the mouth chomps with the distance covered, so it stays still when pacman does
+            let moved = (dx.abs() + dy.abs()).to_f32();
+            self.mouth_phase = (self.mouth_phase + moved / CHOMP_DISTANCE).fract();
+        } else {
+            //if we hit a wall, snap to grid
+            self.snap_to_grid();
+            self.current_direction = Direction::None;
+        }
+
+        self.tick_ghost_mode();
+        self.update_keenness();
+
+        //update ghosts with Pac-Man's position. The workers check the walls around each ghost,
+        //then the ghosts roll their dice here in turn since that draws from the rng, then the
+        //workers look up the paths to their targets and the ghosts move. Everything stays put
+        //with the AI off or a freeze going, and ghosts still in the pen or waiting to respawn do
+        //too. Eyes go home
+        if !self.cheats.ghosts_frozen && self.freeze_timer <= 0.0 {
+            let walls = &self.walls;
+            let speed_factor = self.ghost_speed_factor;
+            self.jobs.map_into(&self.ghosts, GHOSTS_PER_JOB, &mut self.ghost_open, |ghost| {
+                ghost.open_directions(walls, speed_factor)
+            });
+            let pacman = (self.pacman_x, self.pacman_y);
+            for (ghost, open) in self.ghosts.iter_mut().zip(&self.ghost_open) {
+                if ghost.moves() && !ghost.is_eyes() {
+                    ghost.roll(open, pacman, &mut self.rng);
+                }
+            }
+            self.jobs.zip_map_into(&self.ghosts, &self.ghost_open, GHOSTS_PER_JOB, &mut self.ghost_steps, |ghost, open| {
+                if ghost.moves() {
+                    ghost.path_step(open, walls)
+                } else {
+                    None
+                }
+            });
+            for (ghost, &step) in self.ghosts.iter_mut().zip(&self.ghost_steps) {
+                if ghost.is_eyes() {
+                    ghost.return_home(step, walls);
+                } else if ghost.moves() {
+                    ghost.follow(step, walls, speed_factor);
+                }
+            }
+        }
+
+        //update mod-spawned items and hazards
+        self.update_entities(dt);
+
+        //check collisions. A lost life stops the tick here, but the last one doesn't, so the tick
+        //that ends the game still plays out
+        self.check_ghost_collision();
+        if !matches!(self.round, Round::Playing | Round::GameOver) {
+            return;
+        }
+
+        //under the regrowth rule eaten dots come back, except on locked rows and columns
+        if let Some(regrowth) = &mut self.regrowth {
+            let dots = &mut self.dots;
+            regrowth.tick(|col, row| {
+                dots.push(ggez::mint::Point2 {
+                    x: col as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+                    y: row as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+                });
+            });
+        }
+
+        //collect dots
+        let pacman_center = (self.pacman_x + PACMAN.half(), self.pacman_y + PACMAN.half());
+        let reach = PACMAN.half() + Fixed::from_f32(DOT_SIZE / 2.0);
+        let dots_before = self.dots.len();
+        let mut locked = false;
+        self.dots.eat_within(pacman_center, reach, |dot| {
+            let (col, row) = ((dot.x / CELL_SIZE) as usize, (dot.y / CELL_SIZE) as usize);
+            self.score += 10;
+            self.stats.dots_eaten += 1;
+            self.mod_hooks.push(Hook::PelletEaten { col, row, power: false });
+            if let Some(regrowth) = &mut self.regrowth {
+                locked |= regrowth.eaten(col, row);
+            }
+        });
+        if locked {
+            self.effects.toast("Line cleared: its dots won't grow back");
+        }
+
+        //the maze's fruit come out on pacman's start cell as the level's dots go
+        let eaten_before = self.level_dots_eaten;
+        self.level_dots_eaten += (dots_before - self.dots.len()) as u32;
+        let eaten_now = self.level_dots_eaten;
+        if eaten_now > eaten_before {
+            let (col, row) = self.spawns.pacman_tile();
+            for _ in self.ruleset.fruit_dots.iter().filter(|&&at| eaten_before < at && at <= eaten_now) {
+                if let Some(fruit) = self.entity_registry.spawn("fruit", col, row) {
+                    self.entities.push(fruit);
+                }
+            }
+        }
+
+        if let Some(counter) = &mut self.global_dots {
+            let eaten = (dots_before - self.dots.len()) as u32;
+            *counter += eaten;
+            self.pen_idle_ticks = if eaten > 0 { 0 } else { self.pen_idle_ticks + 1 };
+            self.release_by_dot_counter();
+        }
+
+        //the last dot clears the level: the maze flashes, then fills up again for the next one.
+        //Not when the same tick took the last life
+        if dots_before > 0 && self.dots.is_empty() && !self.game_over() {
+            self.round = Round::LevelComplete { ticks_left: LEVEL_COMPLETE_TICKS };
+        }
+    }
+
+    //run one tick plus the bookkeeping around it
+    fn step(&mut self) {
+        let lives_before = self.lives;
+        let dots_before = self.dots.len();
+        let score_before = self.score;
+        let power_before = self.power_pellet_active;
+        self.tick();
+        if self.announcer.is_some() {
+            if self.score / SCORE_MILESTONE > score_before / SCORE_MILESTONE {
+                self.announce(&format!("{} points", self.score / SCORE_MILESTONE * SCORE_MILESTONE));
+            }
+            if self.power_pellet_active && !power_before {
+                self.announce("Power pellet! Ghosts are frightened");
+            }
+            if dots_before > 0 && self.dots.is_empty() {
+                self.announce(&format!("Level {} cleared", self.level));
+            }
+            if self.lives < lives_before {
+                self.announce(&match self.lives {
+                    0 => "Game over".to_string(),
+                    1 => "Life lost, 1 left".to_string(),
+                    lives => format!("Life lost, {} left", lives),
+                });
+            }
+        }
+        if self.lives < lives_before {
+            self.telemetry.log(Event::Death {
+                tick: self.stats.ticks,
+                lives_left: self.lives,
+                score: self.score,
+            });
+        }
+        if dots_before > 0 && self.dots.is_empty() {
+            self.telemetry.log(Event::LevelComplete {
+                level: self.level,
+                tick: self.stats.ticks,
+                score: self.score,
+            });
+        }
+        if self.crash_reporting {
+            crash::record_input(self.stats.ticks, self.requested_direction);
+            //a snapshot a second is plenty to resume from
+            if self.stats.ticks.is_multiple_of(TICKS_PER_SECOND) {
+                crash::record_state(self.seed, self.stats.ticks, self.snapshot());
+            }
+        }
+        if !self.game_over() {
+            self.mod_hooks.push(Hook::Tick(self.stats.ticks));
+        }
+        if let Some(tutorial) = &mut self.tutorial {
+            tutorial.observe(&self.mod_hooks, self.current_direction != Direction::None, self.power_pellet_active);
+            if tutorial.completed() && !self.settings.tutorial_done {
+                self.settings.tutorial_done = true;
+                self.settings.save();
+            }
+            if tutorial.over() {
+                self.tutorial = None;
+            }
+        }
+        if !self.observers.is_empty() {
+            self.notify_observers(lives_before, dots_before);
+        }
+        //slow motion is only for show, but a versus game can't slow down for one side
+        if self.settings.close_call_slowmo && !self.networked() && self.lives == lives_before {
+            self.check_close_call();
+        }
+        self.run_mod_hooks();
+        //versus games roll ticks back and play them again, which would count them twice
+        if !self.networked() {
+            self.record_heatmap();
+            if self.dev_mode {
+                self.record_ghost_trace();
+            }
+        }
+        self.stats.record_tick(self.score, self.lives, self.pacman_x.to_f32(), self.pacman_y.to_f32());
+        if self.game_over() {
+            self.finish_game();
+        }
+    }
+
+    //starts the slow motion when a ghost that could have caught pacman is nearly on him, unless
+    //the last one was too recent
+    fn check_close_call(&mut self) {
+        if self.round != Round::Playing || self.invulnerable_ticks > 0 {
+            return;
+        }
+        if self.close_call.is_some_and(|started| started.elapsed() < CLOSE_CALL_COOLDOWN) {
+            return;
+        }
+        let pacman_center = (self.pacman_x + PACMAN.half(), self.pacman_y + PACMAN.half());
+        let near = self.ghosts.iter().filter(|ghost| ghost.in_play() && !ghost.is_vulnerable()).any(|ghost| {
+            let (dx, dy) = self.walls.delta(pacman_center, (ghost.x + GHOST.half(), ghost.y + GHOST.half()));
+            dx.squared() + dy.squared() < CELL.squared()
+        });
+        if near {
+            self.close_call = Some(Instant::now());
+        }
+    }
+
+    //how far through its slow motion a close call is, from 0 to 1, while it lasts
+    fn slow_motion(&self) -> Option<f32> {
+        let elapsed = self.close_call?.elapsed();
+        if elapsed < CLOSE_CALL_DURATION {
+            Some(elapsed.as_secs_f32() / CLOSE_CALL_DURATION.as_secs_f32())
+        } else {
+            None
+        }
+    }
+
+    //ticks a second right now: the speed assist, slowed further during a close call
+    fn tick_rate(&self) -> u32 {
+        let mut percent = self.assists.game_speed.clamp(25, 100);
+        if self.slow_motion().is_some() {
+            percent = percent * CLOSE_CALL_SPEED_PERCENT / 100;
+        }
+        (TICKS_PER_SECOND * percent / 100).max(1)
+    }
+
+    //from maze coordinates to the window's, zooming in on pacman and back out over a close call
+    fn board_view(&self) -> ggez::mint::ColumnMatrix4<f32> {
+        let progress = match self.slow_motion() {
+            Some(progress) => progress,
+            None => return board_transform(),
+        };
+        let zoom = 1.0 + CLOSE_CALL_ZOOM * (std::f32::consts::PI * progress).sin();
+        let (x, y) = ((self.pacman_x + PACMAN.half()).to_f32(), (self.pacman_y + PACMAN.half()).to_f32());
+        DrawParam::default()
+            .dest(ggez::mint::Point2 { x, y: y + HUD_HEIGHT })
+            .scale([zoom, zoom])
+            .offset(ggez::mint::Point2 { x, y })
+            .trans
+            .to_bare_matrix()
+    }
+
+    //back to the start of the scatter and chase schedule, with no turning round
+    fn reset_ghost_mode(&mut self) {
+        self.ghost_mode = GhostModeTimer::new(&self.ruleset.scatter_chase);
+        for ghost in &mut self.ghosts {
+            ghost.mode = self.ghost_mode.mode();
+        }
+    }
+
+    //moves the schedule on, except while a power pellet has the ghosts frightened
+    fn tick_ghost_mode(&mut self) {
+        if self.power_pellet_active || !self.ghost_mode.tick() {
+            return;
+        }
+        let mode = self.ghost_mode.mode();
+        for ghost in &mut self.ghosts {
+            ghost.switch_mode(mode);
+        }
+    }
+
+    //sets every ghost's keenness from the level's aggression row and how much of the maze is
+    //eaten
+    fn update_keenness(&mut self) {
+        let row = match self.ruleset.aggression_for(self.level) {
+            Some(row) => row,
+            None => return,
+        };
+        let eaten = self.maze_dots.saturating_sub(self.dots.len()) as f32 / self.maze_dots.max(1) as f32;
+        let keenness = Keenness { wander: row.wander * eaten, retarget: row.retarget * eaten };
+        for ghost in &mut self.ghosts {
+            ghost.keenness = keenness;
+        }
+    }
+
+    //counts this tick on the cells under pacman and every ghost in play
+    fn record_heatmap(&mut self) {
+        let cell = |x: Fixed, y: Fixed, size: Fixed| {
+            let (x, y) = ((x + size.half()).to_f32(), (y + size.half()).to_f32());
+            ((x / CELL_SIZE).max(0.0) as usize, (y / CELL_SIZE).max(0.0) as usize)
+        };
+        let ghosts: Vec<Option<(usize, usize)>> = self
+            .ghosts
+            .iter()
+            .map(|ghost| if ghost.in_play() { Some(cell(ghost.x, ghost.y, GHOST)) } else { None })
+            .collect();
+        self.heatmap.record(cell(self.pacman_x, self.pacman_y, PACMAN), &ghosts);
+    }
+
+    //adds this tick to the ghost trace. A tick going by means the scrubber isn't open any more
+    fn record_ghost_trace(&mut self) {
+        self.trace_cursor = None;
+        let ghosts = self.ghosts.iter().map(|ghost| TracedGhost {
+            x: ghost.x,
+            y: ghost.y,
+            direction: ghost.direction,
+            target: (ghost.target_x, ghost.target_y),
+            activity: ghost.activity(),
+        });
+        self.ghost_trace.record(self.level, self.level_ticks, ghosts);
+    }
+
+    //pacman's layer in his yellow, a ghost's in its own colour
+    fn heatmap_color(&self, layer: HeatmapLayer) -> Color {
+        match layer {
+            HeatmapLayer::Pacman => Color::YELLOW,
+            HeatmapLayer::Ghosts => Color::RED,
+            HeatmapLayer::Ghost(ghost) => self.ghosts.get(ghost).map_or(Color::WHITE, |ghost| ghost.color),
+        }
+    }
+
+    //captions `text` and speaks it when the build can
+    fn announce(&mut self, text: &str) {
+        if let Some(announcer) = &mut self.announcer {
+            announcer.say(text);
+            self.effects.toast(text);
+        }
+    }
+
+    //tells the observers what this tick's hooks and bookkeeping saw, then how things stand
+    fn notify_observers(&mut self, lives_before: i32, dots_before: usize) {
+        let mut events: Vec<GameEvent> = self
+            .mod_hooks
+            .iter()
+            .filter_map(|hook| match *hook {
+                Hook::LevelStart(level) => Some(GameEvent::LevelStart { level }),
+                Hook::PelletEaten { col, row, power } => Some(GameEvent::PelletEaten { col, row, power }),
+                Hook::GhostEaten(ghost) => Some(GameEvent::GhostEaten { ghost }),
+                Hook::Tick(_) => None,
+            })
+            .collect();
+        if self.lives < lives_before {
+            events.push(GameEvent::LifeLost { lives_left: self.lives });
+        }
+        if dots_before > 0 && self.dots.is_empty() {
+            events.push(GameEvent::LevelComplete { level: self.level });
+        }
+        if self.game_over() {
+            events.push(GameEvent::GameOver { score: self.score });
+        }
+
+        let view = GameView {
+            tick: self.stats.ticks,
+            seed: self.seed,
+            score: self.score,
+            lives: self.lives,
+            pacman: (self.pacman_x.to_f32() / CELL_SIZE, self.pacman_y.to_f32() / CELL_SIZE),
+            direction: self.current_direction,
+            ghosts: self
+                .ghosts
+                .iter()
+                .map(|ghost| GhostView {
+                    x: ghost.x.to_f32() / CELL_SIZE,
+                    y: ghost.y.to_f32() / CELL_SIZE,
+                    vulnerable: ghost.is_vulnerable(),
+                    in_play: ghost.in_play(),
+                })
+                .collect(),
+            dots_left: self.dots.len(),
+            power_pellet_timer: if self.power_pellet_active { self.power_pellet_timer } else { 0.0 },
+            game_over: self.game_over(),
+        };
+        for observer in &mut self.observers {
+            for event in &events {
+                observer.on_event(event);
+            }
+            observer.on_tick(&view);
+        }
+    }
+
+    //hand the queued hooks to the mods and apply the actions they asked for
+    fn run_mod_hooks(&mut self) {
+        let hooks: Vec<Hook> = self.mod_hooks.drain(..).collect();
+        let mut mods = match self.mods.take() {
+            Some(mods) => mods,
+            None => return,
+        };
+        for hook in hooks {
+            for action in mods.call(hook, self.mod_view()) {
+                self.apply_mod_action(action);
+            }
+        }
+        self.mods = Some(mods);
+    }
+
+    fn mod_view(&self) -> ModView {
+        ModView {
+            tick: self.stats.ticks,
+            score: self.score,
+            lives: self.lives,
+            pacman_col: (self.pacman_x + PACMAN.half()).div_floor(CELL).max(0) as usize,
+            pacman_row: (self.pacman_y + PACMAN.half()).div_floor(CELL).max(0) as usize,
+        }
+    }
+
+    //a console command one of the mods registered
+    fn run_mod_command(&mut self, name: &str, args: &[&str]) -> Result<String, String> {
+        let mut mods = self.mods.take().ok_or_else(|| "Mods are switched off".to_string())?;
+        let (reply, actions) = mods.run_command(name, args, self.mod_view());
+        for action in actions {
+            self.apply_mod_action(action);
+        }
+        self.mods = Some(mods);
+        Ok(reply.unwrap_or_default())
+    }
+
+    //runs a line typed into the console and prints what came of it
+    fn run_console_line(&mut self, line: &str) {
+        match self.run_command(line) {
+            Ok(output) => self.console.print(output, false),
+            Err(e) => self.console.print(e, true),
+        }
+    }
+
+    //one command line, from the console or the cheat menu
+    fn run_command(&mut self, line: &str) -> Result<String, String> {
+        if self.networked() || self.playback.is_some() {
+            return Err("Commands only work in your own games".to_string());
+        }
+        //anything a command changes is missing from the inputs, so the replay would go wrong,
+        //and the score doesn't belong in the history. A new seed starts a new game, and a new
+        //recording with it
+        let name = line.split_whitespace().next().unwrap_or("").to_lowercase();
+        if name != "help" && name != "seed" {
+            self.cheats.used = true;
+            if self.recorder.take().is_some() {
+                self.console.print("This game won't be saved as a replay".to_string(), false);
+            }
+        }
+        //the handlers need the whole state, registry included, so it's lent out while they run
+        let commands = std::mem::take(&mut self.commands);
+        let result = commands.run(self, line);
+        self.commands = commands;
+        result
+    }
+
+    fn apply_mod_action(&mut self, action: ModAction) {
+        match action {
+            ModAction::AddScore(points) => {
+                self.score = (self.score as i64 + points).clamp(0, u32::MAX as i64) as u32;
+            }
+            ModAction::SpawnDot(col, row) => {
+                if let Some(center) = self.free_cell_center(col, row) {
+                    self.dots.push(center);
+                }
+            }
+            ModAction::SpawnPowerPellet(col, row) => {
+                if let Some(center) = self.free_cell_center(col, row) {
+                    self.power_pellets.add(center);
+                }
+            }
+            ModAction::PacmanSpeed(factor) => self.pacman_speed_factor = factor,
+            ModAction::GhostSpeed(factor) => self.ghost_speed_factor = factor,
+            ModAction::RegisterItem { name, color, points } => {
+                let color = Color::from_rgb(color.0, color.1, color.2);
+                self.entity_registry.register(&name, move |x, y| Box::new(Pickup::new(x, y, color, points)));
+            }
+            ModAction::RegisterHazard { name, color, effect } => {
+                let color = Color::from_rgb(color.0, color.1, color.2);
+                self.entity_registry.register(&name, move |x, y| Box::new(Hazard::new(x, y, color, effect)));
+            }
+            ModAction::SpawnEntity(name, col, row) => {
+                if !self.is_open_cell(col, row) {
+                    return;
+                }
+                match self.entity_registry.spawn(&name, col, row) {
+                    Some(entity) => self.entities.push(entity),
+                    None => eprintln!("A mod tried to spawn unknown entity '{}'", name),
+                }
+            }
+            ModAction::RegisterCommand { name, help } => {
+                let command = name.clone();
+                self.commands
+                    .register(&name, &help, move |state: &mut MainState, args| state.run_mod_command(&command, args));
+            }
+        }
+    }
+
+    //whether a cell is inside the maze and neither a wall nor void
+    fn is_open_cell(&self, col: usize, row: usize) -> bool {
+        self.layout.is_open(col as i64, row as i64)
+    }
+
+    //any cell free_cell_center accepts. Picked outside the game's rng, which the console's
+    //spawns mustn't disturb
+    fn random_free_cell(&self) -> Option<(usize, usize)> {
+        let (width, height) = (self.map.width, self.map.height);
+        let cells: Vec<(usize, usize)> = (0..height)
+            .flat_map(|row| (0..width).map(move |col| (col, row)))
+            .filter(|&(col, row)| self.free_cell_center(col, row).is_some())
+            .collect();
+        if cells.is_empty() {
+            None
+        } else {
+            Some(cells[rand::random::<usize>() % cells.len()])
+        }
+    }
+
+    //center of a cell that is inside the maze, not a wall and not already holding an item
+    fn free_cell_center(&self, col: usize, row: usize) -> Option<ggez::mint::Point2<f32>> {
+        if !self.is_open_cell(col, row) {
+            return None;
+        }
+        let center = ggez::mint::Point2 {
+            x: col as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+            y: row as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+        };
+        let occupied = self.dots.iter().chain(&self.power_pellets).any(|item| item.x == center.x && item.y == center.y);
+        if occupied {
+            None
+        } else {
+            Some(center)
+        }
+    }
+
+    //save the replay, add the game to the history and export stats for a game that just ended
+    fn finish_game(&mut self) {
+        crash::clear_state();
+        //a rematch would need a new seed from the host, so the match ends here
+        self.netplay = None;
+        self.telemetry.log(Event::GameOver {
+            tick: self.stats.ticks,
+            score: self.score,
+        });
+        if let Some(recorder) = self.recorder.take() {
+            let options = ReplayOptions {
+                difficulty: self.difficulty,
+                pellet_stacking: self.pellet_stacking,
+                prestige: self.prestige,
+                start_score: self.start_score,
+                intersection_pause: self.assists.intersection_pause,
+                moving_fruit: self.moving_fruit,
+            };
+            if let Err(e) = recorder.finish(self.seed, options).save() {
+                eprintln!("Could not save replay: {}", e);
+            }
+        }
+
+        //replays were already recorded when they were played
+        if self.playback.is_none() && self.practice.is_none() && !self.cheats.used && self.record_history {
+            let record = GameRecord {
+                date: chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+                map: self.map.name.clone(),
+                seed: self.seed,
+                score: self.score,
+                level: self.level,
+                prestige: self.prestige,
+                assisted: self.assists.any(),
+                duration_secs: self.stats.ticks as f32 / TICKS_PER_SECOND as f32,
+            };
+            if let Err(e) = ScoreDb::open().and_then(|db| db.insert(&record)) {
+                eprintln!("Could not record game in history: {}", e);
+            }
+            self.pending_memento = true;
+        }
+
+        //an explicit --export-stats path wins over the settings option
+        let target = match (&self.stats_path, self.settings.stats_export) {
+            (Some(path), _) => Some((path.clone(), StatsFormat::from_path(path))),
+            (None, Some(format)) => Some((stats::default_export_path(format), format)),
+            (None, None) => None,
+        };
+        if let Some((path, format)) = target {
+            match self.stats.export(&path, format) {
+                Ok(()) => println!("Stats written to {}", path.display()),
+                Err(e) => eprintln!("Could not export stats to {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    //opens `screen` over whatever's on screen, to go back to when it's closed
+    fn open_settings(&mut self, screen: SettingsScreen) {
+        let beneath = std::mem::replace(&mut self.screen, GameScreen::Playing);
+        self.screen = GameScreen::Settings(screen, Box::new(beneath));
+    }
+
+    //freezes the game under the pause menu. Everything the game times in ticks stops with them,
+    //and the close call's slow motion, which runs on the clock, is held from here to resume. Only
+    //a game in play pauses, so one that's over keeps its menu
+    fn pause(&mut self) {
+        if !matches!(self.screen, GameScreen::Playing) {
+            return;
+        }
+        self.screen = GameScreen::Paused(PauseMenu::new(!self.networked()));
+        self.paused_at = Some(Instant::now());
+        self.pause_message = None;
+        self.held_directions.clear();
+    }
+
+    fn resume(&mut self) {
+        self.screen = GameScreen::Playing;
+        if let (Some(paused_at), Some(started)) = (self.paused_at.take(), &mut self.close_call) {
+            *started += paused_at.elapsed();
+        }
+    }
+
+    fn pause_action(&mut self, action: PauseAction) {
+        match action {
+            PauseAction::Resume => self.resume(),
+            //the same as playing again after a game over
+            PauseAction::Restart => {
+                self.resume();
+                match self.practice {
+                    Some(options) => self.start_practice(options),
+                    None => self.reset_game(),
+                }
+            }
+            PauseAction::Quit => {
+                //an abandoned game is neither saved as a replay nor left connected
+                self.netplay = None;
+                self.spectating = None;
+                self.recorder = None;
+                self.screen = GameScreen::Menu(MainMenu::new());
+            }
+            PauseAction::None => {}
+        }
+    }
+
+    fn game_over_action(&mut self, ctx: &mut Context, action: GameOverAction) {
+        match action {
+            GameOverAction::PlayAgain => match self.practice {
+                Some(options) => self.start_practice(options),
+                None => self.reset_game(),
+            },
+            GameOverAction::NewGamePlus => {
+                let (prestige, score) = (self.prestige + 1, self.score);
+                self.reset_game();
+                self.enter_prestige(prestige, score);
+            }
+            GameOverAction::MainMenu => self.screen = GameScreen::Menu(MainMenu::new()),
+            GameOverAction::Exit => {
+                self.telemetry.set_enabled(false);
+                event::quit(ctx);
+            }
+            GameOverAction::None => {}
+        }
+    }
+
+    //the state streamed to remote-control clients each tick
+    fn remote_state(&self) -> remote::StateMessage {
+        remote::StateMessage {
+            tick: self.stats.ticks,
+            score: self.score,
+            lives: self.lives,
+            game_over: self.game_over(),
+            pacman: remote::EntityState {
+                x: self.pacman_x.to_f32(),
+                y: self.pacman_y.to_f32(),
+                direction: self.current_direction,
+            },
+            ghosts: self
+                .ghosts
+                .iter()
+                .map(|ghost| remote::GhostState {
+                    x: ghost.x.to_f32(),
+                    y: ghost.y.to_f32(),
+                    direction: ghost.direction,
+                    vulnerable: ghost.is_vulnerable(),
+                })
+                .collect(),
+            dots_remaining: self.dots.len(),
+            power_pellet_timer: self.power_pellet_timer,
+        }
+    }
+
+    //which screen the player is looking at, for the session log
+    fn screen_name(&self) -> &'static str {
+        match self.screen {
+            _ if self.crash_dialog.is_some() => "crash_dialog",
+            GameScreen::Settings(..) => "settings",
+            GameScreen::History(_) => "history",
+            GameScreen::Lobby(_) => "lobby",
+            GameScreen::Practice(_) => "practice",
+            _ if self.cheat_menu.is_some() => "cheats",
+            _ if self.intro.is_some() => "intro",
+            _ if self.race.is_some() => "race",
+            _ if self.photo.is_some() => "photo",
+            GameScreen::Menu(_) => "main_menu",
+            GameScreen::Paused(_) => "paused",
+            //a versus game waits for the other player to confirm it's over before its menu
+            GameScreen::GameOver(_) => "game_over",
+            GameScreen::Playing if self.game_over() => "game_over",
+            GameScreen::Playing if self.playback.is_some() => "replay",
+            GameScreen::Playing => "playing",
+        }
+    }
+
+    //the window's title: the game's name, and whether it's waiting on the player
+    fn title_for_state(&self) -> String {
+        if let GameScreen::Menu(_) | GameScreen::History(_) | GameScreen::Lobby(_) | GameScreen::Practice(_) =
+            self.screen.beneath_settings()
+        {
+            WINDOW_TITLE.to_string()
+        } else if let Some(outcome) = self.race.as_ref().and_then(|race| race.outcome()) {
+            format!("{} - Race over: {}", WINDOW_TITLE, outcome.text())
+        } else if self.game_over() && self.race.is_none() {
+            format!("{} - Game Over, {} points", WINDOW_TITLE, self.score)
+        } else if self.paused() {
+            format!("{} - Paused", WINDOW_TITLE)
+        } else {
+            WINDOW_TITLE.to_string()
+        }
+    }
+
+    //keeps the title in step with the game. A game that ends or stops by itself while the window
+    //is in the background also flashes the taskbar entry, or bounces the dock icon
+    fn sync_window_title(&mut self, ctx: &mut Context) {
+        let title = self.title_for_state();
+        if title == self.window_title {
+            return;
+        }
+        graphics::set_window_title(ctx, &title);
+        if !self.focused && title != WINDOW_TITLE {
+            graphics::window(ctx).request_user_attention(Some(UserAttentionType::Informational));
+        }
+        self.window_title = title;
+    }
+
+    //capture the full game for a save slot
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            pacman: (self.pacman_x.to_f32(), self.pacman_y.to_f32()),
+            current_direction: self.current_direction,
+            requested_direction: self.requested_direction,
+            dots: self.dots.iter().map(|dot| (dot.x, dot.y)).collect(),
+            power_pellets: self.power_pellets.iter().map(|pellet| (pellet.x, pellet.y)).collect(),
+            power_pellet_active: self.power_pellet_active,
+            power_pellet_timer: self.power_pellet_timer,
+            slow_timer: self.slow_timer,
+            held_item: self.held_item,
+            freeze_timer: self.freeze_timer,
+            ghost_mode: self.ghost_mode.clone(),
+            regrowth: self.regrowth.clone(),
+            fruit_eaten: self.fruit_eaten.clone(),
+            entities: self.entities.iter().filter_map(|entity| entity.save()).collect(),
+            invulnerable_ticks: self.invulnerable_ticks,
+            round: self.round,
+            ghost_chain: self.ghost_chain,
+            pellet_ghosts: self.pellet_ghosts,
+            assists: self.assists,
+            prestige: self.prestige,
+            moving_fruit: self.moving_fruit,
+            global_dots: self.global_dots,
+            pen_idle_ticks: self.pen_idle_ticks,
+            level_ticks: self.level_ticks,
+            level_dots_eaten: Some(self.level_dots_eaten),
+            level: self.level,
+            ghosts: self.ghosts.clone(),
+            score: self.score,
+            lives: self.lives,
+            rng: self.rng.clone(),
+        }
+    }
+
+    //resume a game from a save slot
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.restore_board(snapshot);
+        //a resumed game doesn't start from a seed, so it can't be recorded
+        self.recorder = None;
+        self.playback = None;
+        self.stats = GameStats::default();
+        self.screen = GameScreen::Playing;
+    }
+
+    //puts everything the snapshot holds back in place
+    fn restore_board(&mut self, snapshot: Snapshot) {
+        self.pacman_x = Fixed::from_f32(snapshot.pacman.0);
+        self.pacman_y = Fixed::from_f32(snapshot.pacman.1);
+        self.current_direction = snapshot.current_direction;
+        if self.current_direction != Direction::None {
+            self.facing = self.current_direction;
+        }
+        self.requested_direction = snapshot.requested_direction;
+        self.dots.clear();
+        for &(x, y) in &snapshot.dots {
+            self.dots.push(ggez::mint::Point2 { x, y });
+        }
+        //saves from before the count was kept go by the dots left
+        self.level_dots_eaten = snapshot
+            .level_dots_eaten
+            .unwrap_or_else(|| self.maze_dots.saturating_sub(self.dots.len()) as u32);
+        self.power_pellets.restore(&snapshot.power_pellets);
+        self.power_pellet_active = snapshot.power_pellet_active;
+        self.power_pellet_timer = snapshot.power_pellet_timer;
+        self.slow_timer = snapshot.slow_timer;
+        self.held_item = snapshot.held_item;
+        self.freeze_timer = snapshot.freeze_timer;
+        self.ghost_mode = snapshot.ghost_mode;
+        //saves from mazes without the rule, or from before it, have nothing growing back
+        self.regrowth = match snapshot.regrowth {
+            Some(regrowth) if self.ruleset.regrow => Some(regrowth),
+            _ => None,
+        };
+        self.fruit_eaten = snapshot.fruit_eaten;
+        self.entities = snapshot.entities.iter().map(SavedEntity::restore).collect();
+        self.invulnerable_ticks = snapshot.invulnerable_ticks;
+        self.round = snapshot.round;
+        self.ghost_chain = snapshot.ghost_chain;
+        self.pellet_ghosts = snapshot.pellet_ghosts;
+        self.assists = snapshot.assists;
+        self.prestige = snapshot.prestige;
+        self.global_dots = snapshot.global_dots;
+        self.pen_idle_ticks = snapshot.pen_idle_ticks;
+        self.level_ticks = snapshot.level_ticks;
+        self.level = snapshot.level;
+        self.set_moving_fruit(snapshot.moving_fruit);
+        self.ghosts = snapshot.ghosts;
+        self.apply_speeds(self.level);
+        self.score = snapshot.score;
+        self.lives = snapshot.lives;
+        self.rng = snapshot.rng;
+    }
+
+    //puts `ruleset` in play on the same maze, redoing what the rules decide about it: the power
+    //pellets, the ghost roster, the wrapping and the hash replays and versus games check. Takes
+    //hold from the next reset_game
+    fn set_ruleset(&mut self, ruleset: Ruleset) {
+        if ruleset == self.ruleset {
+            return;
+        }
+        let rows = self.map.rows();
+        let rows: Vec<&str> = rows.iter().map(String::as_str).collect();
+        self.power_pellets =
+            if ruleset.power_ups.power_pellets { PowerPellets::for_maze(&self.layout) } else { PowerPellets::none() };
+        self.spawns = spawn::collect(&rows, &self.layout, ruleset.ghosts);
+        self.walls = maze_walls(&self.map, &self.layout, &ruleset, true);
+        self.pacman_walls = maze_walls(&self.map, &self.layout, &ruleset, false);
+        let mut played = self.map.clone();
+        played.rules = ruleset.clone();
+        self.map_hash = replay::map_hash(&played);
+        self.ruleset = ruleset;
+        self.invalidate_walls();
+    }
+
+    //the code someone else can enter to play this game from the start. Only games that started
+    //from a seed on the first level have one, so not practice, New Game+ or a resumed save
+    fn share_code(&self) -> Option<String> {
+        if self.recorder.is_none() || self.prestige > 0 {
+            return None;
+        }
+        Challenge {
+            map_id: share::map_id(&self.map),
+            seed: self.seed,
+            ruleset: self.ruleset.clone(),
+            difficulty: self.difficulty,
+            pellet_stacking: self.pellet_stacking,
+            moving_fruit: self.moving_fruit,
+        }
+        .encode()
+    }
+
+    //a new game from a share code, the same one its sharer played
+    fn start_challenge(&mut self, challenge: Challenge) {
+        self.set_ruleset(challenge.ruleset);
+        self.reset_game();
+        self.seed = challenge.seed;
+        self.rng = Pcg32::seed_from_u64(self.seed);
+        self.difficulty = challenge.difficulty;
+        self.pellet_stacking = challenge.pellet_stacking;
+        self.set_moving_fruit(challenge.moving_fruit);
+        self.stats = GameStats::new(self.seed);
+        self.screen = GameScreen::Playing;
+        self.intro = Some(Intro::new(false));
+    }
+
+    //start a fresh game driven by a recorded replay
+    fn start_replay(&mut self, replay: Replay) {
+        self.reset_game();
+        self.seed = replay.seed;
+        self.rng = Pcg32::seed_from_u64(self.seed);
+        self.difficulty = replay.difficulty;
+        self.pellet_stacking = replay.pellet_stacking;
+        self.set_moving_fruit(replay.moving_fruit);
+        self.enter_prestige(replay.prestige, replay.start_score);
+        //slow motion doesn't change what happens, so replays play at full speed
+        self.assists = Assists {
+            intersection_pause: replay.intersection_pause,
+            ..Assists::default()
+        };
+        self.recorder = None;
+        self.playback = Some(ReplayPlayer::new(replay));
+        self.stats = GameStats::new(self.seed);
+        self.screen = GameScreen::Playing;
+    }
+
+    //a game for learning the maze: any level, any number of ghosts, and neither a replay nor a
+    //history entry at the end
+    fn start_practice(&mut self, options: PracticeOptions) {
+        self.reset_game();
+        self.ghosts.truncate(options.ghosts);
+        self.level = options.level;
+        self.apply_speeds(options.level);
+        self.register_fruit();
+        self.recorder = None;
+        self.practice = Some(options);
+        self.screen = GameScreen::Playing;
+    }
+
+    //a practice game with every ghost and lives to spare, under the tutorial's prompts
+    fn start_tutorial(&mut self) {
+        self.start_practice(PracticeOptions {
+            level: 1,
+            ghosts: self.spawns.roster().len(),
+            infinite_lives: true,
+            freeze_pellets: false,
+        });
+        self.tutorial = Some(Tutorial::new());
+    }
+
+    //start a versus game against the other player, one as pacman and one as the red ghost
+    fn start_netplay(&mut self, netplay: Session) {
+        self.reset_game();
+        self.seed = netplay.seed();
+        self.rng = Pcg32::seed_from_u64(self.seed);
+        //both sides have to simulate the same game whatever their settings say
+        self.difficulty = Difficulty::Normal;
+        self.pellet_stacking = PelletStacking::Refresh;
+        self.set_moving_fruit(false);
+        self.speed_table = SpeedTable::default();
+        self.apply_speeds(1);
+        self.assists = Assists::default();
+        //replays can't hold the ghost player's input
+        self.recorder = None;
+        self.stats = GameStats::new(self.seed);
+        if let Some(ghost) = self.ghosts.first_mut() {
+            ghost.player_direction = Some(Direction::None);
+        }
+        //mods keep state of their own that a rollback can't rewind
+        self.mods = None;
+        self.rollback_frames.clear();
+        self.local_direction = Direction::None;
+        self.screen = GameScreen::Playing;
+        self.netplay = Some(netplay);
+    }
+
+    //watch a versus game from the host's confirmed inputs
+    fn start_spectating(&mut self, spectator: Spectator) {
+        self.reset_game();
+        self.seed = spectator.seed();
+        self.rng = Pcg32::seed_from_u64(self.seed);
+        self.difficulty = Difficulty::Normal;
+        self.pellet_stacking = PelletStacking::Refresh;
+        self.set_moving_fruit(false);
+        self.speed_table = SpeedTable::default();
+        self.apply_speeds(1);
+        self.assists = Assists::default();
+        self.recorder = None;
+        self.stats = GameStats::new(self.seed);
+        if let Some(ghost) = self.ghosts.first_mut() {
+            ghost.player_direction = Some(Direction::None);
+        }
+        //the players have mods switched off too
+        self.mods = None;
+        self.screen = GameScreen::Playing;
+        self.spectating = Some(spectator);
+    }
+
+    //a fresh game for the title screen's demo, which never leaves a replay behind
+    fn start_demo(&mut self) {
+        self.reset_game();
+        self.recorder = None;
+    }
+
+    //one tick of the demo, with the simulation bot steering. It skips step(), so nothing goes
+    //into the history, the session log, the crash state or the mods, and a finished demo just
+    //starts over
+    fn demo_tick(&mut self) {
+        self.requested_direction = simulate::choose_direction(&env::observe(self));
+        self.tick();
+        self.mod_hooks.clear();
+        if self.game_over() {
+            self.start_demo();
+        }
+    }
+
+    //two players side by side on one keyboard, each with a game of their own. Both start from
+    //the same seed, so the mazes play out alike until the players' choices part them. The window
+    //doubles in width to fit the second maze
+    fn start_race(&mut self, ctx: &mut Context) -> GameResult {
+        self.reset_game();
+        self.recorder = None;
+        let mut rival = MainState::new(self.settings.clone(), self.map.clone())?;
+        rival.screen = GameScreen::Playing;
+        rival.record_history = false;
+        rival.speed_table = self.speed_table.clone();
+        rival.reset_game();
+        rival.seed = self.seed;
+        rival.rng = Pcg32::seed_from_u64(self.seed);
+        rival.recorder = None;
+        rival.stats = GameStats::new(self.seed);
+
+        let (w, h) = window_size(&self.map);
+        graphics::set_drawable_size(ctx, w * 2.0, h)?;
+        graphics::set_screen_coordinates(ctx, graphics::Rect::new(0.0, 0.0, w * 2.0, h))?;
+        self.rival = Some(Box::new(rival));
+        self.race = Some(Race::new());
+        self.screen = GameScreen::Playing;
+        Ok(())
+    }
+
+    //back to a single maze's window and the title screen
+    fn end_race(&mut self, ctx: &mut Context) {
+        self.race = None;
+        self.rival = None;
+        let (w, h) = window_size(&self.map);
+        let resized = graphics::set_drawable_size(ctx, w, h)
+            .and_then(|_| graphics::set_screen_coordinates(ctx, graphics::Rect::new(0.0, 0.0, w, h)));
+        if let Err(e) = resized {
+            eprintln!("Could not resize the window after the race: {}", e);
+        }
+        self.held_directions.clear();
+        self.screen = GameScreen::Menu(MainMenu::new());
+    }
+
+    //one tick of both sides of a race, until it's decided
+    fn race_tick(&mut self) {
+        if self.race.as_ref().is_none_or(|race| race.outcome().is_some()) {
+            return;
+        }
+        let right = match &mut self.rival {
+            Some(rival) => rival.racer_tick(),
+            None => return,
+        };
+        let left = self.racer_tick();
+        if let Some(race) = &mut self.race {
+            race.tick(left, right);
+        }
+    }
+
+    //one tick of one side of a race. Like the demo it skips step(), so neither side leaves a
+    //replay or a history entry, and a player out of lives just waits for the result, which the
+    //race shows in place of the game-over menu
+    fn racer_tick(&mut self) -> Standing {
+        if !self.game_over() {
+            self.tick();
+        }
+        self.mod_hooks.clear();
+        if let GameScreen::GameOver(_) = self.screen {
+            self.screen = GameScreen::Playing;
+        }
+        Standing {
+            score: self.score,
+            cleared: matches!(self.round, Round::LevelComplete { .. }),
+            out: self.game_over(),
+        }
+    }
+
+    //keys during a race: each player steers with their own, and Esc pauses both games
+    fn race_key(&mut self, ctx: &mut Context, keycode: KeyCode) {
+        if let GameScreen::Paused(_) = self.screen {
+            match keycode {
+                KeyCode::Escape => self.resume(),
+                KeyCode::M => self.end_race(ctx),
+                _ => {}
+            }
+            return;
+        }
+        if self.race.as_ref().is_some_and(|race| race.outcome().is_some()) {
+            if matches!(keycode, KeyCode::Return | KeyCode::Escape) {
+                self.end_race(ctx);
+            }
+            return;
+        }
+        if keycode == KeyCode::Escape {
+            self.pause();
+            if let Some(rival) = &mut self.rival {
+                rival.held_directions.clear();
+            }
+            return;
+        }
+        if let Some(dir) = race::LEFT_KEYS.direction_for(keycode) {
+            self.press_direction(dir);
+        } else if let (Some(dir), Some(rival)) = (self.settings.bindings.direction_for(keycode), &mut self.rival) {
+            rival.press_direction(dir);
+        }
+    }
+
+    //a direction key went down: it's held until released, and the next tick turns as soon as
+    //it's valid
+    fn press_direction(&mut self, dir: Direction) {
+        if !self.held_directions.contains(&dir) {
+            self.held_directions.push(dir);
+        }
+        self.requested_direction = dir;
+    }
+
+    //ends the intro, back to the menu if that's where it was opened from
+    fn close_intro(&mut self) {
+        if let Some(intro) = self.intro.take() {
+            if intro.from_menu {
+                self.screen = GameScreen::Menu(MainMenu::new());
+            }
+        }
+    }
+
+    //out of lives, whatever the window is showing over it
+    fn game_over(&self) -> bool {
+        self.round == Round::GameOver
+    }
+
+    fn paused(&self) -> bool {
+        matches!(self.screen, GameScreen::Paused(_))
+    }
+
+    //whether the inputs come over the network, as a player or a spectator
+    fn networked(&self) -> bool {
+        self.netplay.is_some() || self.spectating.is_some()
+    }
+
+    //plays the next tick the host confirmed, and a few more per frame while catching up
+    fn update_spectating(&mut self) -> Result<(), String> {
+        for _ in 0..SPECTATOR_CATCH_UP_TICKS {
+            let spectator = match &mut self.spectating {
+                Some(spectator) => spectator,
+                None => return Ok(()),
+            };
+            let (pacman, ghost) = match spectator.next_inputs()? {
+                Some(inputs) => inputs,
+                None => return Ok(()),
+            };
+            let behind = spectator.behind();
+
+            self.requested_direction = pacman;
+            if let Some(ghost_player) = self.ghosts.first_mut() {
+                ghost_player.player_direction = Some(ghost);
+            }
+            self.tick();
+            self.mod_hooks.clear();
+            self.stats.record_tick(self.score, self.lives, self.pacman_x.to_f32(), self.pacman_y.to_f32());
+            //someone else's game doesn't go into this player's history
+            if self.game_over() {
+                self.spectating = None;
+                return Ok(());
+            }
+            if !behind {
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+
+    //one frame of a versus game: fix up wrong guesses, then run the next tick unless too far
+    //ahead of the other player. Returns false once the game is over for both players
+    fn update_netplay(&mut self) -> Result<bool, String> {
+        let mispredicted = match &mut self.netplay {
+            Some(netplay) => netplay.poll()?,
+            None => return Ok(false),
+        };
+        if let Some(tick) = mispredicted {
+            let present = self.stats.ticks;
+            self.rewind_to(tick);
+            while self.stats.ticks < present && !self.game_over() {
+                self.netplay_tick();
+            }
+        }
+        self.check_state_hashes()?;
+
+        let confirmed = self.netplay.as_ref().map_or(0, |netplay| netplay.confirmed());
+        //a game over might still be undone by the other player's late input
+        if self.game_over() {
+            return Ok(confirmed < self.stats.ticks);
+        }
+        if self.stats.ticks >= confirmed + MAX_ROLLBACK {
+            return Ok(true);
+        }
+        if let Some(netplay) = &mut self.netplay {
+            netplay.send_local(self.stats.ticks, self.local_direction)?;
+        }
+        self.netplay_tick();
+        Ok(true)
+    }
+
+    //a tick of a versus game, remembering where it started
+    fn netplay_tick(&mut self) {
+        let tick = self.stats.ticks;
+        let (pacman, ghost) = match &mut self.netplay {
+            Some(netplay) => netplay.inputs(tick),
+            None => return,
+        };
+        self.rollback_frames.push_back(RollbackFrame {
+            tick,
+            snapshot: self.snapshot(),
+            stats: self.stats.mark(),
+        });
+        //a little extra history so the hash check can still find the confirmed ticks
+        if self.rollback_frames.len() > 2 * MAX_ROLLBACK as usize {
+            self.rollback_frames.pop_front();
+        }
+
+        self.requested_direction = pacman;
+        if let Some(ghost_player) = self.ghosts.first_mut() {
+            ghost_player.player_direction = Some(ghost);
+        }
+        self.tick();
+        self.mod_hooks.clear();
+        self.stats.record_tick(self.score, self.lives, self.pacman_x.to_f32(), self.pacman_y.to_f32());
+    }
+
+    //puts the game back to the start of `tick`
+    fn rewind_to(&mut self, tick: u32) {
+        while let Some(frame) = self.rollback_frames.pop_back() {
+            if frame.tick == tick {
+                //the ghost player's input is overwritten by the re-simulated tick
+                self.restore_board(frame.snapshot);
+                self.stats.rewind(frame.stats);
+                //the snapshot brings back the round, which may undo a game over
+                if let GameScreen::GameOver(_) = self.screen {
+                    self.screen = GameScreen::Playing;
+                }
+                return;
+            }
+        }
+    }
+
+    //sends a hash of each confirmed state the other player should have reached too
+    fn check_state_hashes(&mut self) -> Result<(), String> {
+        while let Some(tick) = self.netplay.as_ref().and_then(|netplay| netplay.pending_hash_tick()) {
+            let hash = match self.rollback_frames.iter().find(|frame| frame.tick == tick) {
+                Some(frame) => netplay::state_hash(&frame.snapshot),
+                //not simulated yet
+                None if tick >= self.stats.ticks => return Ok(()),
+                //already dropped, this can't be compared any more
+                None => 0,
+            };
+            if let Some(netplay) = &mut self.netplay {
+                netplay.record_hash(tick, hash)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn draw_walls(&self, ctx: &mut Context, color: Color) -> GameResult {
+        for wall in self.walls.rects() {
+            let wall_mesh = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                *wall,
+                color,
+            )?;
+            graphics::draw(ctx, &wall_mesh, DrawParam::default())?;
+        }
+        Ok(())
+    }
+
+    //the walls never move during a level, so they are drawn into an offscreen canvas once and
+    //each frame blits that, whatever the size of the maze. Rebuilt when the window size changes
+    //or after invalidate_walls. They're drawn white, and tinted when blitted
+    fn ensure_wall_canvas(&mut self, ctx: &mut Context) -> GameResult {
+        let size = graphics::drawable_size(ctx);
+        if self.wall_canvas.as_ref().is_some_and(|(_, canvas_size)| *canvas_size == size) {
+            return Ok(());
+        }
+        let canvas = graphics::Canvas::with_window_size(ctx)?;
+        graphics::set_canvas(ctx, Some(&canvas));
+        graphics::clear(ctx, Color::new(0.0, 0.0, 0.0, 0.0));
+        let drawn = self.draw_walls(ctx, Color::WHITE);
+        graphics::set_canvas(ctx, None);
+        drawn?;
+        self.wall_canvas = Some((canvas, size));
+        Ok(())
+    }
+
+    //call whenever `walls` changes
+    fn invalidate_walls(&mut self) {
+        self.wall_canvas = None;
+    }
+
+    fn fps_cap(&self) -> u32 {
+        if self.focused {
+            self.settings.fps_cap
+        } else {
+            UNFOCUSED_FPS
+        }
+    }
+
+    //how long a frame has at the FPS cap. Uncapped frames are held to the tick rate, below which
+    //smoothing has nothing to draw between
+    fn frame_budget(&self) -> Duration {
+        match self.fps_cap() {
+            0 => Duration::from_secs_f64(1.0 / TICKS_PER_SECOND as f64),
+            cap => Duration::from_secs_f64(1.0 / cap as f64),
+        }
+    }
+
+    //sleeps off the rest of the frame when an FPS cap is set, otherwise just yields
+    fn pace_frame(&mut self) {
+        if self.fps_cap() == 0 {
+            timer::yield_now();
+            return;
+        }
+        let frame = self.frame_budget();
+        let now = Instant::now();
+        if self.next_frame > now {
+            thread::sleep(self.next_frame - now);
+            self.next_frame += frame;
+        } else {
+            //fell behind, so start counting again from now rather than rushing to catch up
+            self.next_frame = now + frame;
+        }
+    }
+
+    //the lines of the HUD widgets switched on in the settings
+    fn hud_widgets(&self) -> Vec<String> {
+        let shown = self.settings.hud;
+        let mut lines = Vec::new();
+        if shown.level_time {
+            let tenths = self.level_ticks * 10 / TICKS_PER_SECOND;
+            lines.push(format!("Time: {}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10));
+        }
+        if shown.dots_left {
+            lines.push(format!("Dots: {}", self.dots.len()));
+        }
+        if shown.ghost_mode {
+            //confused ghosts wander on a chat vote, otherwise they follow the schedule
+            let confused = self.ghosts.iter().map(|ghost| ghost.confused_timer).fold(0.0, f32::max);
+            let mode = match self.ghost_mode.mode() {
+                GhostMode::Scatter => "scatter",
+                GhostMode::Chase => "chase",
+            };
+            lines.push(if self.power_pellet_active {
+                format!("Ghosts: frightened {:.1}s", self.power_pellet_timer.max(0.0))
+            } else if confused > 0.0 {
+                format!("Ghosts: confused {:.1}s", confused)
+            } else {
+                match self.ghost_mode.seconds_left() {
+                    Some(seconds) => format!("Ghosts: {} {:.1}s", mode, seconds),
+                    None => format!("Ghosts: {}", mode),
+                }
+            });
+        }
+        lines
+    }
+
+    //the maze and everything in it, without the HUD
+    fn draw_board(&self, ctx: &mut Context) -> GameResult {
+        profile_scope!("draw_board");
+        //draw walls, from the cached canvas when there is one
+        let palette = if self.settings.high_contrast { Palette::high_contrast() } else { Palette::classic() };
+        let wall_color = match (&self.photo, self.round) {
+            (Some(photo), _) => photo.theme.walls(),
+            //a cleared maze flashes white
+            (None, Round::LevelComplete { ticks_left }) if (ticks_left / LEVEL_FLASH_TICKS).is_multiple_of(2) => Color::WHITE,
+            (None, _) => palette.walls,
+        };
+        match &self.wall_canvas {
+            Some((canvas, _)) => graphics::draw(ctx, canvas, DrawParam::default().color(wall_color))?,
+            None => self.draw_walls(ctx, wall_color)?,
+        }
+        for (col, row) in self.layout.door_cells() {
+            let bar = door_bar(&self.layout, col, row);
+            let door = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), bar, DOOR_COLOR)?;
+            graphics::draw(ctx, &door, DrawParam::default())?;
+        }
+
+        //rows and columns locked against regrowth are shaded, to show the ground won so far
+        if let Some(regrowth) = &self.regrowth {
+            let (width, height) = maze_size(&self.map);
+            let mut builder = graphics::MeshBuilder::new();
+            let mut any = false;
+            for row in regrowth.locked_rows() {
+                builder.rectangle(graphics::DrawMode::fill(), graphics::Rect::new(0.0, row as f32 * CELL_SIZE, width, CELL_SIZE), LOCKED_LINE)?;
+                any = true;
+            }
+            for col in regrowth.locked_cols() {
+                builder.rectangle(graphics::DrawMode::fill(), graphics::Rect::new(col as f32 * CELL_SIZE, 0.0, CELL_SIZE, height), LOCKED_LINE)?;
+                any = true;
+            }
+            if any {
+                let mesh = builder.build(ctx)?;
+                graphics::draw(ctx, &mesh, DrawParam::default())?;
+            }
+        }
+
+        //draw dots
+        for dot in &self.dots {
+            let dot_mesh = graphics::Mesh::new_circle(
+                ctx,
+                graphics::DrawMode::fill(),
+                *dot,
+                DOT_SIZE/2.0,
+                0.1,
+                palette.dots,
+            )?;
+            graphics::draw(ctx, &dot_mesh, DrawParam::default())?;
+        }
+
+        //draw power pellets
+        for pellet in &self.power_pellets {
+            let pellet_mesh = graphics::Mesh::new_circle(
+                ctx,
+                graphics::DrawMode::fill(),
+                *pellet,
+                POWER_PELLET_SIZE/2.0,
+                0.1,
+                palette.dots,
+            )?;
+            graphics::draw(ctx, &pellet_mesh, DrawParam::default())?;
+        }
+
+        self.draw_movers(ctx, &palette)?;
+
+        //on a wrapping maze anything half way over an edge shows up on the far side too, so the
+        //movers are drawn again a maze away in each direction and the window clips the rest
+        if let Some((width, height)) = self.walls.wrap_size() {
+            let (width, height) = (width.to_f32(), height.to_f32());
+            for (dx, dy) in [(width, 0.0), (-width, 0.0), (0.0, height), (0.0, -height)] {
+                let shift = DrawParam::default().dest(ggez::mint::Point2 { x: dx, y: dy }).trans.to_bare_matrix();
+                with_view(ctx, shift, |ctx| self.draw_movers(ctx, &palette))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    //everything on the board that moves: items, pacman and the ghosts
+    fn draw_movers(&self, ctx: &mut Context, palette: &Palette) -> GameResult {
+        //draw mod-spawned items and hazards
+        for entity in &self.entities {
+            entity.draw(ctx)?;
+        }
+
+        //pacman blinks while he can't be caught
+        if (self.invulnerable_ticks / 8).is_multiple_of(2) {
+            self.draw_pacman(ctx, palette)?;
+        }
+
+        //draw the ghosts in one batch, tinting the same circle for each
+        let circle = match &self.circle_sprite {
+            Some(circle) => circle.clone(),
+            None => return Ok(()),
+        };
+        let mut batch = graphics::spritebatch::SpriteBatch::new(circle);
+        for (index, ghost) in self.ghosts.iter().enumerate() {
+            if ghost.in_play() {
+                let color = if self.is_flashing(ghost) {
+                    palette.frightened_flash
+                } else if ghost.is_vulnerable() {
+                    palette.frightened
+                } else {
+                    ghost.color
+                };
+                let (x, y) = self.interpolation.ghost(index, (ghost.x, ghost.y));
+                batch.add(sprites::circle_param(x, y, GHOST_SIZE, color));
+            }
+        }
+        graphics::draw(ctx, &batch, DrawParam::default())?;
+        self.draw_eyes(ctx)?;
+
+        //high contrast rings every ghost
+        if let Some((color, width)) = palette.outline {
+            for (index, ghost) in self.ghosts.iter().enumerate().filter(|(_, ghost)| ghost.in_play()) {
+                let radius = GHOST_SIZE / 2.0;
+                let (x, y) = self.interpolation.ghost(index, (ghost.x, ghost.y));
+                let center = ggez::mint::Point2 { x: x + radius, y: y + radius };
+                let ring = graphics::Mesh::new_circle(ctx, graphics::DrawMode::stroke(width), center, radius, 0.1, color)?;
+                graphics::draw(ctx, &ring, DrawParam::default())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    //whether a frightened ghost is close enough to recovering to flash, and on the flash's
+    //light half. Timed by the ghost's own fright, so it flashes the same whatever the frame rate
+    fn is_flashing(&self, ghost: &Ghost) -> bool {
+        let left = ghost.frightened_timer;
+        ghost.is_vulnerable()
+            && left < self.settings.frightened_flash_seconds
+            && ((left / FRIGHTENED_FLASH_PERIOD) as u32).is_multiple_of(2)
+    }
+
+    //the board's edge glowing on the side the nearest unseen ghost is closing in from, fading
+    //towards the middle. Drawn in maze coordinates
+    fn draw_edge_glow(&self, ctx: &mut Context) -> GameResult {
+        let pacman = (self.pacman_x + PACMAN.half(), self.pacman_y + PACMAN.half());
+        let ghosts = self
+            .ghosts
+            .iter()
+            .filter(|ghost| ghost.in_play())
+            .map(|ghost| ((ghost.x + GHOST.half(), ghost.y + GHOST.half()), ghost.direction));
+        let glow = match proximity::edge_glow(&self.walls, pacman, ghosts) {
+            Some(glow) => glow,
+            None => return Ok(()),
+        };
+        let (width, height) = maze_size(&self.map);
+        let mut builder = graphics::MeshBuilder::new();
+        for strip in 0..GLOW_STRIPS {
+            let inset = strip as f32 * GLOW_STRIP_WIDTH;
+            let rect = match glow.edge {
+                proximity::Edge::Top => graphics::Rect::new(0.0, inset, width, GLOW_STRIP_WIDTH),
+                proximity::Edge::Bottom => graphics::Rect::new(0.0, height - inset - GLOW_STRIP_WIDTH, width, GLOW_STRIP_WIDTH),
+                proximity::Edge::Left => graphics::Rect::new(inset, 0.0, GLOW_STRIP_WIDTH, height),
+                proximity::Edge::Right => graphics::Rect::new(width - inset - GLOW_STRIP_WIDTH, 0.0, GLOW_STRIP_WIDTH, height),
+            };
+            let fade = 1.0 - strip as f32 / GLOW_STRIPS as f32;
+            builder.rectangle(graphics::DrawMode::fill(), rect, Color::new(1.0, 0.1, 0.1, 0.6 * glow.strength * fade))?;
+        }
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+
+    //eaten ghosts on their way home, as two eyes looking the way they go
+    fn draw_eyes(&self, ctx: &mut Context) -> GameResult {
+        if !self.ghosts.iter().any(Ghost::is_eyes) {
+            return Ok(());
+        }
+        let mut builder = graphics::MeshBuilder::new();
+        for (index, ghost) in self.ghosts.iter().enumerate().filter(|(_, ghost)| ghost.is_eyes()) {
+            let (x, y) = self.interpolation.ghost(index, (ghost.x, ghost.y));
+            let (center_x, center_y) = (x + GHOST_SIZE / 2.0, y + GHOST_SIZE / 2.0);
+            let (look_x, look_y) = match ghost.direction {
+                Direction::Up => (0.0, -2.0),
+                Direction::Down => (0.0, 2.0),
+                Direction::Left => (-2.0, 0.0),
+                Direction::Right => (2.0, 0.0),
+                Direction::None => (0.0, 0.0),
+            };
+            for side in [-1.0, 1.0] {
+                let eye = ggez::mint::Point2 { x: center_x + side * 5.0, y: center_y - 2.0 };
+                builder.circle(graphics::DrawMode::fill(), eye, 4.5, 0.1, Color::WHITE)?;
+                let pupil = ggez::mint::Point2 { x: eye.x + look_x, y: eye.y + look_y };
+                builder.circle(graphics::DrawMode::fill(), pupil, 2.0, 0.1, Color::BLUE)?;
+            }
+        }
+        let eyes = builder.build(ctx)?;
+        graphics::draw(ctx, &eyes, DrawParam::default())
+    }
+
+    //pacman as a circle with a wedge cut out for his mouth, turned the way he faces
+    fn draw_pacman(&self, ctx: &mut Context, palette: &Palette) -> GameResult {
+        let radius = PACMAN_SIZE / 2.0;
+        let (x, y) = self.interpolation.pacman((self.pacman_x, self.pacman_y));
+        let center = ggez::mint::Point2 { x: x + radius, y: y + radius };
+        let heading = match self.facing {
+            Direction::Up => -std::f32::consts::FRAC_PI_2,
+            Direction::Down => std::f32::consts::FRAC_PI_2,
+            Direction::Left => std::f32::consts::PI,
+            Direction::Right | Direction::None => 0.0,
+        };
+        //opens over the first half of the chomp and shuts over the second
+        let mouth = MOUTH_ANGLE * (1.0 - (self.mouth_phase * 2.0 - 1.0).abs());
+
+        const SEGMENTS: usize = 24;
+        let mut points = Vec::with_capacity(SEGMENTS + 2);
+        points.push(center);
+        for i in 0..=SEGMENTS {
+            let angle = heading + mouth + (std::f32::consts::TAU - 2.0 * mouth) * i as f32 / SEGMENTS as f32;
+            points.push(ggez::mint::Point2 {
+                x: center.x + radius * angle.cos(),
+                y: center.y + radius * angle.sin(),
+            });
+        }
+        let pacman = graphics::Mesh::new_polygon(ctx, graphics::DrawMode::fill(), &points, palette.pacman)?;
+        graphics::draw(ctx, &pacman, DrawParam::default())?;
+        if let Some((color, width)) = palette.outline {
+            let outline = graphics::Mesh::new_polygon(ctx, graphics::DrawMode::stroke(width), &points, color)?;
+            graphics::draw(ctx, &outline, DrawParam::default())?;
+        }
+        Ok(())
+    }
+
+    //the board through photo mode's camera and filter
+    fn draw_photo_scene(&self, ctx: &mut Context, photo: &PhotoMode) -> GameResult {
+        let (w, h) = graphics::drawable_size(ctx);
+        graphics::clear(ctx, Color::BLACK);
+        with_view(ctx, photo.camera_transform(w, h), |ctx| self.draw_board(ctx))?;
+        photo.draw_filter(ctx)
+    }
+
+    //photo mode's frame: a capture first if one was asked for, then the scene and the controls
+    fn draw_photo(&mut self, ctx: &mut Context) -> GameResult {
+        let capture = self.photo.as_mut().is_some_and(|photo| std::mem::take(&mut photo.pending_capture));
+        let photo = match &self.photo {
+            Some(photo) => photo,
+            None => return Ok(()),
+        };
+        let saved = if capture {
+            Some(photo::capture(ctx, |ctx| self.draw_photo_scene(ctx, photo)))
+        } else {
+            None
+        };
+        self.draw_photo_scene(ctx, photo)?;
+        photo.draw_hint(ctx)?;
+        match saved {
+            Some(Ok(path)) => {
+                println!("Saved photo to {}", path.display());
+                self.effects.toast("Photo saved to gallery");
+            }
+            Some(Err(e)) => eprintln!("Could not save photo: {}", e),
+            None => {}
+        }
+        self.effects.draw_toasts(ctx)
+    }
+
+    //a race's frame: each player's maze in their half of the window with their score above it,
+    //the clock in the middle and the result once there is one
+    fn draw_race(&mut self, ctx: &mut Context) -> GameResult {
+        if let Some(rival) = &mut self.rival {
+            rival.ensure_wall_canvas(ctx)?;
+            if rival.circle_sprite.is_none() {
+                rival.circle_sprite = self.circle_sprite.clone();
+            }
+        }
+        let (rival, race) = match (&self.rival, &self.race) {
+            (Some(rival), Some(race)) => (rival, race),
+            _ => return Ok(()),
+        };
+
+        graphics::clear(ctx, Color::BLACK);
+        let (w, h) = graphics::drawable_size(ctx);
+        let half = w / 2.0;
+        let players: [(&MainState, f32, &str); 2] = [(self, 0.0, "P1 (WASD)"), (rival, half, "P2 (arrows)")];
+        for (game, offset, name) in players {
+            game.draw_race_board(ctx, offset)?;
+            let status = if game.game_over() {
+                "OUT".to_string()
+            } else if matches!(game.round, Round::LevelComplete { .. }) {
+                "CLEARED!".to_string()
+            } else {
+                format!("Lives: {}   Dots: {}", game.lives, game.dots.len())
+            };
+            for (i, line) in [format!("{}   Score: {}", name, game.score), status].iter().enumerate() {
+                graphics::draw(
+                    ctx,
+                    &graphics::Text::new(line.as_str()),
+                    DrawParam::default()
+                        .dest(ggez::mint::Point2 { x: offset + 10.0, y: 10.0 + 20.0 * i as f32 })
+                        .color(Color::WHITE),
+                )?;
+            }
+        }
+        let divider = graphics::Mesh::new_line(
+            ctx,
+            &[ggez::mint::Point2 { x: half, y: 0.0 }, ggez::mint::Point2 { x: half, y: h }],
+            2.0,
+            Color::new(0.5, 0.5, 0.5, 1.0),
+        )?;
+        graphics::draw(ctx, &divider, DrawParam::default())?;
+
+        let seconds = race.seconds_left();
+        let clock = graphics::Text::new(format!("{}:{:02}", seconds / 60, seconds % 60));
+        let clock_w = clock.dimensions(ctx).w * 2.0;
+        let clock_color = if seconds <= 10 { Color::RED } else { Color::YELLOW };
+        graphics::draw(
+            ctx,
+            &clock,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: (w - clock_w) / 2.0, y: 50.0 })
+                .color(clock_color)
+                .scale([2.0, 2.0]),
+        )?;
+
+        let overlay = match (race.outcome(), self.paused()) {
+            (Some(outcome), _) => {
+                let color = match outcome {
+                    RaceOutcome::Winner(_) => Color::YELLOW,
+                    RaceOutcome::Draw => Color::WHITE,
+                };
+                Some((format!("{}!", outcome.text().to_uppercase()), color, "Enter: main menu"))
+            }
+            (None, true) => Some(("PAUSED".to_string(), Color::YELLOW, "M: main menu   Esc: resume")),
+            (None, false) => None,
+        };
+        if let Some((title, color, hint)) = overlay {
+            let background = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(0.0, 0.0, w, h),
+                Color::new(0.0, 0.0, 0.0, 0.7),
+            )?;
+            graphics::draw(ctx, &background, DrawParam::default())?;
+            let title_text = graphics::Text::new(title);
+            let title_w = title_text.dimensions(ctx).w * 2.0;
+            graphics::draw(
+                ctx,
+                &title_text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 { x: (w - title_w) / 2.0, y: h / 2.0 - 60.0 })
+                    .color(color)
+                    .scale([2.0, 2.0]),
+            )?;
+            let hint_text = graphics::Text::new(hint);
+            let hint_w = hint_text.dimensions(ctx).w;
+            graphics::draw(
+                ctx,
+                &hint_text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 { x: (w - hint_w) / 2.0, y: h / 2.0 })
+                    .color(Color::WHITE),
+            )?;
+        }
+
+        if self.console.open {
+            self.console.draw(ctx)?;
+        }
+        if let GameScreen::Settings(screen, _) = &self.screen {
+            screen.draw(ctx, &self.settings)?;
+        }
+        self.effects.draw_toasts(ctx)
+    }
+
+    //one side of a race's board, `offset` across the window. On a wrapping maze the movers are
+    //drawn again a maze away, which would land on the other player's side, so the board is
+    //drawn offscreen first and only its own half copied over
+    fn draw_race_board(&self, ctx: &mut Context, offset: f32) -> GameResult {
+        let draw = |ctx: &mut Context| {
+            self.draw_board(ctx).and_then(|_| match &self.circle_sprite {
+                Some(circle) => self.effects.draw_world(ctx, circle),
+                None => Ok(()),
+            })
+        };
+        if self.walls.wrap_size().is_none() {
+            return with_view(ctx, translated(board_transform(), offset, 0.0), draw);
+        }
+        let canvas = graphics::Canvas::with_window_size(ctx)?;
+        graphics::set_canvas(ctx, Some(&canvas));
+        graphics::clear(ctx, Color::new(0.0, 0.0, 0.0, 0.0));
+        let drawn = with_view(ctx, board_transform(), draw);
+        graphics::set_canvas(ctx, None);
+        drawn?;
+        graphics::draw(
+            ctx,
+            &canvas,
+            DrawParam::default()
+                .src(graphics::Rect::new(0.0, 0.0, 0.5, 1.0))
+                .dest(ggez::mint::Point2 { x: offset, y: 0.0 }),
+        )
+    }
+
+    //how far this frame is from the last tick to the next, for drawing between them. Without
+    //smoothing everything is drawn where the last tick left it, except on the split view's
+    //smoothed half
+    fn frame_blend(&self, ctx: &mut Context) -> f32 {
+        if self.settings.smooth_motion || self.compare_motion {
+            timer::remaining_update_time(ctx).as_secs_f32() * self.tick_rate() as f32
+        } else {
+            1.0
+        }
+    }
+
+    //the split view's left half: the board drawn again offscreen with everything where the ticks
+    //put it, over the smoothed board, with a line down the middle and each half labelled
+    fn draw_raw_half(&mut self, ctx: &mut Context) -> GameResult {
+        let blend = self.frame_blend(ctx);
+        self.interpolation.set_blend(1.0);
+        let canvas = graphics::Canvas::with_window_size(ctx)?;
+        graphics::set_canvas(ctx, Some(&canvas));
+        graphics::clear(ctx, Color::BLACK);
+        let drawn = with_view(ctx, self.board_view(), |ctx| self.draw_board(ctx));
+        graphics::set_canvas(ctx, None);
+        self.interpolation.set_blend(blend);
+        drawn?;
+        graphics::draw(ctx, &canvas, DrawParam::default().src(graphics::Rect::new(0.0, 0.0, 0.5, 1.0)))?;
+
+        let (w, h) = graphics::drawable_size(ctx);
+        let divider = graphics::Mesh::new_line(
+            ctx,
+            &[ggez::mint::Point2 { x: w / 2.0, y: HUD_HEIGHT }, ggez::mint::Point2 { x: w / 2.0, y: h }],
+            2.0,
+            Color::YELLOW,
+        )?;
+        graphics::draw(ctx, &divider, DrawParam::default())?;
+        let raw = graphics::Text::new("Raw ticks");
+        let smooth = graphics::Text::new("Smoothed");
+        let smooth_w = smooth.dimensions(ctx).w;
+        let y = HUD_HEIGHT + 4.0;
+        graphics::draw(ctx, &raw, DrawParam::default().dest(ggez::mint::Point2 { x: 4.0, y }).color(Color::YELLOW))?;
+        graphics::draw(
+            ctx,
+            &smooth,
+            DrawParam::default().dest(ggez::mint::Point2 { x: w - smooth_w - 4.0, y }).color(Color::YELLOW),
+        )
+    }
+
+    //everything in a frame, before it's presented
+    fn draw_frame(&mut self, ctx: &mut Context) -> GameResult {
+        self.ensure_wall_canvas(ctx)?;
+        let blend = self.frame_blend(ctx);
+        self.interpolation.set_blend(blend);
+        if self.circle_sprite.is_none() {
+            self.circle_sprite = Some(sprites::circle(ctx)?);
+        }
+
+        //the memento is rendered offscreen before the frame, so it shows the board without menus
+        if self.pending_memento {
+            self.pending_memento = false;
+            match memento::save(ctx, self.score, |ctx| self.draw_board(ctx)) {
+                Ok(path) => {
+                    println!("Saved game-over memento to {}", path.display());
+                    self.effects.toast("Memento saved to gallery");
+                }
+                Err(e) => eprintln!("Could not save game-over memento: {}", e),
+            }
+        }
+
+        //photo mode replaces the whole frame, and so does a race
+        if self.photo.is_some() {
+            return self.draw_photo(ctx);
+        }
+        if self.race.is_some() {
+            return self.draw_race(ctx);
+        }
+
+        graphics::clear(ctx, Color::BLACK);
+        //the board is drawn in maze coordinates, moved down below the HUD band, and at a lower
+        //resolution while the GPU can't keep up
+        self.render_scale.begin(ctx)?;
+        let drawn = with_view(ctx, self.board_view(), |ctx| {
+            self.draw_board(ctx)
+                .and_then(|_| match &self.circle_sprite {
+                    Some(circle) => self.effects.draw_world(ctx, circle),
+                    None => Ok(()),
+                })
+                .and_then(|_| match self.heatmap_layer {
+                    Some(layer) => self.heatmap.draw(ctx, layer, self.heatmap_color(layer)),
+                    None => Ok(()),
+                })
+                .and_then(|_| match self.trace_cursor {
+                    Some(cursor) if self.paused() => self.ghost_trace.draw_board(ctx, cursor),
+                    _ => Ok(()),
+                })
+                .and_then(|_| {
+                    if self.settings.hud.edge_glow && !self.game_over() {
+                        self.draw_edge_glow(ctx)
+                    } else {
+                        Ok(())
+                    }
+                })
+        });
+        let finished = self.render_scale.finish(ctx);
+        drawn?;
+        finished?;
+        if self.compare_motion {
+            self.draw_raw_half(ctx)?;
+        }
+
+        //score, lives and status, redrawn onto the HUD canvas only when they change
+        let role = match (&self.netplay, &self.spectating) {
+            (Some(netplay), _) => {
+                let player = if netplay.plays_pacman() { "You: Pac-Man" } else { "You: red ghost" };
+                match netplay.spectator_count() {
+                    Some(count) => Some(format!("{}\nSpectators: {}", player, count)),
+                    None => Some(player.to_string()),
+                }
+            }
+            (None, Some(_)) => Some("Spectating".to_string()),
+            (None, None) if self.tutorial.is_some() => Some("Tutorial".to_string()),
+            (None, None) => self.practice.map(|_| format!("Practice, level {}", self.level)),
+        };
+        let values = HudValues {
+            score: self.score,
+            lives: self.lives,
+            threads: self.jobs.threads(),
+            role,
+            level: self.level,
+            widgets: self.hud_widgets(),
+            held_item: self.held_item,
+            fruit: self.fruit_eaten.iter().rev().take(FRUIT_HISTORY).rev().copied().collect(),
+        };
+        self.hud.draw(ctx, values)?;
+
+        //draw the running chat vote
+        if let Some(twitch) = &self.twitch {
+            let status = twitch.status();
+            let vote_line = if status.connected {
+                let tallies: Vec<String> = status
+                    .tallies
+                    .iter()
+                    .map(|(command, count)| format!("{} {}", command, count))
+                    .collect();
+                format!("Chat vote {}s: {}", status.seconds_left, tallies.join("  "))
+            } else {
+                "Chat vote: connecting...".to_string()
+            };
+            let vote_text = graphics::Text::new(vote_line);
+            graphics::draw(
+                ctx,
+                &vote_text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 { x: 10.0, y: 70.0 })
+                    .color(Color::MAGENTA),
+            )?;
+        }
+
+        let banner = match self.round {
+            Round::LifeLost { ticks_left } if ticks_left <= READY_TICKS => Some(("READY!".to_string(), Color::YELLOW)),
+            Round::LevelComplete { .. } => Some((format!("LEVEL {} CLEARED!", self.level), Color::WHITE)),
+            _ => None,
+        };
+        if let Some((banner, color)) = banner {
+            let banner_text = graphics::Text::new(banner);
+            let text_dims = banner_text.dimensions(ctx);
+            let (w, h) = graphics::drawable_size(ctx);
+            graphics::draw(
+                ctx,
+                &banner_text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 {
+                        x: (w - text_dims.w * 2.0) / 2.0,
+                        y: (h - text_dims.h * 2.0) / 2.0,
+                    })
+                    .color(color)
+                    .scale([2.0, 2.0]),
+            )?;
+        }
+
+        //draw game over text if applicable
+        if self.game_over() {
+            let game_over_text = graphics::Text::new("GAME OVER!");
+            let text_dims = game_over_text.dimensions(ctx);
+            let (w, h) = graphics::drawable_size(ctx);
+            graphics::draw(
+                ctx,
+                &game_over_text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 {
+                        x: (w - text_dims.w) / 2.0,
+                        y: (h - text_dims.h) / 2.0,
+                    })
+                    .color(Color::RED)
+                    .scale([2.0, 2.0]),
+            )?;
+        }
+
+        if let Some(layer) = self.heatmap_layer {
+            let name = match layer {
+                HeatmapLayer::Pacman => "Pac-Man".to_string(),
+                HeatmapLayer::Ghosts => "all ghosts".to_string(),
+                HeatmapLayer::Ghost(ghost) => format!("ghost {}", ghost + 1),
+            };
+            let legend = graphics::Text::new(format!("Heatmap: {}   Tab: next   F4: close", name));
+            let (_, h) = graphics::drawable_size(ctx);
+            let legend_h = legend.dimensions(ctx).h;
+            graphics::draw(
+                ctx,
+                &legend,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 { x: 10.0, y: h - legend_h - 10.0 })
+                    .color(self.heatmap_color(layer)),
+            )?;
+        }
+
+        //the screen's own menu: the title screen over the idle game, the pause menu with the other
+        //keys under it or the scrubber's timeline in its place, or the game-over box unless the
+        //heatmap is being looked at instead. The settings screen is drawn over the top of the rest
+        match self.screen.beneath_settings() {
+            GameScreen::Menu(menu) => menu.draw(ctx, self.update_banner.as_deref())?,
+            GameScreen::Paused(menu) => match self.trace_cursor {
+                Some(cursor) => self.ghost_trace.draw_timeline(ctx, cursor)?,
+                None => {
+                    let hint = if self.dev_mode {
+                        "1-3: save to slot   C: photo mode   T: ghost trace   M: main menu"
+                    } else {
+                        "1-3: save to slot   C: photo mode   M: main menu"
+                    };
+                    menu.draw(ctx, hint, self.pause_message.as_deref())?;
+                }
+            },
+            GameScreen::GameOver(menu) if self.heatmap_layer.is_none() => menu.draw(ctx, self.score, &self.stats)?,
+            GameScreen::History(screen) => screen.draw(ctx)?,
+            GameScreen::Lobby(lobby) => lobby.draw(ctx)?,
+            GameScreen::Practice(screen) => screen.draw(ctx)?,
+            GameScreen::GameOver(_) | GameScreen::Playing | GameScreen::Settings(..) => {}
+        }
+
+        if let Some(intro) = &self.intro {
+            intro.draw(ctx)?;
+        }
+
+        //the tutorial's prompt, only while the game underneath is being played
+        if let Some(tutorial) = &self.tutorial {
+            if let GameScreen::Playing = self.screen {
+                tutorial.draw(ctx, &self.settings.bindings, HUD_HEIGHT)?;
+            }
+        }
+
+        if let Some(menu) = &self.cheat_menu {
+            menu.draw(ctx, &self.cheats)?;
+        }
+
+        if self.console.open {
+            self.console.draw(ctx)?;
+        }
+
+        //draw settings screen on top of everything
+        if let GameScreen::Settings(screen, _) = &self.screen {
+            screen.draw(ctx, &self.settings)?;
+        }
+
+        if let Some(dialog) = &self.crash_dialog {
+            dialog.draw(ctx)?;
+        }
+
+        //short notices like the memento being saved
+        self.effects.draw_toasts(ctx)?;
+
+        if self.profiler_overlay {
+            let summary = graphics::Text::new(format!("{}\n{}", profiling::summary_text(), self.effects.stats_text()));
+            let dims = summary.dimensions(ctx);
+            let background = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(5.0, 5.0, dims.w + 10.0, dims.h + 10.0),
+                Color::new(0.0, 0.0, 0.0, 0.8),
+            )?;
+            graphics::draw(ctx, &background, DrawParam::default())?;
+            graphics::draw(ctx, &summary, DrawParam::default().dest(ggez::mint::Point2 { x: 10.0, y: 10.0 }))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl EventHandler<ggez::GameError> for MainState {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        profile_scope!("update");
+        //the session log can be switched on and off from the settings screen
+        if self.telemetry.enabled() != self.settings.telemetry {
+            self.telemetry.set_enabled(self.settings.telemetry);
+        }
+        self.telemetry.record_frame(timer::delta(ctx).as_secs_f32());
+        //so are announcements
+        if self.announcer.is_some() != self.settings.announcements {
+            self.announcer = if self.settings.announcements { Some(Announcer::new()) } else { None };
+        }
+        self.telemetry.set_state(self.screen_name());
+
+        //the lobby talks to the other player while it's open, settings or not. A match that
+        //starts with the settings screen open starts under it
+        if let GameScreen::Lobby(lobby) = self.screen.beneath_settings_mut() {
+            if let LobbyAction::Start(session) = lobby.update() {
+                let settings = match std::mem::replace(&mut self.screen, GameScreen::Playing) {
+                    GameScreen::Settings(screen, _) => Some(screen),
+                    _ => None,
+                };
+                self.start_netplay(*session);
+                if let Some(screen) = settings {
+                    self.open_settings(screen);
+                }
+            }
+        }
+
+        self.sync_window_title(ctx);
+
+        if let Some(version) = self.update_check.as_ref().and_then(|check| check.poll()) {
+            self.update_banner = Some(format!("Version {} is available on GitHub", version));
+            self.update_check = None;
+        }
+
+        //the simulation runs at a fixed rate so replays play back identically
+        //the slow-motion assist and close calls run the same ticks, just fewer of them a second
+        while timer::check_update_time(ctx, self.tick_rate()) {
+            let ghosts = self.ghosts.iter().map(|ghost| (ghost.x, ghost.y));
+            self.interpolation.remember((self.pacman_x, self.pacman_y), ghosts);
+            //the title screen's attract mode carries on while nothing is open over it, and the game
+            //is frozen while paused or while a menu is open
+            let covered = self.crash_dialog.is_some() || self.console.open || self.cheat_menu.is_some();
+            match &mut self.screen {
+                GameScreen::Menu(menu) => {
+                    if !covered {
+                        let start_demo = matches!(menu.update(), AttractAction::StartDemo);
+                        let demo = menu.showing_demo();
+                        if start_demo {
+                            self.start_demo();
+                        }
+                        if demo {
+                            self.demo_tick();
+                        }
+                    }
+                    continue;
+                }
+                GameScreen::Paused(_)
+                | GameScreen::Settings(..)
+                | GameScreen::History(_)
+                | GameScreen::Lobby(_)
+                | GameScreen::Practice(_) => continue,
+                GameScreen::Playing | GameScreen::GameOver(_) => {}
+            }
+            if covered {
+                continue;
+            }
+            //the intro holds the game back until it ends or is skipped
+            if let Some(intro) = &mut self.intro {
+                intro.advance();
+                if intro.finished() {
+                    self.close_intro();
+                }
+                continue;
+            }
+            if self.spectating.is_some() {
+                if let Err(e) = self.update_spectating() {
+                    eprintln!("Stopped watching: {}", e);
+                    self.spectating = None;
+                    self.pause();
+                    self.pause_message = Some(e);
+                }
+                continue;
+            }
+
+            //versus games run their own ticks, see update_netplay
+            if self.netplay.is_some() {
+                match self.update_netplay() {
+                    Ok(true) => {}
+                    Ok(false) => self.finish_game(),
+                    Err(e) => {
+                        eprintln!("Versus game ended: {}", e);
+                        self.netplay = None;
+                        self.pause();
+                        self.pause_message = Some(e);
+                    }
+                }
+                continue;
+            }
+            if self.race.is_some() {
+                self.race_tick();
+                continue;
+            }
+            if self.game_over() {
+                continue;
+            }
+
+            //remote commands steer pacman just like key presses
+            if let Some(remote) = &self.remote {
+                if self.playback.is_none() {
+                    if let Some(&direction) = remote.commands().last() {
+                        self.requested_direction = direction;
+                    }
+                }
+            }
+
+            self.step();
+
+            if let Some(remote) = &self.remote {
+                remote.broadcast(&self.remote_state());
+            }
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        {
+            profile_scope!("draw");
+            //with vsync a cap above the display's refresh can't be met at any scale, so only frames
+            //slower than the tick rate count against it there
+            let mut budget = self.frame_budget();
+            if self.settings.vsync {
+                budget = budget.max(Duration::from_secs_f64(1.0 / TICKS_PER_SECOND as f64));
+            }
+            self.render_scale.frame(budget, self.settings.render_scale_min, self.settings.render_scale_max);
+            self.draw_frame(ctx)?;
+        }
+        graphics::present(ctx)?;
+        profiling::end_frame();
+        self.pace_frame();
+        Ok(())
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut Context,
+        button: event::MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if button != event::MouseButton::Left {
+            return;
+        }
+        let over_pause = self.console.open || self.cheat_menu.is_some();
+        match &mut self.screen {
+            //a versus game over only counts once the other player's inputs confirm it
+            GameScreen::GameOver(menu) if self.netplay.is_none() => {
+                let action = menu.click(ctx, x, y);
+                self.game_over_action(ctx, action);
+            }
+            GameScreen::Paused(menu)
+                if self.race.is_none() && self.photo.is_none() && self.trace_cursor.is_none() && !over_pause =>
+            {
+                let action = menu.click(ctx, x, y);
+                self.pause_action(action);
+            }
+            _ => {}
+        }
+    }
+    fn focus_event(&mut self, ctx: &mut Context, gained: bool) {
+        self.focused = gained;
+        //alt-tabbing away shouldn't cost a life. A versus game can't stop for one player, so it
+        //only drops the frame rate
+        let racing = self.race.as_ref().is_some_and(|race| race.outcome().is_none());
+        let playing = matches!(self.screen, GameScreen::Playing) && (!self.game_over() || racing);
+        if !gained && playing && !self.networked() {
+            self.pause();
+            self.pause_message = Some("Paused while the window was in the background".to_string());
+            //the player knows, they just switched away. Only the title says so
+            let title = self.title_for_state();
+            graphics::set_window_title(ctx, &title);
+            self.window_title = title;
+        }
+        //key releases don't arrive while unfocused
+        if !gained {
+            self.held_directions.clear();
+            if let Some(rival) = &mut self.rival {
+                rival.held_directions.clear();
+            }
+        }
+    }
+
+    fn quit_event(&mut self, _ctx: &mut Context) -> bool {
+        self.telemetry.set_enabled(false);
+        false
+    }
+
+    fn key_down_event(&mut self, ctx: &mut Context, keycode: KeyCode, _keymod: KeyMods, _repeat: bool) {
+        //the crash dialog has to be answered before anything else
+        if let Some(dialog) = &mut self.crash_dialog {
+            match dialog.handle_key(keycode) {
+                CrashAction::Resume(snapshot) => {
+                    self.restore(*snapshot);
+                    self.crash_dialog = None;
+                }
+                CrashAction::Close => self.crash_dialog = None,
+                CrashAction::None => {}
+            }
+            return;
+        }
+
+        //the settings screen captures all input while open
+        if let GameScreen::Settings(screen, _) = &mut self.screen {
+            if !screen.handle_key(ctx, keycode, &mut self.settings) {
+                if let GameScreen::Settings(_, beneath) = std::mem::replace(&mut self.screen, GameScreen::Playing) {
+                    self.screen = *beneath;
+                }
+                self.settings.save();
+            }
+            return;
+        }
+        //the profiler overlay only exists in builds with the profiling feature
+        if keycode == KeyCode::F3 && cfg!(feature = "profiling") {
+            self.profiler_overlay = !self.profiler_overlay;
+            return;
+        }
+        //the heatmap is there after a game, or whenever in dev mode. Over a finished game it
+        //stands in for the game-over box, so it takes the keys until closed
+        if keycode == KeyCode::F4 && (self.game_over() || self.dev_mode) && self.race.is_none() {
+            self.heatmap_layer = match self.heatmap_layer {
+                Some(_) => None,
+                None => Some(HeatmapLayer::Pacman),
+            };
+            return;
+        }
+        //dev mode's split view, raw ticks on the left and smoothed motion on the right
+        if keycode == KeyCode::F6 && self.dev_mode {
+            self.compare_motion = !self.compare_motion;
+            return;
+        }
+        if let Some(layer) = self.heatmap_layer {
+            if keycode == KeyCode::Tab {
+                self.heatmap_layer = Some(self.heatmap.next_layer(layer));
+                return;
+            }
+            if self.game_over() {
+                if keycode == KeyCode::Escape {
+                    self.heatmap_layer = None;
+                }
+                return;
+            }
+        }
+        if keycode == KeyCode::F1 {
+            self.held_directions.clear();
+            self.open_settings(SettingsScreen::new());
+            return;
+        }
+
+        if self.console.open {
+            match self.console.handle_key(keycode) {
+                ConsoleAction::Close => self.console.open = false,
+                ConsoleAction::Run(line) => self.run_console_line(&line),
+                ConsoleAction::None => {}
+            }
+            return;
+        }
+        //the lobby takes typed text too, and networked games can't be changed from one side
+        if keycode == KeyCode::Grave && !matches!(self.screen, GameScreen::Lobby(_)) && !self.networked() {
+            self.held_directions.clear();
+            self.console.open = true;
+            return;
+        }
+
+        if let Some(menu) = &mut self.cheat_menu {
+            match menu.handle_key(keycode) {
+                CheatAction::Close => self.cheat_menu = None,
+                CheatAction::Run(line) => {
+                    let result = self.run_command(line);
+                    if let Some(menu) = &mut self.cheat_menu {
+                        menu.set_result(result);
+                    }
+                }
+                CheatAction::None => {}
+            }
+            return;
+        }
+        if keycode == KeyCode::F2 && self.dev_mode && !matches!(self.screen, GameScreen::Lobby(_)) && !self.networked() {
+            self.held_directions.clear();
+            self.cheat_menu = Some(CheatMenu::new());
+            return;
+        }
+
+        //any other key skips the intro
+        if self.intro.is_some() {
+            self.close_intro();
+            return;
+        }
+
+        match &mut self.screen {
+            GameScreen::History(screen) => {
+                if !screen.handle_key(keycode) {
+                    self.screen = GameScreen::Menu(MainMenu::new());
+                }
+                return;
+            }
+            GameScreen::Lobby(lobby) => {
+                match lobby.handle_key(keycode) {
+                    LobbyAction::Close => self.screen = GameScreen::Menu(MainMenu::new()),
+                    LobbyAction::Start(session) => self.start_netplay(*session),
+                    LobbyAction::None => {}
+                }
+                return;
+            }
+            GameScreen::Practice(screen) => {
+                match screen.handle_key(keycode) {
+                    PracticeAction::Close => self.screen = GameScreen::Menu(MainMenu::new()),
+                    PracticeAction::Start(options) => self.start_practice(options),
+                    PracticeAction::None => {}
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        //photo mode sits over the pause menu, and a race has keys of its own for both players
+        if let Some(photo) = &mut self.photo {
+            if let PhotoAction::Close = photo.handle_key(keycode) {
+                self.photo = None;
+            }
+            return;
+        }
+
+        if self.race.is_some() {
+            self.race_key(ctx, keycode);
+            return;
+        }
+
+        match &mut self.screen {
+            //main menu navigation
+            GameScreen::Menu(menu) => {
+                match menu.handle_key(keycode) {
+                    MenuAction::NewGame => {
+                        //a share code's rules only last for its own games, and everything else
+                        //from the menu is back on the maze's
+                        self.set_ruleset(self.map.rules.clone());
+                        self.reset_game();
+                        self.screen = GameScreen::Playing;
+                        self.intro = Some(Intro::new(false));
+                    }
+                    MenuAction::Load(slot) => match save::load_slot(slot) {
+                        Some(snapshot) => {
+                            self.set_ruleset(self.map.rules.clone());
+                            self.restore(snapshot);
+                        }
+                        None => menu.set_message(format!("Slot {} is empty", slot + 1)),
+                    },
+                    MenuAction::Tutorial => {
+                        self.set_ruleset(self.map.rules.clone());
+                        self.start_tutorial();
+                    }
+                    MenuAction::Intro => {
+                        self.intro = Some(Intro::new(true));
+                        self.screen = GameScreen::Playing;
+                    }
+                    MenuAction::Practice => {
+                        self.set_ruleset(self.map.rules.clone());
+                        self.screen = GameScreen::Practice(PracticeScreen::new(self.spawns.roster().len()));
+                    }
+                    MenuAction::Versus => {
+                        self.set_ruleset(self.map.rules.clone());
+                        self.screen = GameScreen::Lobby(LobbyScreen::new(self.map.name.clone(), self.map_hash.clone()));
+                    }
+                    MenuAction::Race => {
+                        self.set_ruleset(self.map.rules.clone());
+                        if let Err(e) = self.start_race(ctx) {
+                            eprintln!("Could not start a race: {}", e);
+                        }
+                    }
+                    MenuAction::Challenge(challenge) => {
+                        if challenge.map_id == share::map_id(&self.map) {
+                            self.start_challenge(challenge);
+                        } else {
+                            menu.set_message("That code is for a different maze".to_string());
+                        }
+                    }
+                    MenuAction::History => self.screen = GameScreen::History(HistoryScreen::new()),
+                    MenuAction::Settings => self.open_settings(SettingsScreen::new()),
+                    MenuAction::Exit => {
+                        self.telemetry.set_enabled(false);
+                        event::quit(ctx);
+                    }
+                    MenuAction::None => {}
+                }
+                return;
+            }
+            //a versus game over only counts once the other player's inputs confirm it
+            GameScreen::GameOver(menu) if self.netplay.is_none() => {
+                let action = menu.handle_key(keycode);
+                self.game_over_action(ctx, action);
+                return;
+            }
+            //pause menu: its buttons, then keys for the save slots, photo mode and the title
+            //screen. In dev mode T opens the ghost trace's scrubber, which takes the keys until
+            //it's closed
+            GameScreen::Paused(menu) => {
+                if let Some(cursor) = self.trace_cursor {
+                    self.trace_cursor = self.ghost_trace.scrub(cursor, keycode);
+                    return;
+                }
+                if keycode == KeyCode::T && self.dev_mode && !self.ghost_trace.is_empty() {
+                    self.trace_cursor = Some(self.ghost_trace.latest());
+                    return;
+                }
+                let slot = match keycode {
+                    KeyCode::Key1 => Some(0),
+                    KeyCode::Key2 => Some(1),
+                    KeyCode::Key3 => Some(2),
+                    _ => None,
+                };
+                let action = match keycode {
+                    KeyCode::C => {
+                        self.photo = Some(PhotoMode::new(maze_size(&self.map)));
+                        PauseAction::None
+                    }
+                    KeyCode::M => PauseAction::Quit,
+                    _ if slot.is_some() => PauseAction::None,
+                    _ => menu.handle_key(keycode),
+                };
+                if let Some(slot) = slot {
+                    self.pause_message = Some(match save::save_slot(slot, &self.snapshot()) {
+                        Ok(()) => format!("Saved to slot {}", slot + 1),
+                        Err(e) => format!("Save failed: {}", e),
+                    });
+                }
+                self.pause_action(action);
+                return;
+            }
+            //the full-window menus have had the key already
+            GameScreen::Settings(..) | GameScreen::History(_) | GameScreen::Lobby(_) | GameScreen::Practice(_) => return,
+            GameScreen::Playing | GameScreen::GameOver(_) => {}
+        }
+
+        if keycode == KeyCode::Back && self.tutorial.is_some() {
+            self.tutorial = None;
+            self.settings.tutorial_done = true;
+            self.settings.save();
+            self.screen = GameScreen::Menu(MainMenu::new());
+            return;
+        }
+        //P pauses too, unless it steers
+        let pause_key = keycode == KeyCode::Escape
+            || (keycode == KeyCode::P && self.settings.bindings.direction_for(keycode).is_none());
+        if pause_key && !self.game_over() {
+            self.pause();
+            return;
+        }
+
+        //spectators only watch
+        if self.spectating.is_some() {
+            return;
+        }
+        //in a versus game keys only pick the direction sent to the other player
+        if self.netplay.is_some() {
+            if let Some(dir) = self.settings.bindings.direction_for(keycode) {
+                self.local_direction = dir;
+            }
+            return;
+        }
+
+        //a replay drives pacman on its own
+        if !self.game_over() && self.playback.is_none() {
+            //Space sets off the held power-up, unless it's bound to a direction
+            if keycode == KeyCode::Space && self.settings.bindings.direction_for(keycode).is_none() {
+                self.use_item_requested = true;
+                return;
+            }
+            //remember held direction keys, most recently pressed last
+            if let Some(dir) = self.settings.bindings.direction_for(keycode) {
+                self.press_direction(dir);
+            }
+        }
+    }
+
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) {
+        if self.console.open {
+            self.console.text_input(character);
+        } else if let GameScreen::Lobby(lobby) = &mut self.screen {
+            lobby.text_input(character);
+        } else if let GameScreen::Menu(menu) = &mut self.screen {
+            menu.text_input(character);
+        }
+    }
+
+    fn key_up_event(&mut self, _ctx: &mut Context, keycode: KeyCode, _keymod: KeyMods) {
+        //in a race the settings' keys belong to the right-hand player
+        if let Some(rival) = &mut self.rival {
+            if let Some(dir) = race::LEFT_KEYS.direction_for(keycode) {
+                self.held_directions.retain(|&held| held != dir);
+            } else if let Some(dir) = self.settings.bindings.direction_for(keycode) {
+                rival.held_directions.retain(|&held| held != dir);
+            }
+            return;
+        }
+        if let Some(dir) = self.settings.bindings.direction_for(keycode) {
+            self.held_directions.retain(|&held| held != dir);
+        }
+    }
+}
+
+//the maze's walls, joined up through its tunnels, or at every edge if the rules wrap it. The
+//ghost house's doors are walls too unless they're open, as they are for the ghosts
+fn maze_walls(map: &Map, layout: &Layout, ruleset: &Ruleset, doors_open: bool) -> WallGrid {
+    let mut blocked = layout.void_cells();
+    if !doors_open {
+        blocked.extend(layout.door_cells());
+    }
+    let mut walls = WallGrid::with_void(&map.walls(), &blocked);
+    walls.set_tunnels(Tunnels::new(map.width, map.height, &map.tunnel_cells()));
+    if ruleset.wrap {
+        walls.set_wrap(map.width, map.height);
+    }
+    walls
+}
+
+//a door across its cell, lying the way the walls either side of it run
+fn door_bar(layout: &Layout, col: usize, row: usize) -> graphics::Rect {
+    let (x, y) = (col as f32 * CELL_SIZE, row as f32 * CELL_SIZE);
+    let (col, row) = (col as i64, row as i64);
+    let inset = (CELL_SIZE - DOOR_THICKNESS) / 2.0;
+    if layout.is_open(col, row - 1) || layout.is_open(col, row + 1) {
+        graphics::Rect::new(x, y + inset, CELL_SIZE, DOOR_THICKNESS)
+    } else {
+        graphics::Rect::new(x + inset, y, DOOR_THICKNESS, CELL_SIZE)
+    }
+}
+
+//the maze in pixels
+fn maze_size(map: &Map) -> (f32, f32) {
+    (CELL_SIZE * map.width as f32, CELL_SIZE * map.height as f32)
+}
+
+//the maze with the HUD band on top
+fn window_size(map: &Map) -> (f32, f32) {
+    let (width, height) = maze_size(map);
+    (width, height + HUD_HEIGHT)
+}
+
+//`matrix` moved by (dx, dy) before it applies
+fn translated(matrix: ggez::mint::ColumnMatrix4<f32>, dx: f32, dy: f32) -> ggez::mint::ColumnMatrix4<f32> {
+    let column = |x: f32, y: f32, w: f32| x * dx + y * dy + w;
+    ggez::mint::ColumnMatrix4 {
+        w: ggez::mint::Vector4 {
+            x: column(matrix.x.x, matrix.y.x, matrix.w.x),
+            y: column(matrix.x.y, matrix.y.y, matrix.w.y),
+            z: column(matrix.x.z, matrix.y.z, matrix.w.z),
+            w: column(matrix.x.w, matrix.y.w, matrix.w.w),
+        },
+        ..matrix
+    }
+}
+
+//the cell under the middle of a ghost whose box starts at (x, y), as (column, row)
+fn ghost_cell(x: Fixed, y: Fixed) -> (i64, i64) {
+    ((x + GHOST.half()).div_floor(CELL) as i64, (y + GHOST.half()).div_floor(CELL) as i64)
+}
+
+//from maze coordinates to the window's
+fn board_transform() -> ggez::mint::ColumnMatrix4<f32> {
+    DrawParam::default().dest(ggez::mint::Point2 { x: 0.0, y: HUD_HEIGHT }).trans.to_bare_matrix()
+}
+
+//runs `draw` with `view` applied on top of whatever view is already set. ggez 0.6 has no
+//transform stack, so this moves the screen coordinates under the drawing instead, which covers
+//views that only scale and move, as all of ours do
+fn with_view<T, F>(ctx: &mut Context, view: ggez::mint::ColumnMatrix4<f32>, draw: F) -> GameResult<T>
+where
+    F: FnOnce(&mut Context) -> GameResult<T>,
+{
+    let screen = graphics::screen_coordinates(ctx);
+    let (scale_x, scale_y) = (view.x.x, view.y.y);
+    graphics::set_screen_coordinates(
+        ctx,
+        graphics::Rect::new(
+            (screen.x - view.w.x) / scale_x,
+            (screen.y - view.w.y) / scale_y,
+            screen.w / scale_x,
+            screen.h / scale_y,
+        ),
+    )?;
+    let drawn = draw(ctx);
+    graphics::set_screen_coordinates(ctx, screen)?;
+    drawn
+}
+
+//the console's built-in commands, mods add theirs with register_command
+fn console_commands() -> CommandRegistry<MainState> {
+    let mut commands = CommandRegistry::default();
+    commands.register("set", "lives|score <n>", |state: &mut MainState, args| {
+        let value: u32 = console_arg(args, 1)?;
+        match args.first().copied() {
+            Some("lives") => state.lives = value as i32,
+            Some("score") => state.score = value,
+            _ => return Err("Usage: set lives|score <n>".to_string()),
+        }
+        Ok(format!("{} set to {}", args[0], value))
+    });
+    commands.register("tp", "<col> <row>, moves pacman", |state: &mut MainState, args| {
+        let col: usize = console_arg(args, 0)?;
+        let row: usize = console_arg(args, 1)?;
+        if !state.is_open_cell(col, row) {
+            return Err(format!("{} {} is not an open cell", col, row));
+        }
+        state.pacman_x = CELL * col as i32 + (CELL - PACMAN).half();
+        state.pacman_y = CELL * row as i32 + (CELL - PACMAN).half();
+        state.intersection_cell = None;
+        Ok(format!("Moved to {} {}", col, row))
+    });
+    commands.register("seed", "<n>, restarts with that seed", |state: &mut MainState, args| {
+        let seed: u64 = console_arg(args, 0)?;
+        state.reset_game();
+        state.seed = seed;
+        state.rng = Pcg32::seed_from_u64(seed);
+        state.stats = GameStats::new(seed);
+        state.screen = GameScreen::Playing;
+        Ok(format!("Restarted with seed {}", seed))
+    });
+    commands.register("spawn", "dot|pellet|<entity> [col row]", |state: &mut MainState, args| {
+        let kind = args.first().copied().ok_or_else(|| "Usage: spawn <what> [col row]".to_string())?;
+        let (col, row) = if args.len() >= 3 {
+            (console_arg(args, 1)?, console_arg(args, 2)?)
+        } else {
+            state.random_free_cell().ok_or_else(|| "There's no free cell left".to_string())?
+        };
+        let center = state
+            .free_cell_center(col, row)
+            .ok_or_else(|| format!("{} {} is not a free cell", col, row))?;
+        match kind {
+            "dot" => state.dots.push(center),
+            "pellet" => state.power_pellets.add(center),
+            name => match state.entity_registry.spawn(name, col, row) {
+                Some(entity) => state.entities.push(entity),
+                None => return Err(format!("Nothing called '{}' to spawn", name)),
+            },
+        }
+        Ok(format!("Spawned {} at {} {}", kind, col, row))
+    });
+    commands.register("god", "[on|off], nothing costs a life", |state: &mut MainState, args| {
+        state.cheats.invincible = console_switch(args, state.cheats.invincible)?;
+        Ok(format!("Invincibility {}", if state.cheats.invincible { "on" } else { "off" }))
+    });
+    commands.register("ai", "[on|off], off stops the ghosts", |state: &mut MainState, args| {
+        state.cheats.ghosts_frozen = !console_switch(args, !state.cheats.ghosts_frozen)?;
+        Ok(format!("Ghost AI {}", if state.cheats.ghosts_frozen { "off" } else { "on" }))
+    });
+    commands.register("clear", "eats every dot but one", |state: &mut MainState, _| {
+        //the last dot goes under pacman, so the next tick clears the level the usual way
+        state.dots.clear();
+        state.dots.push(ggez::mint::Point2 {
+            x: (state.pacman_x + PACMAN.half()).to_f32(),
+            y: (state.pacman_y + PACMAN.half()).to_f32(),
+        });
+        Ok("Level cleared".to_string())
+    });
+    commands.register("power", "as if a power pellet was just eaten", |state: &mut MainState, _| {
+        state.power_pellet_active = true;
+        state.power_pellet_timer = state.ruleset.pellet_seconds;
+        state.ghost_chain = 0;
+        state.pellet_ghosts = 0;
+        for ghost in state.ghosts.iter_mut().filter(|ghost| ghost.in_play()) {
+            ghost.frighten(state.ruleset.pellet_seconds, false);
+        }
+        Ok("Power pellet refreshed".to_string())
+    });
+    commands.register("reload", "map, puts the dots and pellets back", |state: &mut MainState, args| {
+        match args.first().copied() {
+            Some("map") => {
+                state.reload_map();
+                Ok("Map reloaded".to_string())
+            }
+            _ => Err("Usage: reload map".to_string()),
+        }
+    });
+    commands
+}
+
+//`on` or `off`, or the opposite of `current` without an argument
+fn console_switch(args: &[&str], current: bool) -> Result<bool, String> {
+    match args.first().copied() {
+        None => Ok(!current),
+        Some("on") => Ok(true),
+        Some("off") => Ok(false),
+        Some(other) => Err(format!("Expected on or off, got '{}'", other)),
+    }
+}
+
+//the console argument at `index`, parsed
+fn console_arg<T: std::str::FromStr>(args: &[&str], index: usize) -> Result<T, String> {
+    let arg = args.get(index).ok_or_else(|| "Not enough arguments".to_string())?;
+    arg.parse().map_err(|_| format!("'{}' is not a valid number", arg))
+}
+
+//value following a command line flag, e.g. `--replay <file>`
+fn flag_value(args: &[String], flag: &str) -> GameResult<Option<String>> {
+    match args.iter().position(|arg| arg == flag) {
+        Some(i) => match args.get(i + 1) {
+            Some(value) => Ok(Some(value.clone())),
+            None => Err(ggez::GameError::CustomError(format!("{} needs a value", flag))),
+        },
+        None => Ok(None),
+    }
+}
+
+//call window setup and run event given context and state. Called from main with the command line
+pub fn run(args: Vec<String>) -> GameResult {
+    run_with_observers(args, Vec::new())
+}
+
+//`run` with observers told about every tick of whatever gets played
+pub fn run_with_observers(args: Vec<String>, observers: Vec<Box<dyn Observer>>) -> GameResult {
+    //`--convert-map <file>` prints a maze from another format as this game's maze text.
+    //`--map-format <native|berkeley|arcade>` overrides the guess from its glyphs
+    if let Some(path) = flag_value(&args, "--convert-map")? {
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| ggez::GameError::CustomError(format!("Could not read {}: {}", path, e)))?;
+        let format = match flag_value(&args, "--map-format")? {
+            Some(name) => map_import::Format::from_name(&name)
+                .ok_or_else(|| ggez::GameError::CustomError(format!("Unknown map format {}", name)))?,
+            None => map_import::Format::detect(&text),
+        };
+        let map = map_import::import(&text, format)
+            .map_err(|e| ggez::GameError::CustomError(format!("{}: {}", path, e)))?;
+        print!("{}", map);
+        return Ok(());
+    }
+
+    //`--simulate <games>` plays that many bot games on every core and prints the results
+    if let Some(games) = flag_value(&args, "--simulate")? {
+        let games = games
+            .parse()
+            .map_err(|_| ggez::GameError::CustomError(format!("--simulate needs a number of games, got {}", games)))?;
+        return simulate::run(games).map_err(ggez::GameError::CustomError);
+    }
+
+    paths::init();
+    let settings = Settings::load();
+    let fullscreen_type = if settings.fullscreen {
+        ggez::conf::FullscreenType::Desktop
+    } else {
+        ggez::conf::FullscreenType::Windowed
+    };
+
+    //`--map <file>` plays another maze. It's read and checked here, so a broken one stops the
+    //game before a window opens
+    let map = match flag_value(&args, "--map")? {
+        Some(path) => Map::load(Path::new(&path)).map_err(ggez::GameError::CustomError)?,
+        None => Map::classic(),
+    };
+    let map_hash = replay::map_hash(&map);
+    let window = window_size(&map);
+
+    //`--replay <file>` plays back a recorded game instead of showing the menu
+    let replay = match flag_value(&args, "--replay")? {
+        Some(path) => Some(Replay::load(path, &map_hash).map_err(ggez::GameError::CustomError)?),
+        None => None,
+    };
+    let stats_path = flag_value(&args, "--export-stats")?.map(PathBuf::from);
+
+    let mut state = MainState::new(settings, map)?;
+    state.stats_path = stats_path;
+    state.observers = observers;
+    //`--dev` unlocks the cheat menu in release builds
+    if args.iter().any(|arg| arg == "--dev") {
+        state.dev_mode = true;
+    }
+    state.telemetry.set_enabled(state.settings.telemetry);
+
+    //mods run in replays too, so a replay only plays back the same with the same mods
+    let mut mods = ModHost::load(&paths::mods_dir());
+    if !mods.is_empty() {
+        for action in mods.take_pending() {
+            state.apply_mod_action(action);
+        }
+        state.mods = Some(mods);
+    }
+    //loaded here rather than in new(), so headless runs keep the arcade speeds
+    state.speed_table = SpeedTable::load();
+    state.apply_speeds(1);
+
+    //`--remote <addr>` streams the game to TCP clients and accepts their commands
+    if let Some(addr) = flag_value(&args, "--remote")? {
+        let server = RemoteServer::start(&addr)
+            .map_err(|e| ggez::GameError::CustomError(format!("Could not listen on {}: {}", addr, e)))?;
+        println!("Remote control listening on {}", addr);
+        state.remote = Some(server);
+    }
+
+    //`--twitch <channel>` lets chat vote on effects
+    if let Some(channel) = flag_value(&args, "--twitch")? {
+        state.twitch = Some(TwitchChat::connect(&channel, state.event_bus.injector()));
+    }
+
+    //`--export-frames <dir|->` renders every tick of the replay to PNGs, or raw frames on stdout
+    if let Some(target) = flag_value(&args, "--export-frames")? {
+        let replay = replay.ok_or_else(|| ggez::GameError::CustomError("--export-frames needs --replay <file>".to_string()))?;
+        state.start_replay(replay);
+        let frames = frame_export::export(&mut state, &FrameSink::from_arg(&target))
+            .map_err(ggez::GameError::CustomError)?;
+        //stdout may be carrying the frames, so report on stderr
+        let frame = raster::render(&state);
+        eprintln!("Exported {} frames of {}x{} at {} fps", frames, frame.width, frame.height, TICKS_PER_SECOND);
+        return Ok(());
+    }
+
+    //`--headless` re-simulates the replay without opening a window
+    if args.iter().any(|arg| arg == "--headless") {
+        let replay = replay.ok_or_else(|| ggez::GameError::CustomError("--headless needs --replay <file>".to_string()))?;
+        state.start_replay(replay);
+        while !state.game_over() && !state.playback.as_ref().is_none_or(|player| player.finished()) {
+            state.step();
+        }
+        if !state.game_over() {
+            state.finish_game();
+        }
+        println!("Final score: {}", state.score);
+        return Ok(());
+    }
+    if let Some(replay) = replay {
+        state.start_replay(replay);
+    }
+
+    //`--host <addr>` waits for a second player, `--join <addr>` connects to one
+    let netplay = match (flag_value(&args, "--host")?, flag_value(&args, "--join")?) {
+        (Some(addr), _) => Some(Session::host(&addr, rand::random(), map_hash)),
+        (None, Some(addr)) => Some(Session::join(&addr, map_hash)),
+        (None, None) => None,
+    };
+    if let Some(netplay) = netplay {
+        let mut netplay = netplay.map_err(ggez::GameError::CustomError)?;
+        //`--spectators <addr>` on the host lets others watch with `--watch <addr>`
+        if let Some(addr) = flag_value(&args, "--spectators")? {
+            netplay.allow_spectators(&addr).map_err(ggez::GameError::CustomError)?;
+        }
+        state.start_netplay(netplay);
+    } else if let Some(addr) = flag_value(&args, "--watch")? {
+        state.start_spectating(Spectator::watch(&addr, &state.map_hash).map_err(ggez::GameError::CustomError)?);
+    }
+
+    //the first launch goes straight into the tutorial, unless something else was asked for
+    if !state.settings.tutorial_done && matches!(state.screen, GameScreen::Menu(_)) {
+        state.start_tutorial();
+    }
+
+    profiling::start();
+
+    //a panic leaves a crash file behind, which the next launch offers to resume from
+    crash::install_hook();
+    state.crash_reporting = true;
+    if let Some(report) = crash::pending_report() {
+        state.crash_dialog = Some(CrashDialog::new(report));
+    }
+
+    if state.settings.check_updates {
+        state.update_check = Some(UpdateCheck::start());
+    }
+
+    let cb = ContextBuilder::new("pacman", "Your Name")
+        .window_setup(ggez::conf::WindowSetup::default().title(WINDOW_TITLE).vsync(state.settings.vsync))
+        .window_mode(ggez::conf::WindowMode::default()
+            .dimensions(window.0, window.1)
+            .fullscreen_type(fullscreen_type)
+            .resizable(false));
+
+    let (ctx, event_loop) = cb.build()?;
+    event::run(ctx, event_loop, state)
+}
@@ -0,0 +1,466 @@
+//host/join screen for versus games: the host shows its address and a short code for it, the
+//guest types either in, the host picks the sides on the maze both were started with, and once
+//both players are ready a countdown starts the match on both machines
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, TcpListener, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::netplay::{Lobby, Session};
+
+pub const DEFAULT_PORT: u16 = 7878;
+const COUNTDOWN: f32 = 3.0;
+//Crockford's base 32, which leaves out letters that are easy to mistake for digits. Share codes
+//use it too
+pub const CODE_ALPHABET: &[u8] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const CHOICES: [&str; 3] = ["Host game", "Join game", "Back"];
+//how often the host's accept loop checks whether it was cancelled
+const ACCEPT_POLL: Duration = Duration::from_millis(100);
+
+//what the caller should do after a key press or update
+pub enum LobbyAction {
+    None,
+    Close,
+    Start(Box<Session>),
+}
+
+enum Stage {
+    Choose { selected: usize },
+    Hosting { address: Option<SocketAddr>, pending: Receiver<Result<Session, String>>, cancel: Arc<AtomicBool> },
+    Entering { text: String },
+    Joining { pending: Receiver<Result<Session, String>> },
+    Connected(Box<Room>),
+}
+
+//two connected players agreeing on a match
+struct Room {
+    session: Session,
+    host: bool,
+    host_plays_pacman: bool,
+    ready: bool,
+    other_ready: bool,
+    countdown: Option<Instant>,
+}
+
+impl Room {
+    //the host sends its choices again whenever they change, which un-readies both players
+    fn send_settings(&mut self) -> Result<(), String> {
+        self.ready = false;
+        self.other_ready = false;
+        self.session.send_lobby(Lobby::Settings { host_plays_pacman: self.host_plays_pacman })
+    }
+
+    fn maybe_start(&mut self) -> Result<(), String> {
+        if self.host && self.ready && self.other_ready && self.countdown.is_none() {
+            self.session.send_lobby(Lobby::Start)?;
+            self.countdown = Some(Instant::now());
+        }
+        Ok(())
+    }
+}
+
+pub struct LobbyScreen {
+    stage: Stage,
+    message: Option<String>,
+    //the maze this side plays, which the other player has to have too
+    map_name: String,
+    map_hash: String,
+}
+
+impl LobbyScreen {
+    pub fn new(map_name: String, map_hash: String) -> Self {
+        LobbyScreen {
+            stage: Stage::Choose { selected: 0 },
+            message: None,
+            map_name,
+            map_hash,
+        }
+    }
+
+    pub fn handle_key(&mut self, keycode: KeyCode) -> LobbyAction {
+        let result = match &mut self.stage {
+            Stage::Choose { selected } => match keycode {
+                KeyCode::Up => {
+                    *selected = (*selected + CHOICES.len() - 1) % CHOICES.len();
+                    Ok(())
+                }
+                KeyCode::Down => {
+                    *selected = (*selected + 1) % CHOICES.len();
+                    Ok(())
+                }
+                KeyCode::Return => match CHOICES[*selected] {
+                    "Host game" => {
+                        self.message = None;
+                        self.stage = start_hosting(self.map_hash.clone());
+                        Ok(())
+                    }
+                    "Join game" => {
+                        self.message = None;
+                        self.stage = Stage::Entering { text: String::new() };
+                        Ok(())
+                    }
+                    _ => return LobbyAction::Close,
+                },
+                KeyCode::Escape => return LobbyAction::Close,
+                _ => Ok(()),
+            },
+            Stage::Hosting { cancel, .. } => {
+                if keycode == KeyCode::Escape {
+                    cancel.store(true, Ordering::Relaxed);
+                    self.stage = Stage::Choose { selected: 0 };
+                }
+                Ok(())
+            }
+            Stage::Entering { text } => match keycode {
+                KeyCode::Back => {
+                    text.pop();
+                    Ok(())
+                }
+                KeyCode::Return => match parse_address(text) {
+                    Some(addr) => {
+                        self.message = None;
+                        let (sender, pending) = mpsc::channel();
+                        let map_hash = self.map_hash.clone();
+                        thread::spawn(move || {
+                            let _ = sender.send(Session::join(&addr.to_string(), map_hash));
+                        });
+                        self.stage = Stage::Joining { pending };
+                        Ok(())
+                    }
+                    None => Err("Enter a code or an address like 192.168.1.20:7878".to_string()),
+                },
+                KeyCode::Escape => {
+                    self.stage = Stage::Choose { selected: 1 };
+                    Ok(())
+                }
+                _ => Ok(()),
+            },
+            Stage::Joining { .. } => {
+                //the connection attempt finishes on its own and is dropped
+                if keycode == KeyCode::Escape {
+                    self.stage = Stage::Choose { selected: 1 };
+                }
+                Ok(())
+            }
+            Stage::Connected(room) => {
+                if room.countdown.is_some() {
+                    return LobbyAction::None;
+                }
+                match keycode {
+                    KeyCode::Escape => {
+                        self.stage = Stage::Choose { selected: 0 };
+                        Ok(())
+                    }
+                    KeyCode::Left | KeyCode::Right if room.host => {
+                        room.host_plays_pacman = !room.host_plays_pacman;
+                        room.send_settings()
+                    }
+                    KeyCode::Return | KeyCode::Space => {
+                        room.ready = !room.ready;
+                        let ready = room.ready;
+                        room.session.send_lobby(Lobby::Ready(ready)).and_then(|_| room.maybe_start())
+                    }
+                    _ => Ok(()),
+                }
+            }
+        };
+        if let Err(e) = result {
+            self.fail(e);
+        }
+        LobbyAction::None
+    }
+
+    //typed characters for the address field
+    pub fn text_input(&mut self, character: char) {
+        if let Stage::Entering { text } = &mut self.stage {
+            if (character.is_ascii_alphanumeric() || ".:-".contains(character)) && text.len() < 40 {
+                text.push(character);
+            }
+        }
+    }
+
+    //checks on connections and the other player, and starts the match when the countdown ends
+    pub fn update(&mut self) -> LobbyAction {
+        let result = match &mut self.stage {
+            Stage::Hosting { pending, .. } | Stage::Joining { pending } => match pending.try_recv() {
+                Ok(Ok(session)) => {
+                    let host = matches!(self.stage, Stage::Hosting { .. });
+                    let mut room = Room {
+                        session,
+                        host,
+                        host_plays_pacman: true,
+                        ready: false,
+                        other_ready: false,
+                        countdown: None,
+                    };
+                    let sent = if host { room.send_settings() } else { Ok(()) };
+                    self.stage = Stage::Connected(Box::new(room));
+                    sent
+                }
+                Ok(Err(e)) => Err(e),
+                Err(TryRecvError::Empty) => Ok(()),
+                Err(TryRecvError::Disconnected) => Err("Stopped waiting".to_string()),
+            },
+            Stage::Connected(room) => match room.session.poll_lobby() {
+                Ok(updates) => {
+                    for update in updates {
+                        match update {
+                            Lobby::Settings { host_plays_pacman } => {
+                                room.host_plays_pacman = host_plays_pacman;
+                                room.ready = false;
+                                room.other_ready = false;
+                            }
+                            Lobby::Ready(ready) => room.other_ready = ready,
+                            Lobby::Start => room.countdown = Some(Instant::now()),
+                        }
+                    }
+                    room.maybe_start()
+                }
+                Err(e) => Err(e),
+            },
+            _ => Ok(()),
+        };
+        if let Err(e) = result {
+            self.fail(e);
+            return LobbyAction::None;
+        }
+
+        let finished = match &self.stage {
+            Stage::Connected(room) => room.countdown.is_some_and(|start| start.elapsed().as_secs_f32() >= COUNTDOWN),
+            _ => false,
+        };
+        if finished {
+            if let Stage::Connected(room) = std::mem::replace(&mut self.stage, Stage::Choose { selected: 0 }) {
+                let mut session = room.session;
+                session.set_host_plays_pacman(room.host_plays_pacman);
+                return LobbyAction::Start(Box::new(session));
+            }
+        }
+        LobbyAction::None
+    }
+
+    fn fail(&mut self, message: String) {
+        if let Stage::Hosting { cancel, .. } = &self.stage {
+            cancel.store(true, Ordering::Relaxed);
+        }
+        self.message = Some(message);
+        self.stage = Stage::Choose { selected: 0 };
+    }
+
+    pub fn draw(&self, ctx: &mut Context) -> GameResult {
+        let (w, h) = graphics::drawable_size(ctx);
+
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, 0.0, w, h),
+            Color::new(0.0, 0.0, 0.0, 0.85),
+        )?;
+        graphics::draw(ctx, &background, DrawParam::default())?;
+
+        let menu_width = 420.0;
+        let menu_height = 300.0;
+        let menu_x = (w - menu_width) / 2.0;
+        let menu_y = (h - menu_height) / 2.0;
+        let menu_bg = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(menu_x, menu_y, menu_width, menu_height),
+            Color::new(0.2, 0.2, 0.2, 1.0),
+        )?;
+        graphics::draw(ctx, &menu_bg, DrawParam::default())?;
+
+        let title = graphics::Text::new("Versus");
+        let title_dims = title.dimensions(ctx);
+        graphics::draw(
+            ctx,
+            &title,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 {
+                    x: menu_x + (menu_width - title_dims.w * 2.0) / 2.0,
+                    y: menu_y + 20.0,
+                })
+                .color(Color::YELLOW)
+                .scale([2.0, 2.0]),
+        )?;
+
+        //(text, highlighted) lines under the title
+        let lines: Vec<(String, bool)> = match &self.stage {
+            Stage::Choose { selected } => CHOICES
+                .iter()
+                .enumerate()
+                .map(|(i, choice)| (choice.to_string(), i == *selected))
+                .collect(),
+            Stage::Hosting { address, .. } => match address {
+                Some(address) => vec![
+                    (format!("Address: {}", address), false),
+                    (format!("Code: {}", encode_code(address).unwrap_or_default()), true),
+                    ("Waiting for the other player...".to_string(), false),
+                ],
+                None => vec![("Waiting for the other player...".to_string(), false)],
+            },
+            Stage::Entering { text } => vec![
+                ("Enter the host's code or address:".to_string(), false),
+                (format!("{}_", text), true),
+            ],
+            Stage::Joining { .. } => vec![("Connecting...".to_string(), false)],
+            Stage::Connected(room) => {
+                let pacman = if room.host_plays_pacman == room.host { "You" } else { "Other player" };
+                let mut lines = vec![
+                    (format!("Map: {}", self.map_name), false),
+                    (format!("Pac-Man: {}", pacman), false),
+                    (format!("You: {}", if room.ready { "ready" } else { "not ready" }), room.ready),
+                    (format!("Other player: {}", if room.other_ready { "ready" } else { "not ready" }), room.other_ready),
+                ];
+                if let Some(start) = room.countdown {
+                    let left = (COUNTDOWN - start.elapsed().as_secs_f32()).ceil().max(1.0);
+                    lines.push((format!("Starting in {}", left), true));
+                }
+                lines
+            }
+        };
+        for (i, (line, highlighted)) in lines.iter().enumerate() {
+            let text = graphics::Text::new(line.as_str());
+            let color = if *highlighted { Color::YELLOW } else { Color::WHITE };
+            graphics::draw(
+                ctx,
+                &text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 { x: menu_x + 30.0, y: menu_y + 80.0 + i as f32 * 30.0 })
+                    .color(color),
+            )?;
+        }
+
+        if let Some(message) = &self.message {
+            let mut text = graphics::Text::new(message.as_str());
+            text.set_bounds(
+                ggez::mint::Point2 { x: menu_width - 40.0, y: f32::INFINITY },
+                graphics::Align::Left,
+            );
+            graphics::draw(
+                ctx,
+                &text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 { x: menu_x + 20.0, y: menu_y + 230.0 })
+                    .color(Color::RED),
+            )?;
+        }
+
+        let hint = match &self.stage {
+            Stage::Connected(room) if room.host => "Enter: ready   Left/Right: sides   Esc: leave",
+            Stage::Connected(_) => "Enter: ready   Esc: leave",
+            _ => "Enter: select   Esc: back",
+        };
+        graphics::draw(
+            ctx,
+            &graphics::Text::new(hint),
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: menu_x + 20.0, y: menu_y + 270.0 })
+                .color(Color::WHITE),
+        )?;
+        Ok(())
+    }
+}
+
+//listens on the default port and hands over the first player to connect
+fn start_hosting(map_hash: String) -> Stage {
+    let (sender, pending) = mpsc::channel();
+    let cancel = Arc::new(AtomicBool::new(false));
+    let address = lan_address().map(|ip| SocketAddr::new(ip.into(), DEFAULT_PORT));
+
+    let cancelled = Arc::clone(&cancel);
+    thread::spawn(move || {
+        let listener = match TcpListener::bind(("0.0.0.0", DEFAULT_PORT)) {
+            Ok(listener) => listener,
+            Err(e) => {
+                let _ = sender.send(Err(format!("Could not listen on port {}: {}", DEFAULT_PORT, e)));
+                return;
+            }
+        };
+        //non-blocking so leaving the lobby frees the port
+        if let Err(e) = listener.set_nonblocking(true) {
+            let _ = sender.send(Err(e.to_string()));
+            return;
+        }
+        while !cancelled.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let _ = stream.set_nonblocking(false);
+                    let _ = sender.send(Session::host_stream(stream, rand::random(), map_hash));
+                    return;
+                }
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => thread::sleep(ACCEPT_POLL),
+                Err(e) => {
+                    let _ = sender.send(Err(e.to_string()));
+                    return;
+                }
+            }
+        }
+    });
+    Stage::Hosting { address, pending, cancel }
+}
+
+//the address other machines on the network reach this one at. Connecting a UDP socket picks the
+//outgoing interface without sending anything
+fn lan_address() -> Option<Ipv4Addr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:9").ok()?;
+    match socket.local_addr().ok()? {
+        SocketAddr::V4(addr) if !addr.ip().is_unspecified() => Some(*addr.ip()),
+        _ => None,
+    }
+}
+
+//the four address bytes and the port, 48 bits, as ten base 32 digits split in two halves
+fn encode_code(address: &SocketAddr) -> Option<String> {
+    let address = match address {
+        SocketAddr::V4(address) => address,
+        SocketAddr::V6(_) => return None,
+    };
+    let value = ((u32::from(*address.ip()) as u64) << 16) | address.port() as u64;
+    let digits: String = (0..10)
+        .rev()
+        .map(|i| CODE_ALPHABET[((value >> (i * 5)) & 31) as usize] as char)
+        .collect();
+    Some(format!("{}-{}", &digits[..5], &digits[5..]))
+}
+
+fn decode_code(code: &str) -> Option<SocketAddr> {
+    let digits: Vec<char> = code.chars().filter(|&c| c != '-').map(|c| c.to_ascii_uppercase()).collect();
+    if digits.len() != 10 {
+        return None;
+    }
+    let mut value: u64 = 0;
+    for digit in digits {
+        value = (value << 5) | code_digit(digit)? as u64;
+    }
+    let ip = Ipv4Addr::from((value >> 16) as u32);
+    Some(SocketAddr::V4(SocketAddrV4::new(ip, (value & 0xffff) as u16)))
+}
+
+//the value of one base 32 digit, either case. Crockford's alphabet reads O as 0 and I or L as 1
+pub fn code_digit(digit: char) -> Option<u8> {
+    let digit = match digit.to_ascii_uppercase() {
+        'O' => '0',
+        'I' | 'L' => '1',
+        other => other,
+    };
+    CODE_ALPHABET.iter().position(|&c| c as char == digit).map(|index| index as u8)
+}
+
+//a lobby code, `ip:port`, or a bare ip on the default port
+fn parse_address(text: &str) -> Option<SocketAddr> {
+    let text = text.trim();
+    if let Ok(address) = text.parse() {
+        return Some(address);
+    }
+    if let Ok(ip) = text.parse::<Ipv4Addr>() {
+        return Some(SocketAddr::new(ip.into(), DEFAULT_PORT));
+    }
+    decode_code(text)
+}
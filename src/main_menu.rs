@@ -0,0 +1,370 @@
+//title screen menu shown at startup. Left alone, it runs the attract mode like an arcade
+//cabinet: the high scores, the ghost intro and a demo game take turns with the menu
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+
+use crate::intro::Intro;
+use crate::save::{self, SLOT_COUNT};
+use crate::share::Challenge;
+use crate::scores::{ScoreDb, SortKey};
+use crate::TICKS_PER_SECOND;
+
+//how long the menu waits for a key, and how long each attract page stays up
+const ATTRACT_PAGE_TICKS: u32 = 15 * TICKS_PER_SECOND;
+const HIGH_SCORE_COUNT: usize = 10;
+//longer than any share code, dashes and all
+const MAX_CODE_LENGTH: usize = 80;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Item {
+    NewGame,
+    Practice,
+    Tutorial,
+    Ghosts,
+    LoadGame,
+    Versus,
+    Race,
+    EnterCode,
+    History,
+    Settings,
+    Exit,
+}
+
+const ITEMS: [(Item, &str); 11] = [
+    (Item::NewGame, "New Game"),
+    (Item::Practice, "Practice"),
+    (Item::Tutorial, "Tutorial"),
+    (Item::Ghosts, "Meet the Ghosts"),
+    (Item::LoadGame, "Load Game"),
+    (Item::Versus, "Versus"),
+    (Item::Race, "Race"),
+    (Item::EnterCode, "Enter Code"),
+    (Item::History, "History"),
+    (Item::Settings, "Settings"),
+    (Item::Exit, "Exit"),
+];
+
+//what the caller should do after a key press
+pub enum MenuAction {
+    None,
+    NewGame,
+    Practice,
+    Tutorial,
+    Intro,
+    Load(usize),
+    Versus,
+    Race,
+    //play the game a share code was made from
+    Challenge(Challenge),
+    History,
+    Settings,
+    Exit,
+}
+
+//the attract pages, in the order they take turns
+enum Page {
+    Menu,
+    //one line per game
+    HighScores(Vec<String>),
+    Intro(Intro),
+    Demo,
+}
+
+//what the caller should do after an attract tick
+pub enum AttractAction {
+    None,
+    //set up a game for the demo page to show
+    StartDemo,
+}
+
+pub struct MainMenu {
+    selected: usize,
+    //Some while the save slot list is shown, holding each slot's summary line
+    slots: Option<Vec<String>>,
+    //Some while a share code is being typed in
+    code: Option<String>,
+    message: Option<String>,
+    page: Page,
+    //ticks on this page without a key press
+    page_ticks: u32,
+}
+
+impl MainMenu {
+    pub fn new() -> Self {
+        MainMenu {
+            selected: 0,
+            slots: None,
+            code: None,
+            message: None,
+            page: Page::Menu,
+            page_ticks: 0,
+        }
+    }
+
+    //one tick of the attract mode, moving on to the next page when this one's time is up. The
+    //slot list is being read, so it stays
+    pub fn update(&mut self) -> AttractAction {
+        if let Page::Intro(intro) = &mut self.page {
+            intro.advance();
+        }
+        self.page_ticks += 1;
+        if self.page_ticks < ATTRACT_PAGE_TICKS || self.slots.is_some() || self.code.is_some() {
+            return AttractAction::None;
+        }
+        self.page_ticks = 0;
+        let (page, action) = match self.page {
+            Page::Menu => (Page::HighScores(high_score_lines()), AttractAction::None),
+            Page::HighScores(_) => (Page::Intro(Intro::new(true)), AttractAction::None),
+            Page::Intro(_) => (Page::Demo, AttractAction::StartDemo),
+            Page::Demo => (Page::Menu, AttractAction::None),
+        };
+        self.page = page;
+        action
+    }
+
+    //the demo page is up, so the game underneath should play itself
+    pub fn showing_demo(&self) -> bool {
+        matches!(self.page, Page::Demo)
+    }
+
+    pub fn handle_key(&mut self, keycode: KeyCode) -> MenuAction {
+        self.page_ticks = 0;
+        //a key during the attract mode only brings the menu back
+        if !matches!(self.page, Page::Menu) {
+            self.page = Page::Menu;
+            return MenuAction::None;
+        }
+        if let Some(code) = &mut self.code {
+            match keycode {
+                KeyCode::Back => {
+                    code.pop();
+                }
+                KeyCode::Escape => {
+                    self.code = None;
+                    self.message = None;
+                }
+                KeyCode::Return => match Challenge::decode(code) {
+                    Some(challenge) => {
+                        self.code = None;
+                        self.message = None;
+                        return MenuAction::Challenge(challenge);
+                    }
+                    None => self.message = Some("That code isn't right, check it for typos".to_string()),
+                },
+                _ => {}
+            }
+            return MenuAction::None;
+        }
+        let count = match &self.slots {
+            Some(slots) => slots.len(),
+            None => ITEMS.len(),
+        };
+        match keycode {
+            KeyCode::Up => self.selected = (self.selected + count - 1) % count,
+            KeyCode::Down => self.selected = (self.selected + 1) % count,
+            KeyCode::Escape if self.slots.is_some() => {
+                self.slots = None;
+                self.selected = ITEMS.iter().position(|&(item, _)| item == Item::LoadGame).unwrap_or(0);
+            }
+            KeyCode::Return => {
+                if self.slots.is_some() {
+                    return MenuAction::Load(self.selected);
+                }
+                match ITEMS[self.selected].0 {
+                    Item::NewGame => return MenuAction::NewGame,
+                    Item::Practice => return MenuAction::Practice,
+                    Item::Tutorial => return MenuAction::Tutorial,
+                    Item::Ghosts => return MenuAction::Intro,
+                    Item::LoadGame => {
+                        self.slots = Some(slot_summaries());
+                        self.selected = 0;
+                        self.message = None;
+                    }
+                    Item::Versus => return MenuAction::Versus,
+                    Item::Race => return MenuAction::Race,
+                    Item::EnterCode => {
+                        self.code = Some(String::new());
+                        self.message = None;
+                    }
+                    Item::History => return MenuAction::History,
+                    Item::Settings => return MenuAction::Settings,
+                    Item::Exit => return MenuAction::Exit,
+                }
+            }
+            _ => {}
+        }
+        MenuAction::None
+    }
+
+    //typed characters for the share code field
+    pub fn text_input(&mut self, character: char) {
+        if let Some(code) = &mut self.code {
+            if (character.is_ascii_alphanumeric() || character == '-') && code.len() < MAX_CODE_LENGTH {
+                code.push(character.to_ascii_uppercase());
+            }
+        }
+    }
+
+    pub fn set_message(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    //`banner` is a notice above the title, e.g. that a new version is out
+    pub fn draw(&self, ctx: &mut Context, banner: Option<&str>) -> GameResult {
+        let (w, h) = graphics::drawable_size(ctx);
+        match &self.page {
+            Page::Menu => {}
+            Page::HighScores(lines) => return draw_high_scores(ctx, lines),
+            Page::Intro(intro) => return intro.draw(ctx),
+            //the game underneath shows through, under a reminder that it's only a demo
+            Page::Demo => {
+                let text = graphics::Text::new("DEMO - press any key");
+                let dims = text.dimensions(ctx);
+                return graphics::draw(
+                    ctx,
+                    &text,
+                    DrawParam::default()
+                        .dest(ggez::mint::Point2 { x: (w - dims.w * 2.0) / 2.0, y: h * 0.5 })
+                        .color(Color::YELLOW)
+                        .scale([2.0, 2.0]),
+                );
+            }
+        }
+
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, 0.0, w, h),
+            Color::new(0.0, 0.0, 0.0, 0.85),
+        )?;
+        graphics::draw(ctx, &background, DrawParam::default())?;
+
+        if let Some(banner) = banner {
+            let text = graphics::Text::new(banner);
+            let dims = text.dimensions(ctx);
+            graphics::draw(
+                ctx,
+                &text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 { x: (w - dims.w) / 2.0, y: h * 0.1 })
+                    .color(Color::GREEN),
+            )?;
+        }
+
+        let title = graphics::Text::new("PAC-MAN");
+        let title_dims = title.dimensions(ctx);
+        graphics::draw(
+            ctx,
+            &title,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 {
+                    x: (w - title_dims.w * 3.0) / 2.0,
+                    y: h * 0.2,
+                })
+                .color(Color::YELLOW)
+                .scale([3.0, 3.0]),
+        )?;
+
+        let labels: Vec<String> = match (&self.slots, &self.code) {
+            (Some(slots), _) => slots.clone(),
+            (None, Some(code)) => vec!["Type in a share code:".to_string(), format!("{}_", code)],
+            (None, None) => ITEMS.iter().map(|(_, label)| label.to_string()).collect(),
+        };
+        //the code being typed stands out instead
+        let selected = if self.code.is_some() { 1 } else { self.selected };
+        for (i, label) in labels.iter().enumerate() {
+            let text = graphics::Text::new(label.as_str());
+            let dims = text.dimensions(ctx);
+            let color = if i == selected { Color::YELLOW } else { Color::WHITE };
+            graphics::draw(
+                ctx,
+                &text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 {
+                        x: (w - dims.w) / 2.0,
+                        y: h * 0.4 + i as f32 * 35.0,
+                    })
+                    .color(color),
+            )?;
+        }
+
+        if let Some(message) = &self.message {
+            let text = graphics::Text::new(message.as_str());
+            let dims = text.dimensions(ctx);
+            graphics::draw(
+                ctx,
+                &text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 { x: (w - dims.w) / 2.0, y: h * 0.9 })
+                    .color(Color::RED),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+//one line per save slot describing what it holds
+fn slot_summaries() -> Vec<String> {
+    (0..SLOT_COUNT)
+        .map(|slot| match save::load_slot(slot) {
+            Some(snapshot) => format!(
+                "Slot {}: Score {}  Lives {}",
+                slot + 1,
+                snapshot.score,
+                snapshot.lives
+            ),
+            None => format!("Slot {}: empty", slot + 1),
+        })
+        .collect()
+}
+
+//the best games from the history, or a note that there aren't any yet
+fn high_score_lines() -> Vec<String> {
+    match ScoreDb::open().and_then(|db| db.games(SortKey::Score, HIGH_SCORE_COUNT)) {
+        Ok(games) if !games.is_empty() => games
+            .iter()
+            .enumerate()
+            .map(|(i, game)| format!("{:>2}. {:>8}   {}", i + 1, game.score, game.date))
+            .collect(),
+        Ok(_) => vec!["No games played yet".to_string()],
+        Err(e) => {
+            eprintln!("Could not read the high scores: {}", e);
+            vec!["No high scores".to_string()]
+        }
+    }
+}
+
+fn draw_high_scores(ctx: &mut Context, lines: &[String]) -> GameResult {
+    let (w, h) = graphics::drawable_size(ctx);
+    let background = graphics::Mesh::new_rectangle(
+        ctx,
+        graphics::DrawMode::fill(),
+        graphics::Rect::new(0.0, 0.0, w, h),
+        Color::BLACK,
+    )?;
+    graphics::draw(ctx, &background, DrawParam::default())?;
+
+    let title = graphics::Text::new("HIGH SCORES");
+    let title_dims = title.dimensions(ctx);
+    graphics::draw(
+        ctx,
+        &title,
+        DrawParam::default()
+            .dest(ggez::mint::Point2 { x: (w - title_dims.w * 2.0) / 2.0, y: h * 0.15 })
+            .color(Color::YELLOW)
+            .scale([2.0, 2.0]),
+    )?;
+
+    //one block, centered on its widest line, so the columns stay lined up
+    let mut table = graphics::Text::new(lines.join("\n"));
+    table.set_bounds(ggez::mint::Point2 { x: w, y: f32::INFINITY }, graphics::Align::Left);
+    let dims = table.dimensions(ctx);
+    graphics::draw(
+        ctx,
+        &table,
+        DrawParam::default()
+            .dest(ggez::mint::Point2 { x: (w - dims.w) / 2.0, y: h * 0.3 })
+            .color(Color::WHITE),
+    )
+}
@@ -0,0 +1,166 @@
+//maze text parser. Rows are lines of cells: 'W' wall, '.' dot, 'P' pacman's start,
+//'G' ghost spawn, 'T' tunnel, 'D' ghost house door and ' ' empty. Short rows are padded with
+//empty cells, so every (col, row) inside width x height is valid. A `[rules]` line ends the
+//rows, and what follows it is the maze's Ruleset
+use ggez::graphics::Rect;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::rules::{Ruleset, RULES_HEADER};
+use crate::{CELL_SIZE, THIN_WALL_SIZE};
+
+pub const MAX_MAP_SIZE: usize = 100;
+const CELLS: &str = "W.PGTD ";
+//the maze the game ships with, played unless `--map` names another
+const CLASSIC: &str = include_str!("../maps/classic.txt");
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Map {
+    //what the game's history files it under: "classic" for the built-in maze, the file's name
+    //for one loaded with `--map`
+    pub name: String,
+    pub width: usize,
+    pub height: usize,
+    cells: Vec<char>,
+    pub rules: Ruleset,
+}
+
+impl Map {
+    pub fn classic() -> Map {
+        Map { name: "classic".to_string(), ..parse(CLASSIC).expect("the built-in maze is valid") }
+    }
+
+    //a maze file, in the text format `parse` reads
+    pub fn load(path: &Path) -> Result<Map, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let map = parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        match path.file_stem() {
+            Some(stem) => Ok(Map { name: stem.to_string_lossy().into_owned(), ..map }),
+            None => Ok(map),
+        }
+    }
+
+    //every row at the full width, short ones padded with empty cells
+    pub fn rows(&self) -> Vec<String> {
+        self.cells.chunks(self.width).map(|row| row.iter().collect()).collect()
+    }
+
+    pub fn cell(&self, col: usize, row: usize) -> Option<char> {
+        if col < self.width && row < self.height {
+            Some(self.cells[row * self.width + col])
+        } else {
+            None
+        }
+    }
+
+    //the single 'P' cell
+    pub fn pacman_start(&self) -> (usize, usize) {
+        let index = self.cells.iter().position(|&cell| cell == 'P').unwrap_or(0);
+        (index % self.width, index / self.width)
+    }
+
+    //one square per 'W' cell
+    pub fn walls(&self) -> Vec<Rect> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, &cell)| cell == 'W')
+            .map(|(index, _)| {
+                let (col, row) = (index % self.width, index / self.width);
+                Rect::new(col as f32 * CELL_SIZE, row as f32 * CELL_SIZE, THIN_WALL_SIZE, THIN_WALL_SIZE)
+            })
+            .collect()
+    }
+
+    //every '.' cell, row by row
+    pub fn dot_cells(&self) -> Vec<(usize, usize)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, &cell)| cell == '.')
+            .map(|(index, _)| (index % self.width, index / self.width))
+            .collect()
+    }
+
+    //every 'T' cell, row by row. One on an edge of the maze leads out to the opposite edge
+    pub fn tunnel_cells(&self) -> Vec<(usize, usize)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, &cell)| cell == 'T')
+            .map(|(index, _)| (index % self.width, index / self.width))
+            .collect()
+    }
+
+    pub fn ghost_spawns(&self) -> Vec<(usize, usize)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, &cell)| cell == 'G')
+            .map(|(index, _)| (index % self.width, index / self.width))
+            .collect()
+    }
+}
+
+//writes the map back out in the text format `parse` reads
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.cells.chunks(self.width) {
+            writeln!(f, "{}", row.iter().collect::<String>().trim_end())?;
+        }
+        if self.rules != Ruleset::default() {
+            write!(f, "{}\n{}", RULES_HEADER, self.rules.to_toml())?;
+        }
+        Ok(())
+    }
+}
+
+//the rows, and the rules block after them if there is one
+pub fn split_rules(text: &str) -> (&str, Option<&str>) {
+    let mut start = 0;
+    for line in text.split_inclusive('\n') {
+        if line.trim() == RULES_HEADER {
+            return (&text[..start], Some(&text[start + line.len()..]));
+        }
+        start += line.len();
+    }
+    (text, None)
+}
+
+pub fn parse(text: &str) -> Result<Map, String> {
+    let (text, rules) = split_rules(text);
+    let rules = match rules {
+        Some(rules) => Ruleset::parse(rules)?,
+        None => Ruleset::default(),
+    };
+    let rows: Vec<Vec<char>> = text
+        .lines()
+        .map(|line| line.trim_end_matches('\r').chars().collect())
+        .collect();
+    //trailing blank lines don't count as rows
+    let height = rows.iter().rposition(|row| !row.is_empty()).map_or(0, |last| last + 1);
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    if height == 0 || width == 0 {
+        return Err("the map is empty".to_string());
+    }
+    if width > MAX_MAP_SIZE || height > MAX_MAP_SIZE {
+        return Err(format!("the map is {}x{}, the limit is {}x{}", width, height, MAX_MAP_SIZE, MAX_MAP_SIZE));
+    }
+
+    let mut cells = Vec::with_capacity(width * height);
+    for (y, row) in rows[..height].iter().enumerate() {
+        if let Some((x, cell)) = row.iter().enumerate().find(|(_, cell)| !CELLS.contains(**cell)) {
+            return Err(format!("unknown cell {:?} at row {}, column {}", cell, y + 1, x + 1));
+        }
+        cells.extend(row.iter().copied());
+        cells.resize((y + 1) * width, ' ');
+    }
+
+    match cells.iter().filter(|&&cell| cell == 'P').count() {
+        1 => {}
+        0 => return Err("the map has no 'P' start cell".to_string()),
+        n => return Err(format!("the map has {} 'P' start cells, it needs exactly one", n)),
+    }
+    Ok(Map { name: "custom".to_string(), width, height, cells, rules })
+}
@@ -0,0 +1,110 @@
+//converters from other pac-man maze text formats into `Map`, so fan-made levels don't need
+//to be redrawn by hand. Power pellets become dots, since the map model places its own, and ghost
+//house doors become walls, as arcade text draws them with the same glyphs as walls. A `[rules]`
+//block is carried over as it is
+use crate::map::{self, Map};
+use crate::rules::RULES_HEADER;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    //this crate's own "W.PGTD " cells
+    Native,
+    //the UC Berkeley AI course `.lay` layouts: '%' wall, '.' food, 'o' capsule, 'P', 'G'
+    Berkeley,
+    //28x31 arcade transcriptions drawn with '#', '|', '-', '+' or '=' walls and 'o', 'O' or '*'
+    //energizers. Most don't mark the start cells, so the arcade ones are filled in
+    Arcade,
+}
+
+const ARCADE_WALLS: &str = "#|-+=";
+//pacman starts just below the ghost house, and the ghosts inside it, in the 28x31 arcade maze
+const ARCADE_PACMAN_START: (usize, usize) = (13, 23);
+const ARCADE_GHOST_SPAWNS: [(usize, usize); 4] = [(11, 14), (13, 14), (14, 14), (16, 14)];
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "native" => Some(Format::Native),
+            "berkeley" => Some(Format::Berkeley),
+            "arcade" => Some(Format::Arcade),
+            _ => None,
+        }
+    }
+
+    //guesses the format from the wall glyphs used
+    pub fn detect(text: &str) -> Format {
+        if text.contains('%') {
+            Format::Berkeley
+        } else if text.contains(|glyph| ARCADE_WALLS.contains(glyph)) {
+            Format::Arcade
+        } else {
+            Format::Native
+        }
+    }
+
+    //the native cell for a glyph, None when the format doesn't use it
+    fn cell(self, glyph: char) -> Option<char> {
+        match (self, glyph) {
+            (Format::Native, _) => Some(glyph),
+            (_, ' ') => Some(' '),
+            (_, '.') | (_, 'o') => Some('.'),
+            (_, 'P') => Some('P'),
+            (_, 'G') => Some('G'),
+            (Format::Berkeley, '%') => Some('W'),
+            (Format::Arcade, glyph) if ARCADE_WALLS.contains(glyph) => Some('W'),
+            (Format::Arcade, 'O') | (Format::Arcade, '*') => Some('.'),
+            (Format::Arcade, '<') => Some('P'),
+            _ => None,
+        }
+    }
+}
+
+pub fn import(text: &str, format: Format) -> Result<Map, String> {
+    if format == Format::Native {
+        return map::parse(text);
+    }
+
+    let (text, rules) = map::split_rules(text);
+    let mut rows = Vec::new();
+    for (y, line) in text.lines().enumerate() {
+        let mut row = Vec::new();
+        for (x, glyph) in line.trim_end_matches('\r').chars().enumerate() {
+            let cell = format
+                .cell(glyph)
+                .ok_or_else(|| format!("unknown {:?} cell {:?} at row {}, column {}", format, glyph, y + 1, x + 1))?;
+            row.push(cell);
+        }
+        rows.push(row);
+    }
+    if format == Format::Arcade {
+        add_arcade_starts(&mut rows);
+    }
+
+    let mut native: Vec<String> = rows.iter().map(|row| row.iter().collect()).collect();
+    if let Some(rules) = rules {
+        native.push(RULES_HEADER.to_string());
+        native.push(rules.to_string());
+    }
+    map::parse(&native.join("\n"))
+}
+
+//puts pacman and the ghosts where the arcade game does, unless the layout marks its own
+fn add_arcade_starts(rows: &mut [Vec<char>]) {
+    if !rows.iter().flatten().any(|&cell| cell == 'P') {
+        place(rows, ARCADE_PACMAN_START, 'P');
+    }
+    if !rows.iter().flatten().any(|&cell| cell == 'G') {
+        for &spawn in ARCADE_GHOST_SPAWNS.iter() {
+            place(rows, spawn, 'G');
+        }
+    }
+}
+
+//only over open cells, in case the layout differs from the arcade one
+fn place(rows: &mut [Vec<char>], (col, row): (usize, usize), cell: char) {
+    if let Some(slot) = rows.get_mut(row).and_then(|row| row.get_mut(col)) {
+        if *slot == ' ' || *slot == '.' {
+            *slot = cell;
+        }
+    }
+}
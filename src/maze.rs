@@ -0,0 +1,130 @@
+//what each cell of the maze text is for movement. 'W' is a wall and ' ' is void: the space
+//around and between the corridors, just as impassable as a wall but never drawn. Spaces joined
+//to a 'G' cell are the ghost house's floor rather than void, 'D' is the house's door, which only
+//ghosts go through, and everything else is open
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Cell {
+    Wall,
+    Void,
+    Door,
+    Open,
+}
+
+pub struct Layout {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl Layout {
+    //short rows are padded with void
+    pub fn new(rows: &[&str]) -> Layout {
+        let width = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+        let height = rows.len();
+        let mut cells = vec![Cell::Void; width * height];
+        let mut house = VecDeque::new();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, cell) in row.chars().enumerate() {
+                cells[y * width + x] = match cell {
+                    'W' => Cell::Wall,
+                    ' ' => Cell::Void,
+                    'D' => Cell::Door,
+                    _ => Cell::Open,
+                };
+                if cell == 'G' {
+                    house.push_back((x, y));
+                }
+            }
+        }
+
+        //the ghost house's floor: spaces reachable from a 'G' through other spaces
+        while let Some((x, y)) = house.pop_front() {
+            let neighbours = [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)];
+            for (nx, ny) in neighbours {
+                if nx < width && ny < height && cells[ny * width + nx] == Cell::Void {
+                    cells[ny * width + nx] = Cell::Open;
+                    house.push_back((nx, ny));
+                }
+            }
+        }
+        Layout { width, height, cells }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn cell(&self, col: i64, row: i64) -> Cell {
+        if col < 0 || row < 0 || col as usize >= self.width || row as usize >= self.height {
+            return Cell::Void;
+        }
+        self.cells[row as usize * self.width + col as usize]
+    }
+
+    //whether pacman or a ghost can be on the cell. Anything outside the maze is void, and doors
+    //are only for passing through
+    pub fn is_open(&self, col: i64, row: i64) -> bool {
+        self.cell(col, row) == Cell::Open
+    }
+
+    //void cells, as (column, row), for the wall grid to block
+    pub fn void_cells(&self) -> Vec<(usize, usize)> {
+        self.cells_of(Cell::Void)
+    }
+
+    //door cells, as (column, row), blocked for everyone but the ghosts
+    pub fn door_cells(&self) -> Vec<(usize, usize)> {
+        self.cells_of(Cell::Door)
+    }
+
+    fn cells_of(&self, kind: Cell) -> Vec<(usize, usize)> {
+        (0..self.cells.len())
+            .filter(|&index| self.cells[index] == kind)
+            .map(|index| (index % self.width, index / self.width))
+            .collect()
+    }
+
+    //void cells right next to an open one. A maze edited by hand can end up with these where a
+    //wall was left out, and the corridor then stops dead there without a wall drawn
+    pub fn gaps(&self) -> Vec<(usize, usize)> {
+        self.void_cells()
+            .into_iter()
+            .filter(|&(col, row)| {
+                let (col, row) = (col as i64, row as i64);
+                [(col - 1, row), (col + 1, row), (col, row - 1), (col, row + 1)]
+                    .iter()
+                    .any(|&(c, r)| self.is_open(c, r))
+            })
+            .collect()
+    }
+
+    //the open cell nearest to `start`, searching outwards ring by ring in reading order
+    pub fn nearest_open(&self, start: (usize, usize)) -> Option<(usize, usize)> {
+        let (start_col, start_row) = (start.0 as i64, start.1 as i64);
+        let size = self.width.max(self.height) as i64;
+        for distance in 0..=size {
+            for row in start_row - distance..=start_row + distance {
+                for col in start_col - distance..=start_col + distance {
+                    let on_ring = (row - start_row).abs().max((col - start_col).abs()) == distance;
+                    if on_ring && self.is_open(col, row) {
+                        return Some((col as usize, row as usize));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+//warns about every gap in the maze's walls
+pub fn check(layout: &Layout) {
+    for (col, row) in layout.gaps() {
+        eprintln!("The maze has a gap at column {}, row {}: empty space next to a corridor without a wall", col + 1, row + 1);
+    }
+}
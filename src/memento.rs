@@ -0,0 +1,70 @@
+//game-over keepsake: the final board with the score and date, rendered offscreen and
+//saved as a PNG in the gallery folder
+use ggez::graphics::{self, Canvas, Color, DrawParam};
+use ggez::{Context, GameResult};
+use std::path::{Path, PathBuf};
+
+use crate::paths;
+
+//how long the "saved" toast stays up, in seconds
+pub const TOAST_DURATION: f32 = 3.0;
+const CAPTION_HEIGHT: f32 = 40.0;
+
+//draws the board with `draw_board` into a canvas, adds the caption and writes the image
+pub fn save<F>(ctx: &mut Context, score: u32, draw_board: F) -> Result<PathBuf, String>
+where
+    F: FnOnce(&mut Context) -> GameResult,
+{
+    let now = chrono::Local::now();
+    let canvas = Canvas::with_window_size(ctx).map_err(|e| e.to_string())?;
+    graphics::set_canvas(ctx, Some(&canvas));
+    let drawn = compose(ctx, score, &now.format("%Y-%m-%d %H:%M").to_string(), draw_board);
+    graphics::set_canvas(ctx, None);
+    drawn.map_err(|e| e.to_string())?;
+
+    let path = paths::gallery_dir().join(format!("pacman-{}.png", now.format("%Y-%m-%d_%H-%M-%S")));
+    write_png(ctx, &canvas, &path)?;
+    Ok(path)
+}
+
+//reads a canvas back and saves it as a PNG
+pub fn write_png(ctx: &mut Context, canvas: &Canvas, path: &Path) -> Result<(), String> {
+    let frame = canvas.raw_image();
+    let (width, height) = (frame.width() as u32, frame.height() as u32);
+    let pixels = frame.to_rgba8(ctx).map_err(|e| e.to_string())?;
+    //canvas images come back bottom row first
+    let row_len = width as usize * 4;
+    let pixels: Vec<u8> = pixels.chunks(row_len).rev().flatten().copied().collect();
+    image::save_buffer(path, &pixels, width, height, image::ColorType::Rgba8).map_err(|e| e.to_string())
+}
+
+fn compose<F>(ctx: &mut Context, score: u32, date: &str, draw_board: F) -> GameResult
+where
+    F: FnOnce(&mut Context) -> GameResult,
+{
+    graphics::clear(ctx, Color::BLACK);
+    draw_board(ctx)?;
+
+    //caption band along the bottom
+    let (w, h) = graphics::drawable_size(ctx);
+    let band = graphics::Mesh::new_rectangle(
+        ctx,
+        graphics::DrawMode::fill(),
+        graphics::Rect::new(0.0, h - CAPTION_HEIGHT, w, CAPTION_HEIGHT),
+        Color::new(0.0, 0.0, 0.0, 0.8),
+    )?;
+    graphics::draw(ctx, &band, DrawParam::default())?;
+
+    let caption = graphics::Text::new(format!("Final Score: {}    {}", score, date));
+    let caption_dims = caption.dimensions(ctx);
+    graphics::draw(
+        ctx,
+        &caption,
+        DrawParam::default()
+            .dest(ggez::mint::Point2 {
+                x: (w - caption_dims.w) / 2.0,
+                y: h - (CAPTION_HEIGHT + caption_dims.h) / 2.0,
+            })
+            .color(Color::YELLOW),
+    )
+}
@@ -0,0 +1,294 @@
+//Rhai mods loaded from the mods directory. A script defines any of these hooks:
+//
+//    fn on_level_start(level) { ... }
+//    fn on_tick(tick) { ... }
+//    fn on_pellet_eaten(col, row, power) { ... }
+//    fn on_ghost_eaten(ghost) { ... }
+//    fn on_command(name, args) { ... }
+//
+//Scripts can't reach the game directly: the API below only reads a snapshot of the
+//state and queues actions that MainState applies after the hooks have run
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, Scope, AST};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::entities::EntityEffect;
+
+//limits that stop a broken or hostile script from hanging the game
+const MAX_OPERATIONS: u64 = 100_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_COLLECTION_SIZE: usize = 1_000;
+const MAX_STRING_SIZE: usize = 10_000;
+
+//speed multipliers mods can set are clamped to this
+pub const MAX_SPEED_FACTOR: f32 = 2.0;
+//how long a scripted "slow" hazard slows pacman for
+const SLOW_HAZARD_DURATION: f32 = 5.0;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Hook {
+    LevelStart(u32),
+    Tick(u32),
+    PelletEaten { col: usize, row: usize, power: bool },
+    GhostEaten(usize),
+}
+
+#[derive(Clone, Debug)]
+pub enum ModAction {
+    AddScore(i64),
+    SpawnDot(usize, usize),
+    SpawnPowerPellet(usize, usize),
+    PacmanSpeed(f32),
+    GhostSpeed(f32),
+    RegisterItem { name: String, color: (u8, u8, u8), points: u32 },
+    RegisterHazard { name: String, color: (u8, u8, u8), effect: EntityEffect },
+    SpawnEntity(String, usize, usize),
+    RegisterCommand { name: String, help: String },
+}
+
+//what scripts can read about the game
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ModView {
+    pub tick: u32,
+    pub score: u32,
+    pub lives: i32,
+    pub pacman_col: usize,
+    pub pacman_row: usize,
+}
+
+struct Script {
+    name: String,
+    ast: AST,
+    scope: Scope<'static>,
+    hooks: HashSet<String>,
+    //a script that errors is switched off instead of failing every tick
+    failed: bool,
+}
+
+pub struct ModHost {
+    engine: Engine,
+    scripts: Vec<Script>,
+    actions: Rc<RefCell<Vec<ModAction>>>,
+    view: Rc<RefCell<ModView>>,
+}
+
+impl ModHost {
+    //loads every `.rhai` file in `dir`; scripts that don't compile are skipped
+    pub fn load(dir: &Path) -> ModHost {
+        let actions = Rc::new(RefCell::new(Vec::new()));
+        let view = Rc::new(RefCell::new(ModView::default()));
+        let engine = sandboxed_engine(&actions, &view);
+
+        let mut paths: Vec<_> = match fs::read_dir(dir) {
+            Ok(entries) => entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        //load order decides which mod's actions apply last
+        paths.sort();
+
+        let mut scripts = Vec::new();
+        for path in paths {
+            let name = path.file_name().map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+            let source = match fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(e) => {
+                    eprintln!("Could not read mod {}: {}", name, e);
+                    continue;
+                }
+            };
+            let ast = match engine.compile(&source) {
+                Ok(ast) => ast,
+                Err(e) => {
+                    eprintln!("Mod {} failed to compile: {}", name, e);
+                    continue;
+                }
+            };
+            //top-level statements run once, when the mod loads
+            let mut scope = Scope::new();
+            if let Err(e) = engine.run_ast_with_scope(&mut scope, &ast) {
+                eprintln!("Mod {} failed to load: {}", name, e);
+                continue;
+            }
+            let hooks = ast.iter_functions().map(|function| function.name.to_string()).collect();
+            println!("Loaded mod {}", name);
+            scripts.push(Script { name, ast, scope, hooks, failed: false });
+        }
+
+        ModHost { engine, scripts, actions, view }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    //actions queued outside of a hook, e.g. entity kinds registered when the mods loaded
+    pub fn take_pending(&mut self) -> Vec<ModAction> {
+        self.actions.borrow_mut().drain(..).collect()
+    }
+
+    //runs `hook` in every mod that defines it and returns the actions they queued
+    pub fn call(&mut self, hook: Hook, view: ModView) -> Vec<ModAction> {
+        *self.view.borrow_mut() = view;
+        let (name, args): (&str, Vec<Dynamic>) = match hook {
+            Hook::LevelStart(level) => ("on_level_start", vec![Dynamic::from(level as i64)]),
+            Hook::Tick(tick) => ("on_tick", vec![Dynamic::from(tick as i64)]),
+            Hook::PelletEaten { col, row, power } => (
+                "on_pellet_eaten",
+                vec![Dynamic::from(col as i64), Dynamic::from(row as i64), Dynamic::from(power)],
+            ),
+            Hook::GhostEaten(ghost) => ("on_ghost_eaten", vec![Dynamic::from(ghost as i64)]),
+        };
+
+        for script in &mut self.scripts {
+            if script.failed || !script.hooks.contains(name) {
+                continue;
+            }
+            let options = CallFnOptions::new().eval_ast(false);
+            let result = self.engine.call_fn_with_options::<Dynamic>(
+                options,
+                &mut script.scope,
+                &script.ast,
+                name,
+                args.clone(),
+            );
+            if let Err(e) = result {
+                eprintln!("Mod {} disabled after an error in {}: {}", script.name, name, e);
+                script.failed = true;
+            }
+        }
+        self.actions.borrow_mut().drain(..).collect()
+    }
+
+    //runs a console command a mod registered through every mod's on_command. Returns what the
+    //first mod to answer with a string said, and the actions they queued
+    pub fn run_command(&mut self, name: &str, args: &[&str], view: ModView) -> (Option<String>, Vec<ModAction>) {
+        *self.view.borrow_mut() = view;
+        let args: rhai::Array = args.iter().map(|arg| Dynamic::from(arg.to_string())).collect();
+        let mut reply = None;
+        for script in &mut self.scripts {
+            if script.failed || !script.hooks.contains("on_command") {
+                continue;
+            }
+            let options = CallFnOptions::new().eval_ast(false);
+            let result = self.engine.call_fn_with_options::<Dynamic>(
+                options,
+                &mut script.scope,
+                &script.ast,
+                "on_command",
+                (name.to_string(), args.clone()),
+            );
+            match result {
+                Ok(value) => {
+                    if reply.is_none() && value.is_string() {
+                        reply = Some(value.to_string());
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Mod {} disabled after an error in on_command: {}", script.name, e);
+                    script.failed = true;
+                }
+            }
+        }
+        (reply, self.actions.borrow_mut().drain(..).collect())
+    }
+}
+
+fn sandboxed_engine(actions: &Rc<RefCell<Vec<ModAction>>>, view: &Rc<RefCell<ModView>>) -> Engine {
+    let mut engine = Engine::new();
+    //no importing other files and no eval of generated code
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+    engine.set_max_array_size(MAX_COLLECTION_SIZE);
+    engine.set_max_map_size(MAX_COLLECTION_SIZE);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+
+    let queue = actions.clone();
+    engine.register_fn("add_score", move |points: i64| queue.borrow_mut().push(ModAction::AddScore(points)));
+    let queue = actions.clone();
+    engine.register_fn("spawn_dot", move |col: i64, row: i64| {
+        if col >= 0 && row >= 0 {
+            queue.borrow_mut().push(ModAction::SpawnDot(col as usize, row as usize));
+        }
+    });
+    let queue = actions.clone();
+    engine.register_fn("spawn_power_pellet", move |col: i64, row: i64| {
+        if col >= 0 && row >= 0 {
+            queue.borrow_mut().push(ModAction::SpawnPowerPellet(col as usize, row as usize));
+        }
+    });
+    let queue = actions.clone();
+    engine.register_fn("set_pacman_speed", move |factor: f64| {
+        queue.borrow_mut().push(ModAction::PacmanSpeed((factor as f32).clamp(0.0, MAX_SPEED_FACTOR)));
+    });
+    let queue = actions.clone();
+    engine.register_fn("set_ghost_speed", move |factor: f64| {
+        queue.borrow_mut().push(ModAction::GhostSpeed((factor as f32).clamp(0.0, MAX_SPEED_FACTOR)));
+    });
+
+    let queue = actions.clone();
+    engine.register_fn("register_item", move |name: &str, r: i64, g: i64, b: i64, points: i64| {
+        queue.borrow_mut().push(ModAction::RegisterItem {
+            name: name.to_string(),
+            color: rgb(r, g, b),
+            points: points.clamp(0, u32::MAX as i64) as u32,
+        });
+    });
+    let queue = actions.clone();
+    engine.register_fn(
+        "register_hazard",
+        move |name: &str, r: i64, g: i64, b: i64, effect: &str| -> Result<(), Box<EvalAltResult>> {
+            let effect = match effect {
+                "lose_life" => EntityEffect::LoseLife,
+                "slow" => EntityEffect::SlowPacman(SLOW_HAZARD_DURATION),
+                other => return Err(format!("unknown hazard effect '{}'", other).into()),
+            };
+            queue.borrow_mut().push(ModAction::RegisterHazard {
+                name: name.to_string(),
+                color: rgb(r, g, b),
+                effect,
+            });
+            Ok(())
+        },
+    );
+    let queue = actions.clone();
+    engine.register_fn("spawn_entity", move |name: &str, col: i64, row: i64| {
+        if col >= 0 && row >= 0 {
+            queue.borrow_mut().push(ModAction::SpawnEntity(name.to_string(), col as usize, row as usize));
+        }
+    });
+
+    let queue = actions.clone();
+    engine.register_fn("register_command", move |name: &str, help: &str| {
+        queue.borrow_mut().push(ModAction::RegisterCommand {
+            name: name.to_string(),
+            help: help.to_string(),
+        });
+    });
+
+    let state = view.clone();
+    engine.register_fn("score", move || state.borrow().score as i64);
+    let state = view.clone();
+    engine.register_fn("lives", move || state.borrow().lives as i64);
+    let state = view.clone();
+    engine.register_fn("tick", move || state.borrow().tick as i64);
+    let state = view.clone();
+    engine.register_fn("pacman_col", move || state.borrow().pacman_col as i64);
+    let state = view.clone();
+    engine.register_fn("pacman_row", move || state.borrow().pacman_row as i64);
+
+    engine
+}
+
+fn rgb(r: i64, g: i64, b: i64) -> (u8, u8, u8) {
+    (r.clamp(0, 255) as u8, g.clamp(0, 255) as u8, b.clamp(0, 255) as u8)
+}
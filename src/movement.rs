@@ -0,0 +1,36 @@
+//grid movement rules for pacman. Positions are the top-left corner of pacman's box,
+//which sits centered in a cell when he is on the grid
+use crate::collision::{self, WallGrid};
+use crate::fixed::Fixed;
+use crate::{Direction, CELL, PACMAN};
+
+fn offset() -> Fixed {
+    (CELL - PACMAN).half()
+}
+
+//position of the nearest grid cell
+pub fn snap_to_grid(x: Fixed, y: Fixed) -> (Fixed, Fixed) {
+    (CELL * x.div_round(CELL) + offset(), CELL * y.div_round(CELL) + offset())
+}
+
+pub fn is_at_grid_center(x: Fixed, y: Fixed) -> bool {
+    let center_x = CELL * (x - offset()).div_round(CELL) + offset();
+    let center_y = CELL * (y - offset()).div_round(CELL) + offset();
+    let one = Fixed::from_int(1);
+    (x - center_x).abs() < one && (y - center_y).abs() < one
+}
+
+//whether the cell next to the one at (x, y) in `direction` is free for pacman
+pub fn can_move(walls: &WallGrid, x: Fixed, y: Fixed, direction: Direction) -> bool {
+    let (dx, dy) = match direction {
+        Direction::Up => (Fixed::ZERO, -CELL),
+        Direction::Down => (Fixed::ZERO, CELL),
+        Direction::Left => (-CELL, Fixed::ZERO),
+        Direction::Right => (CELL, Fixed::ZERO),
+        Direction::None => (Fixed::ZERO, Fixed::ZERO),
+    };
+
+    //'snap' pacman to a grid cell to allow for smoother grid tracing
+    let (test_x, test_y) = snap_to_grid(x, y);
+    !collision::box_hits_wall(walls, test_x + dx, test_y + dy, PACMAN, PACMAN)
+}
@@ -0,0 +1,490 @@
+//two-player versus over TCP: one player is pacman and the other the red ghost. Both machines
+//run the same deterministic simulation from a shared seed and only exchange inputs. The other
+//player's input is predicted to repeat until it arrives, and a wrong guess rolls the game back
+//to that tick and re-simulates it, so neither side waits on the network. Spectators get the
+//confirmed inputs of both players from the host and simulate the match themselves
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::save::Snapshot;
+use crate::Direction;
+
+//local inputs take effect this many ticks later, which saves rollbacks on short latencies
+const INPUT_DELAY: u32 = 2;
+//how far the game may run ahead of the other player's inputs before it waits for them
+pub const MAX_ROLLBACK: u32 = 30;
+//both sides compare a hash of the game state this often
+const HASH_INTERVAL: u32 = 60;
+const PROTOCOL_VERSION: u32 = 3;
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+//spectators stay this many ticks behind the newest input they have, to ride out hiccups
+const SPECTATOR_DELAY: usize = 120;
+//a spectator that can't keep up for this long is dropped
+const SPECTATOR_WRITE_TIMEOUT: Duration = Duration::from_millis(200);
+
+//one JSON object per line, like the remote-control protocol
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message {
+    Hello {
+        protocol: u32,
+        game_version: String,
+        map_hash: String,
+        seed: u64,
+    },
+    Input {
+        tick: u32,
+        direction: Direction,
+    },
+    //hash of the state at the start of `tick`
+    Hash {
+        tick: u32,
+        hash: u64,
+    },
+    //everything said in the lobby before the match starts
+    Lobby {
+        update: Lobby,
+    },
+    //both players' inputs for a tick, sent to spectators
+    Confirmed {
+        tick: u32,
+        pacman: Direction,
+        ghost: Direction,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Role {
+    Host,
+    Guest,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum Lobby {
+    //the host's choices, which the guest follows
+    Settings { host_plays_pacman: bool },
+    Ready(bool),
+    //the host starts the countdown once both players are ready
+    Start,
+}
+
+pub struct Session {
+    role: Role,
+    //which side controls pacman, the other one has the red ghost
+    pacman: Role,
+    seed: u64,
+    //of the maze both sides play, see replay::map_hash
+    map_hash: String,
+    stream: TcpStream,
+    incoming: Receiver<Result<Message, String>>,
+    //inputs by tick. `remote` only holds the confirmed ones
+    local: Vec<Direction>,
+    remote: Vec<Direction>,
+    //the remote input each simulated tick actually used, confirmed or predicted
+    used_remote: Vec<Direction>,
+    next_hash_tick: u32,
+    local_hashes: HashMap<u32, u64>,
+    remote_hashes: HashMap<u32, u64>,
+    //the host's spectator server and the next tick to pass on to it
+    spectators: Option<Spectators>,
+    broadcast_tick: u32,
+}
+
+impl Session {
+    //waits for a guest to connect and agree on the game
+    pub fn host(addr: &str, seed: u64, map_hash: String) -> Result<Session, String> {
+        let listener = TcpListener::bind(addr).map_err(|e| format!("Could not listen on {}: {}", addr, e))?;
+        println!("Waiting for the other player on {}", addr);
+        let (stream, peer) = listener.accept().map_err(|e| e.to_string())?;
+        println!("{} joined", peer);
+        Session::host_stream(stream, seed, map_hash)
+    }
+
+    //the host's side of a connection a guest just opened
+    pub fn host_stream(stream: TcpStream, seed: u64, map_hash: String) -> Result<Session, String> {
+        Session::connect(Role::Host, stream, seed, map_hash)
+    }
+
+    pub fn join(addr: &str, map_hash: String) -> Result<Session, String> {
+        let stream = TcpStream::connect(addr).map_err(|e| format!("Could not connect to {}: {}", addr, e))?;
+        //the host picks the seed
+        Session::connect(Role::Guest, stream, 0, map_hash)
+    }
+
+    fn connect(role: Role, mut stream: TcpStream, seed: u64, map_hash: String) -> Result<Session, String> {
+        //inputs are tiny and sent every tick, batching them only adds latency
+        let _ = stream.set_nodelay(true);
+        let incoming = spawn_reader(&stream)?;
+        send(
+            &mut stream,
+            &Message::Hello {
+                protocol: PROTOCOL_VERSION,
+                game_version: env!("CARGO_PKG_VERSION").to_string(),
+                map_hash: map_hash.clone(),
+                seed,
+            },
+        )?;
+
+        let seed = match incoming.recv_timeout(HANDSHAKE_TIMEOUT) {
+            Ok(Ok(Message::Hello { protocol, game_version, map_hash: peer_map, seed: peer_seed })) => {
+                if protocol != PROTOCOL_VERSION || game_version != env!("CARGO_PKG_VERSION") {
+                    return Err(format!("The other player runs version {}, this is {}", game_version, env!("CARGO_PKG_VERSION")));
+                }
+                if peer_map != map_hash {
+                    return Err("The other player has a different maze".to_string());
+                }
+                if role == Role::Guest {
+                    peer_seed
+                } else {
+                    seed
+                }
+            }
+            Ok(Ok(_)) => return Err("The other player didn't say hello".to_string()),
+            Ok(Err(e)) => return Err(e),
+            Err(_) => return Err("The other player didn't answer".to_string()),
+        };
+
+        //nobody can have pressed anything during the first ticks
+        let idle = vec![Direction::None; INPUT_DELAY as usize];
+        Ok(Session {
+            role,
+            pacman: Role::Host,
+            seed,
+            map_hash,
+            stream,
+            incoming,
+            local: idle.clone(),
+            remote: idle,
+            used_remote: Vec::new(),
+            next_hash_tick: HASH_INTERVAL,
+            local_hashes: HashMap::new(),
+            remote_hashes: HashMap::new(),
+            spectators: None,
+            broadcast_tick: 0,
+        })
+    }
+
+    //lets clients watch the match from `addr`. Only the host knows every confirmed input first
+    pub fn allow_spectators(&mut self, addr: &str) -> Result<(), String> {
+        let hello = Message::Hello {
+            protocol: PROTOCOL_VERSION,
+            game_version: env!("CARGO_PKG_VERSION").to_string(),
+            map_hash: self.map_hash.clone(),
+            seed: self.seed,
+        };
+        let hello = serde_json::to_string(&hello).map_err(|e| e.to_string())?;
+        self.spectators = Some(Spectators::start(addr, hello)?);
+        println!("Spectators can watch on {}", addr);
+        Ok(())
+    }
+
+    pub fn spectator_count(&self) -> Option<usize> {
+        self.spectators.as_ref().map(|spectators| spectators.count.load(Ordering::Relaxed))
+    }
+
+    pub fn set_host_plays_pacman(&mut self, host_plays_pacman: bool) {
+        self.pacman = if host_plays_pacman { Role::Host } else { Role::Guest };
+    }
+
+    pub fn plays_pacman(&self) -> bool {
+        self.pacman == self.role
+    }
+
+    pub fn send_lobby(&mut self, update: Lobby) -> Result<(), String> {
+        send(&mut self.stream, &Message::Lobby { update })
+    }
+
+    //what the other player said in the lobby since the last call
+    pub fn poll_lobby(&mut self) -> Result<Vec<Lobby>, String> {
+        let mut updates = Vec::new();
+        loop {
+            match self.incoming.try_recv() {
+                Ok(Ok(Message::Lobby { update })) => updates.push(update),
+                Ok(Ok(_)) => return Err("Unexpected message from the other player".to_string()),
+                Ok(Err(e)) => return Err(e),
+                Err(TryRecvError::Empty) => return Ok(updates),
+                Err(TryRecvError::Disconnected) => return Err("The other player left".to_string()),
+            }
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    //number of ticks whose inputs from both players are known
+    pub fn confirmed(&self) -> u32 {
+        self.remote.len() as u32
+    }
+
+    //schedules the local player's input, once per simulated `tick`
+    pub fn send_local(&mut self, tick: u32, direction: Direction) -> Result<(), String> {
+        let scheduled = tick + INPUT_DELAY;
+        if self.local.len() as u32 == scheduled {
+            self.local.push(direction);
+            send(&mut self.stream, &Message::Input { tick: scheduled, direction })?;
+        }
+        Ok(())
+    }
+
+    //the (pacman, ghost) inputs for `tick`, guessing that the other player still holds their
+    //last direction when their input hasn't arrived yet
+    pub fn inputs(&mut self, tick: u32) -> (Direction, Direction) {
+        let local = self.local.get(tick as usize).copied().unwrap_or(Direction::None);
+        let remote = match self.remote.get(tick as usize) {
+            Some(&confirmed) => confirmed,
+            None => self.remote.last().copied().unwrap_or(Direction::None),
+        };
+        self.used_remote.truncate(tick as usize);
+        self.used_remote.push(remote);
+        if self.plays_pacman() {
+            (local, remote)
+        } else {
+            (remote, local)
+        }
+    }
+
+    //reads what the other player sent. Returns the earliest tick that was simulated with a
+    //wrong guess, which the game has to roll back to
+    pub fn poll(&mut self) -> Result<Option<u32>, String> {
+        let mut mispredicted: Option<u32> = None;
+        loop {
+            match self.incoming.try_recv() {
+                Ok(Ok(Message::Input { tick, direction })) => {
+                    if tick != self.confirmed() {
+                        return Err(format!("Expected input for tick {}, got {}", self.confirmed(), tick));
+                    }
+                    self.remote.push(direction);
+                    if self.used_remote.get(tick as usize).is_some_and(|&used| used != direction) {
+                        mispredicted = Some(mispredicted.map_or(tick, |earliest| earliest.min(tick)));
+                    }
+                }
+                Ok(Ok(Message::Hash { tick, hash })) => {
+                    self.remote_hashes.insert(tick, hash);
+                    self.compare_hashes(tick)?;
+                }
+                Ok(Ok(Message::Hello { .. })) => return Err("The other player restarted".to_string()),
+                Ok(Err(e)) => return Err(e),
+                Ok(Ok(_)) => return Err("Unexpected message from the other player".to_string()),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Err("The other player left".to_string()),
+            }
+        }
+
+        if let Some(spectators) = &self.spectators {
+            while self.broadcast_tick < self.confirmed() {
+                let tick = self.broadcast_tick as usize;
+                let (pacman, ghost) = if self.plays_pacman() {
+                    (self.local[tick], self.remote[tick])
+                } else {
+                    (self.remote[tick], self.local[tick])
+                };
+                spectators.send(&Message::Confirmed { tick: self.broadcast_tick, pacman, ghost });
+                self.broadcast_tick += 1;
+            }
+        }
+        Ok(mispredicted)
+    }
+
+    //the next tick whose starting state should be hashed, once all inputs before it are known
+    pub fn pending_hash_tick(&self) -> Option<u32> {
+        if self.next_hash_tick <= self.confirmed() {
+            Some(self.next_hash_tick)
+        } else {
+            None
+        }
+    }
+
+    pub fn record_hash(&mut self, tick: u32, hash: u64) -> Result<(), String> {
+        send(&mut self.stream, &Message::Hash { tick, hash })?;
+        self.local_hashes.insert(tick, hash);
+        self.next_hash_tick = tick + HASH_INTERVAL;
+        self.compare_hashes(tick)
+    }
+
+    //the same inputs must have led to the same state on both machines
+    fn compare_hashes(&mut self, tick: u32) -> Result<(), String> {
+        if let (Some(&local), Some(&remote)) = (self.local_hashes.get(&tick), self.remote_hashes.get(&tick)) {
+            self.local_hashes.remove(&tick);
+            self.remote_hashes.remove(&tick);
+            if local != remote {
+                return Err(format!("Desync detected at tick {} ({:016x} here, {:016x} there)", tick, local, remote));
+            }
+        }
+        Ok(())
+    }
+}
+
+//the host's side of spectating: accepts watchers and writes every confirmed tick to them on
+//threads of its own, like the remote-control server, so a slow watcher never stalls the match
+struct Spectators {
+    lines: Sender<String>,
+    count: Arc<AtomicUsize>,
+}
+
+impl Spectators {
+    fn start(addr: &str, hello: String) -> Result<Spectators, String> {
+        let listener = TcpListener::bind(addr).map_err(|e| format!("Could not listen on {}: {}", addr, e))?;
+        let (client_tx, client_rx) = mpsc::channel::<TcpStream>();
+        let (lines, line_rx) = mpsc::channel::<String>();
+        let count = Arc::new(AtomicUsize::new(0));
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = stream.set_write_timeout(Some(SPECTATOR_WRITE_TIMEOUT));
+                let _ = stream.set_nodelay(true);
+                if client_tx.send(stream).is_err() {
+                    return;
+                }
+            }
+        });
+
+        //late joiners get the whole match so far and catch up on their own
+        let counter = Arc::clone(&count);
+        thread::spawn(move || {
+            let mut history: Vec<String> = Vec::new();
+            let mut clients: Vec<TcpStream> = Vec::new();
+            for line in line_rx {
+                for mut client in client_rx.try_iter() {
+                    let sent = writeln!(client, "{}", hello).is_ok()
+                        && history.iter().all(|old| writeln!(client, "{}", old).is_ok());
+                    if sent {
+                        clients.push(client);
+                    }
+                }
+                clients.retain_mut(|client| writeln!(client, "{}", line).is_ok());
+                history.push(line);
+                counter.store(clients.len(), Ordering::Relaxed);
+            }
+        });
+
+        Ok(Spectators { lines, count })
+    }
+
+    fn send(&self, message: &Message) {
+        if let Ok(json) = serde_json::to_string(message) {
+            let _ = self.lines.send(json);
+        }
+    }
+}
+
+//a read-only client: buffers the host's confirmed inputs and hands them out one tick at a time
+pub struct Spectator {
+    seed: u64,
+    incoming: Receiver<Result<Message, String>>,
+    buffer: VecDeque<(Direction, Direction)>,
+    next_tick: u32,
+    //false while (re)filling the buffer
+    playing: bool,
+    //the host is gone, what's buffered is all there is
+    ended: bool,
+}
+
+impl Spectator {
+    pub fn watch(addr: &str, map_hash: &str) -> Result<Spectator, String> {
+        let stream = TcpStream::connect(addr).map_err(|e| format!("Could not connect to {}: {}", addr, e))?;
+        let incoming = spawn_reader(&stream)?;
+        let seed = match incoming.recv_timeout(HANDSHAKE_TIMEOUT) {
+            Ok(Ok(Message::Hello { protocol, game_version, map_hash: host_map, seed })) => {
+                if protocol != PROTOCOL_VERSION || game_version != env!("CARGO_PKG_VERSION") {
+                    return Err(format!("The host runs version {}, this is {}", game_version, env!("CARGO_PKG_VERSION")));
+                }
+                if host_map != map_hash {
+                    return Err("The host has a different maze".to_string());
+                }
+                seed
+            }
+            Ok(Ok(_)) => return Err("The host didn't say hello".to_string()),
+            Ok(Err(e)) => return Err(e),
+            Err(_) => return Err("The host didn't answer".to_string()),
+        };
+        Ok(Spectator {
+            seed,
+            incoming,
+            buffer: VecDeque::new(),
+            next_tick: 0,
+            playing: false,
+            ended: false,
+        })
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    //the (pacman, ghost) inputs for the next tick, None while buffering. Err once the host
+    //has left and everything it sent has been played
+    pub fn next_inputs(&mut self) -> Result<Option<(Direction, Direction)>, String> {
+        while !self.ended {
+            match self.incoming.try_recv() {
+                Ok(Ok(Message::Confirmed { tick, pacman, ghost })) => {
+                    if tick != self.next_tick {
+                        return Err(format!("Expected tick {} from the host, got {}", self.next_tick, tick));
+                    }
+                    self.buffer.push_back((pacman, ghost));
+                    self.next_tick += 1;
+                }
+                Ok(Ok(_)) => return Err("Unexpected message from the host".to_string()),
+                Ok(Err(_)) | Err(TryRecvError::Disconnected) => self.ended = true,
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+
+        if self.buffer.is_empty() {
+            if self.ended {
+                return Err("The host closed the match".to_string());
+            }
+            self.playing = false;
+        }
+        if !self.playing && (self.buffer.len() >= SPECTATOR_DELAY || self.ended) {
+            self.playing = true;
+        }
+        if self.playing {
+            Ok(self.buffer.pop_front())
+        } else {
+            Ok(None)
+        }
+    }
+
+    //true while the buffer holds much more than the delay, e.g. after joining mid-match
+    pub fn behind(&self) -> bool {
+        self.buffer.len() > 2 * SPECTATOR_DELAY
+    }
+}
+
+//FNV-1a over the serialized snapshot, which covers the rng as well as everything on the board
+pub fn state_hash(snapshot: &Snapshot) -> u64 {
+    let bytes = serde_json::to_vec(snapshot).unwrap_or_default();
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+fn send(stream: &mut TcpStream, message: &Message) -> Result<(), String> {
+    let json = serde_json::to_string(message).map_err(|e| e.to_string())?;
+    writeln!(stream, "{}", json).map_err(|e| format!("Connection lost: {}", e))
+}
+
+//parses lines on a thread so the game never blocks on the socket
+fn spawn_reader(stream: &TcpStream) -> Result<Receiver<Result<Message, String>>, String> {
+    let reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in reader.lines() {
+            let message = match line {
+                Ok(line) => serde_json::from_str(&line).map_err(|e| format!("Bad message from the other player: {}", e)),
+                Err(e) => Err(format!("Connection lost: {}", e)),
+            };
+            let failed = message.is_err();
+            if sender.send(message).is_err() || failed {
+                return;
+            }
+        }
+    });
+    Ok(receiver)
+}
@@ -0,0 +1,56 @@
+//push notifications for code embedding the game, e.g. stream overlays, analytics or trainers.
+//Register an Observer with `PacmanEnv::add_observer` or `run_with_observers` and it is called
+//after every tick, instead of polling the state or changing the update loop:
+//
+//    struct Deaths(u32);
+//    impl Observer for Deaths {
+//        fn on_event(&mut self, event: &GameEvent) {
+//            if let GameEvent::LifeLost { .. } = event {
+//                self.0 += 1;
+//            }
+//        }
+//    }
+//
+//Observers only watch: they get copies of the state, so they can't change how a game plays out
+use crate::Direction;
+
+//positions are in tile units, like `env::Observation`
+#[derive(Clone, Debug, PartialEq)]
+pub struct GhostView {
+    pub x: f32,
+    pub y: f32,
+    pub vulnerable: bool,
+    //eyes on their way home, or waiting to respawn
+    pub in_play: bool,
+}
+
+//the state after a tick
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameView {
+    pub tick: u32,
+    pub seed: u64,
+    pub score: u32,
+    pub lives: i32,
+    pub pacman: (f32, f32),
+    pub direction: Direction,
+    pub ghosts: Vec<GhostView>,
+    pub dots_left: usize,
+    pub power_pellet_timer: f32,
+    pub game_over: bool,
+}
+
+//something that happened during a tick. Each tick's events arrive before its `on_tick`
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    LevelStart { level: u32 },
+    PelletEaten { col: usize, row: usize, power: bool },
+    GhostEaten { ghost: usize },
+    LifeLost { lives_left: i32 },
+    LevelComplete { level: u32 },
+    GameOver { score: u32 },
+}
+
+pub trait Observer {
+    fn on_tick(&mut self, _view: &GameView) {}
+    fn on_event(&mut self, _event: &GameEvent) {}
+}
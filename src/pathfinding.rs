@@ -0,0 +1,201 @@
+//A* over the maze's open tiles, for ghosts finding their way to a target instead of heading
+//whichever way is closest as the crow flies, which walks them into dead ends. The graph is built
+//once with the walls, and the search reuses buffers sized for it so steering never allocates
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::Mutex;
+
+use crate::collision::{Tunnels, WallGrid};
+use crate::Direction;
+
+//the four ways out of a tile, as the column and row steps
+const NEIGHBOURS: [(Direction, i64, i64); 4] =
+    [(Direction::Up, 0, -1), (Direction::Down, 0, 1), (Direction::Left, -1, 0), (Direction::Right, 1, 0)];
+
+//the maze's open tiles and how they join up
+#[derive(Default)]
+pub struct TileGraph {
+    width: usize,
+    height: usize,
+    walkable: Vec<bool>,
+    //whether stepping off an edge comes back in on the far side
+    wrap: bool,
+    //or only off the ends of the side tunnels
+    tunnels: Tunnels,
+    //spare working state for searches, one per search running at once on different threads
+    scratch: Mutex<Vec<Scratch>>,
+}
+
+//one search's working state. A tile's entries only count when its stamp is the current search's,
+//so nothing has to be cleared in between
+#[derive(Default)]
+struct Scratch {
+    search: u32,
+    stamp: Vec<u32>,
+    cost: Vec<u32>,
+    parent: Vec<u32>,
+    closed: Vec<bool>,
+    //(cost plus estimate, estimate, tile). Every tile is closed once and pushes at most its four
+    //neighbours, so the capacity given up front is never outgrown
+    open: BinaryHeap<Reverse<(u32, u32, u32)>>,
+}
+
+impl Scratch {
+    fn new(tiles: usize) -> Self {
+        Scratch {
+            search: 0,
+            stamp: vec![0; tiles],
+            cost: vec![0; tiles],
+            parent: vec![0; tiles],
+            closed: vec![false; tiles],
+            open: BinaryHeap::with_capacity(4 * tiles + 1),
+        }
+    }
+}
+
+impl Clone for TileGraph {
+    fn clone(&self) -> Self {
+        TileGraph::from_cells(self.width, self.height, self.walkable.clone(), self.wrap, self.tunnels.clone())
+    }
+}
+
+impl TileGraph {
+    //the open tiles of `walls`, which wrap or lead through tunnels if the walls do
+    pub fn new(walls: &WallGrid) -> Self {
+        let (width, height) = walls.size();
+        let walkable = (0..height)
+            .flat_map(|row| (0..width).map(move |col| (col as i64, row as i64)))
+            .map(|(col, row)| !walls.is_wall(col, row))
+            .collect();
+        TileGraph::from_cells(width, height, walkable, walls.wrap_size().is_some(), walls.tunnels().clone())
+    }
+
+    fn from_cells(width: usize, height: usize, walkable: Vec<bool>, wrap: bool, tunnels: Tunnels) -> Self {
+        let scratch = Scratch::new(width * height);
+        TileGraph { width, height, walkable, wrap, tunnels, scratch: Mutex::new(vec![scratch]) }
+    }
+
+    //the way to take out of `from` along a shortest path to `to`, both as (column, row). A target
+    //that is a wall, off the maze or cut off is swapped for the reachable tile nearest to it.
+    //None when `from` isn't an open tile or is already the best there is
+    pub fn first_step(&self, from: (i64, i64), to: (i64, i64)) -> Option<Direction> {
+        let start = self.index(from)?;
+        if !self.walkable[start] {
+            return None;
+        }
+        let goal = self.clamp(to);
+        //a spare scratch if there is one, a new one when every one is in use elsewhere
+        let spare = self.scratch.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).pop();
+        let mut scratch = spare.unwrap_or_else(|| Scratch::new(self.width * self.height));
+        let step = self.search(&mut scratch, start, goal);
+        self.scratch.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(scratch);
+        step
+    }
+
+    fn search(&self, scratch: &mut Scratch, start: usize, goal: (i64, i64)) -> Option<Direction> {
+        let Scratch { search, stamp, cost, parent, closed, open } = scratch;
+        *search = search.wrapping_add(1);
+        if *search == 0 {
+            //every stamp is stale again after wrapping round
+            stamp.iter_mut().for_each(|stamp| *stamp = 0);
+            *search = 1;
+        }
+        open.clear();
+
+        stamp[start] = *search;
+        cost[start] = 0;
+        parent[start] = start as u32;
+        closed[start] = false;
+        let start_estimate = self.estimate(start, goal);
+        open.push(Reverse((start_estimate, start_estimate, start as u32)));
+        //the closest tile to the goal reached so far, nearest the start on ties
+        let mut best = (start_estimate, 0, start);
+
+        while let Some(Reverse((_, estimate, tile))) = open.pop() {
+            let tile = tile as usize;
+            if closed[tile] {
+                continue;
+            }
+            closed[tile] = true;
+            if (estimate, cost[tile]) < (best.0, best.1) {
+                best = (estimate, cost[tile], tile);
+            }
+            if estimate == 0 {
+                break;
+            }
+            for &(_, dcol, drow) in &NEIGHBOURS {
+                let next = match self.step(tile, dcol, drow) {
+                    Some(next) if self.walkable[next] => next,
+                    _ => continue,
+                };
+                let next_cost = cost[tile] + 1;
+                if stamp[next] == *search && (closed[next] || cost[next] <= next_cost) {
+                    continue;
+                }
+                stamp[next] = *search;
+                cost[next] = next_cost;
+                parent[next] = tile as u32;
+                closed[next] = false;
+                let next_estimate = self.estimate(next, goal);
+                open.push(Reverse((next_cost + next_estimate, next_estimate, next as u32)));
+            }
+        }
+
+        //walk back from the best tile to the one just after the start
+        let mut tile = best.2;
+        if tile == start {
+            return None;
+        }
+        while parent[tile] as usize != start {
+            tile = parent[tile] as usize;
+        }
+        NEIGHBOURS
+            .iter()
+            .find(|&&(_, dcol, drow)| self.step(start, dcol, drow) == Some(tile))
+            .map(|&(direction, _, _)| direction)
+    }
+
+    fn index(&self, (col, row): (i64, i64)) -> Option<usize> {
+        let (col, row) = if self.wrap && self.width > 0 && self.height > 0 {
+            (col.rem_euclid(self.width as i64), row.rem_euclid(self.height as i64))
+        } else {
+            self.tunnels.wrap_cell(col, row)
+        };
+        if col < 0 || row < 0 || col as usize >= self.width || row as usize >= self.height {
+            return None;
+        }
+        Some(row as usize * self.width + col as usize)
+    }
+
+    //`tile` on the grid, pulled in from beyond an edge
+    fn clamp(&self, (col, row): (i64, i64)) -> (i64, i64) {
+        match self.index((col, row)) {
+            Some(index) => ((index % self.width) as i64, (index / self.width) as i64),
+            None => (
+                col.clamp(0, self.width as i64 - 1),
+                row.clamp(0, self.height as i64 - 1),
+            ),
+        }
+    }
+
+    //the tile one step from `tile`, if there is one
+    fn step(&self, tile: usize, dcol: i64, drow: i64) -> Option<usize> {
+        let (col, row) = ((tile % self.width) as i64, (tile / self.width) as i64);
+        self.index((col + dcol, row + drow))
+    }
+
+    //steps from `tile` to `goal` with no walls in the way, the long way round an edge or not. With
+    //tunnels only some rows and columns go round, but the shorter way still never overestimates
+    fn estimate(&self, tile: usize, goal: (i64, i64)) -> u32 {
+        let (col, row) = ((tile % self.width) as i64, (tile / self.width) as i64);
+        let along = |delta: i64, span: usize| {
+            let delta = delta.abs();
+            if self.wrap || self.tunnels.leads_anywhere() {
+                delta.min(span as i64 - delta)
+            } else {
+                delta
+            }
+        };
+        (along(goal.0 - col, self.width) + along(goal.1 - row, self.height)) as u32
+    }
+}
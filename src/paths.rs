@@ -0,0 +1,109 @@
+//platform-correct locations for settings and game data
+use directories::{BaseDirs, ProjectDirs};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "", "rust-pacman")
+}
+
+//falls back to the working directory when the platform has no home directory
+pub fn config_dir() -> PathBuf {
+    project_dirs().map_or_else(|| PathBuf::from("."), |dirs| dirs.config_dir().to_path_buf())
+}
+
+pub fn data_dir() -> PathBuf {
+    project_dirs().map_or_else(|| PathBuf::from("."), |dirs| dirs.data_dir().to_path_buf())
+}
+
+pub fn settings_file() -> PathBuf {
+    config_dir().join("settings.toml")
+}
+
+pub fn speeds_file() -> PathBuf {
+    config_dir().join("speeds.toml")
+}
+
+pub fn saves_dir() -> PathBuf {
+    data_dir().join("saves")
+}
+
+pub fn replays_dir() -> PathBuf {
+    data_dir().join("replays")
+}
+
+pub fn stats_dir() -> PathBuf {
+    data_dir().join("stats")
+}
+
+pub fn maps_dir() -> PathBuf {
+    data_dir().join("maps")
+}
+
+pub fn gallery_dir() -> PathBuf {
+    data_dir().join("gallery")
+}
+
+pub fn logs_dir() -> PathBuf {
+    data_dir().join("logs")
+}
+
+pub fn mods_dir() -> PathBuf {
+    data_dir().join("mods")
+}
+
+pub fn scores_db() -> PathBuf {
+    data_dir().join("scores.db")
+}
+
+//creates the directories on first run and moves over files older versions kept in
+//the working directory (or, for settings, directly in the OS config directory)
+pub fn init() {
+    for dir in [config_dir(), saves_dir(), replays_dir(), stats_dir(), maps_dir(), mods_dir(), gallery_dir(), logs_dir()].iter() {
+        if let Err(e) = fs::create_dir_all(dir) {
+            eprintln!("Could not create {}: {}", dir.display(), e);
+        }
+    }
+
+    let mut legacy = vec![
+        (PathBuf::from("saves"), saves_dir()),
+        (PathBuf::from("replays"), replays_dir()),
+        (PathBuf::from("stats"), stats_dir()),
+        (PathBuf::from("scores.db"), scores_db()),
+    ];
+    if let Some(base) = BaseDirs::new() {
+        legacy.push((base.config_dir().join("rust-pacman").join("settings.toml"), settings_file()));
+    }
+    for (from, to) in legacy {
+        if from.exists() && from != to {
+            match migrate(&from, &to) {
+                Ok(()) => println!("Moved {} to {}", from.display(), to.display()),
+                Err(e) => eprintln!("Could not move {} to {}: {}", from.display(), to.display(), e),
+            }
+        }
+    }
+}
+
+//moves a file, or every file in a directory, without overwriting anything already there
+fn migrate(from: &Path, to: &Path) -> io::Result<()> {
+    if from.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            migrate(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        //only removed once empty, e.g. if the user keeps other files there
+        let _ = fs::remove_dir(from);
+        return Ok(());
+    }
+    if to.exists() {
+        return Ok(());
+    }
+    //rename fails across filesystems, fall back to copying
+    if fs::rename(from, to).is_err() {
+        fs::copy(from, to)?;
+        fs::remove_file(from)?;
+    }
+    Ok(())
+}
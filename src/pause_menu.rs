@@ -0,0 +1,104 @@
+//the menu over a paused game: buttons to resume, start the game over or quit to the title
+//screen. The save slots, photo mode and the like keep keys of their own, listed under the buttons
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+
+use crate::widgets::ButtonGrid;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PauseAction {
+    None,
+    Resume,
+    Restart,
+    Quit,
+}
+
+pub struct PauseMenu {
+    buttons: ButtonGrid<PauseAction>,
+}
+
+impl PauseMenu {
+    //a versus game can't be started over by one side, so it has no Restart
+    pub fn new(can_restart: bool) -> Self {
+        let mut buttons = vec![(PauseAction::Resume, "Resume")];
+        if can_restart {
+            buttons.push((PauseAction::Restart, "Restart"));
+        }
+        buttons.push((PauseAction::Quit, "Quit"));
+        let columns = buttons.len();
+        PauseMenu { buttons: ButtonGrid::new(buttons, columns) }
+    }
+
+    //the buttons' top-left corner in a window of size (w, h), centered a little above the middle
+    fn buttons_origin(&self, w: f32, h: f32) -> (f32, f32) {
+        ((w - self.buttons.size().0) / 2.0, h / 2.0 - 30.0)
+    }
+
+    //Escape and P resume straight away, wherever the selection is
+    pub fn handle_key(&mut self, keycode: KeyCode) -> PauseAction {
+        if matches!(keycode, KeyCode::Escape | KeyCode::P) {
+            return PauseAction::Resume;
+        }
+        self.buttons.handle_key(keycode).unwrap_or(PauseAction::None)
+    }
+
+    pub fn click(&mut self, ctx: &Context, x: f32, y: f32) -> PauseAction {
+        let (w, h) = graphics::drawable_size(ctx);
+        self.buttons.click(self.buttons_origin(w, h), x, y).unwrap_or(PauseAction::None)
+    }
+
+    //dims the game, with the keys in `hint` under the buttons and `message`, such as a save's
+    //outcome, under those
+    pub fn draw(&self, ctx: &mut Context, hint: &str, message: Option<&str>) -> GameResult {
+        let (w, h) = graphics::drawable_size(ctx);
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, 0.0, w, h),
+            Color::new(0.0, 0.0, 0.0, 0.7),
+        )?;
+        graphics::draw(ctx, &background, DrawParam::default())?;
+
+        let paused_text = graphics::Text::new("PAUSED");
+        let paused_dims = paused_text.dimensions(ctx);
+        graphics::draw(
+            ctx,
+            &paused_text,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 {
+                    x: (w - paused_dims.w * 2.0) / 2.0,
+                    y: h / 2.0 - 90.0,
+                })
+                .color(Color::YELLOW)
+                .scale([2.0, 2.0]),
+        )?;
+
+        let origin = self.buttons_origin(w, h);
+        self.buttons.draw(ctx, origin)?;
+        let below = origin.1 + self.buttons.size().1;
+
+        let hint_text = graphics::Text::new(hint);
+        let hint_dims = hint_text.dimensions(ctx);
+        graphics::draw(
+            ctx,
+            &hint_text,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: (w - hint_dims.w) / 2.0, y: below + 20.0 })
+                .color(Color::WHITE),
+        )?;
+
+        if let Some(message) = message {
+            let message_text = graphics::Text::new(message);
+            let message_dims = message_text.dimensions(ctx);
+            graphics::draw(
+                ctx,
+                &message_text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 { x: (w - message_dims.w) / 2.0, y: below + 50.0 })
+                    .color(Color::GREEN),
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+//the power pellets of a level. Every spot the level places one on remembers whether it was
+//eaten, so resetting puts back exactly the level's own pellets and nothing a mod dropped in
+use ggez::mint::Point2;
+
+use crate::fixed::Fixed;
+use crate::maze::Layout;
+use crate::CELL_SIZE;
+
+#[derive(Clone, Copy)]
+pub struct Slot {
+    position: Point2<f32>,
+    collected: bool,
+    //added during play rather than by the level, gone on the next reset
+    spawned: bool,
+}
+
+pub struct PowerPellets {
+    slots: Vec<Slot>,
+}
+
+type Live<'a> = std::iter::FilterMap<std::slice::Iter<'a, Slot>, fn(&'a Slot) -> Option<&'a Point2<f32>>>;
+
+impl PowerPellets {
+    //one pellet near each corner of the maze, on the open tile closest to the corner's
+    //inner cell when that one is blocked
+    pub fn for_maze(layout: &Layout) -> Self {
+        let (width, height) = (layout.width(), layout.height());
+        let mut slots: Vec<Slot> = Vec::new();
+        if width >= 2 && height >= 2 {
+            for corner in [(1, 1), (width - 2, 1), (1, height - 2), (width - 2, height - 2)] {
+                if let Some((col, row)) = layout.nearest_open(corner) {
+                    let position = Point2 {
+                        x: col as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+                        y: row as f32 * CELL_SIZE + CELL_SIZE / 2.0,
+                    };
+                    //small mazes can send two corners to the same tile
+                    if !slots.iter().any(|slot| slot.position == position) {
+                        slots.push(Slot { position, collected: false, spawned: false });
+                    }
+                }
+            }
+        }
+        PowerPellets { slots }
+    }
+
+    //for mazes whose rules leave power pellets out
+    pub fn none() -> Self {
+        PowerPellets { slots: Vec::new() }
+    }
+
+    //a pellet that isn't part of the level, e.g. one a mod placed
+    pub fn add(&mut self, position: Point2<f32>) {
+        self.slots.push(Slot { position, collected: false, spawned: true });
+    }
+
+    //back to the level's own pellets, all uneaten
+    pub fn reset(&mut self) {
+        self.slots.retain(|slot| !slot.spawned);
+        for slot in &mut self.slots {
+            slot.collected = false;
+        }
+    }
+
+    //the pellets left at `live`, as a snapshot lists them. Level pellets missing from the list
+    //were eaten, listed spots the level doesn't have were added during play
+    pub fn restore(&mut self, live: &[(f32, f32)]) {
+        self.reset();
+        for slot in &mut self.slots {
+            slot.collected = !live.iter().any(|&(x, y)| slot.position.x == x && slot.position.y == y);
+        }
+        for &(x, y) in live {
+            if !self.slots.iter().any(|slot| slot.position.x == x && slot.position.y == y) {
+                self.add(Point2 { x, y });
+            }
+        }
+    }
+
+    //the pellets still there
+    pub fn iter(&self) -> Live<'_> {
+        let live: fn(&Slot) -> Option<&Point2<f32>> =
+            |slot| if slot.collected { None } else { Some(&slot.position) };
+        self.slots.iter().filter_map(live)
+    }
+
+    //marks every pellet closer than `reach` to `center` as eaten, handing each to `eaten`
+    pub fn eat_within(&mut self, center: (Fixed, Fixed), reach: Fixed, mut eaten: impl FnMut(Point2<f32>)) {
+        for slot in self.slots.iter_mut().filter(|slot| !slot.collected) {
+            let distance_squared = (center.0 - Fixed::from_f32(slot.position.x)).squared() +
+                                   (center.1 - Fixed::from_f32(slot.position.y)).squared();
+            if distance_squared < reach.squared() {
+                slot.collected = true;
+                eaten(slot.position);
+            }
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a PowerPellets {
+    type Item = &'a Point2<f32>;
+    type IntoIter = Live<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
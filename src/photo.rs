@@ -0,0 +1,222 @@
+//photo mode, opened from the pause screen: the HUD goes away, the camera pans and zooms freely
+//over the frozen board, the walls and colours can be restyled, and captures are saved to the
+//gallery at a multiple of the window's resolution
+use ggez::graphics::{self, BlendMode, Canvas, Color, DrawParam, Drawable};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+use std::path::PathBuf;
+
+use crate::memento;
+use crate::paths;
+
+const MIN_ZOOM: f32 = 1.0;
+const MAX_ZOOM: f32 = 4.0;
+const ZOOM_STEP: f32 = 1.25;
+//how far one press of an arrow pans, in window pixels whatever the zoom
+const PAN_STEP: f32 = 20.0;
+//captures are this many times the window's size
+const CAPTURE_SCALE: u16 = 2;
+
+#[derive(Clone, Copy)]
+pub enum Theme {
+    Classic,
+    Neon,
+    Mono,
+}
+
+impl Theme {
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Classic => "Classic",
+            Theme::Neon => "Neon",
+            Theme::Mono => "Mono",
+        }
+    }
+
+    fn next(self) -> Theme {
+        match self {
+            Theme::Classic => Theme::Neon,
+            Theme::Neon => Theme::Mono,
+            Theme::Mono => Theme::Classic,
+        }
+    }
+
+    pub fn walls(self) -> Color {
+        match self {
+            Theme::Classic => Color::new(0.0, 0.0, 1.0, 1.0),
+            Theme::Neon => Color::new(1.0, 0.2, 0.8, 1.0),
+            Theme::Mono => Color::new(0.6, 0.6, 0.6, 1.0),
+        }
+    }
+}
+
+//a colour the finished picture is multiplied by
+#[derive(Clone, Copy)]
+enum Filter {
+    None,
+    Warm,
+    Cool,
+    Night,
+}
+
+impl Filter {
+    fn name(self) -> &'static str {
+        match self {
+            Filter::None => "None",
+            Filter::Warm => "Warm",
+            Filter::Cool => "Cool",
+            Filter::Night => "Night",
+        }
+    }
+
+    fn next(self) -> Filter {
+        match self {
+            Filter::None => Filter::Warm,
+            Filter::Warm => Filter::Cool,
+            Filter::Cool => Filter::Night,
+            Filter::Night => Filter::None,
+        }
+    }
+
+    fn tint(self) -> Option<Color> {
+        match self {
+            Filter::None => None,
+            Filter::Warm => Some(Color::new(1.0, 0.85, 0.6, 1.0)),
+            Filter::Cool => Some(Color::new(0.6, 0.8, 1.0, 1.0)),
+            Filter::Night => Some(Color::new(0.35, 0.4, 0.7, 1.0)),
+        }
+    }
+}
+
+//what the caller should do after a key press
+pub enum PhotoAction {
+    None,
+    Close,
+}
+
+pub struct PhotoMode {
+    //the maze point in the middle of the window
+    center: (f32, f32),
+    zoom: f32,
+    pub theme: Theme,
+    filter: Filter,
+    hint_hidden: bool,
+    //set by the capture key, the next frame renders and saves the picture
+    pub pending_capture: bool,
+}
+
+impl PhotoMode {
+    //starts looking at the middle of a maze `size` big
+    pub fn new(size: (f32, f32)) -> Self {
+        PhotoMode {
+            center: (size.0 / 2.0, size.1 / 2.0),
+            zoom: MIN_ZOOM,
+            theme: Theme::Classic,
+            filter: Filter::None,
+            hint_hidden: false,
+            pending_capture: false,
+        }
+    }
+
+    pub fn handle_key(&mut self, keycode: KeyCode) -> PhotoAction {
+        let pan = PAN_STEP / self.zoom;
+        match keycode {
+            KeyCode::Escape => return PhotoAction::Close,
+            KeyCode::Up => self.center.1 -= pan,
+            KeyCode::Down => self.center.1 += pan,
+            KeyCode::Left => self.center.0 -= pan,
+            KeyCode::Right => self.center.0 += pan,
+            KeyCode::Equals | KeyCode::Plus | KeyCode::NumpadAdd => self.zoom = (self.zoom * ZOOM_STEP).min(MAX_ZOOM),
+            KeyCode::Minus | KeyCode::NumpadSubtract => self.zoom = (self.zoom / ZOOM_STEP).max(MIN_ZOOM),
+            KeyCode::T => self.theme = self.theme.next(),
+            KeyCode::F => self.filter = self.filter.next(),
+            KeyCode::H => self.hint_hidden = !self.hint_hidden,
+            KeyCode::Return | KeyCode::Space => self.pending_capture = true,
+            _ => {}
+        }
+        PhotoAction::None
+    }
+
+    //from maze coordinates to a window of size (w, h), through the camera
+    pub fn camera_transform(&self, w: f32, h: f32) -> ggez::mint::ColumnMatrix4<f32> {
+        DrawParam::default()
+            .dest(ggez::mint::Point2 {
+                x: w / 2.0 - self.center.0 * self.zoom,
+                y: h / 2.0 - self.center.1 * self.zoom,
+            })
+            .scale(ggez::mint::Vector2 { x: self.zoom, y: self.zoom })
+            .trans
+            .to_bare_matrix()
+    }
+
+    //multiplies everything drawn so far by the filter's colour
+    pub fn draw_filter(&self, ctx: &mut Context) -> GameResult {
+        let tint = match self.filter.tint() {
+            Some(tint) => tint,
+            None => return Ok(()),
+        };
+        let (w, h) = graphics::drawable_size(ctx);
+        let mut overlay = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, 0.0, w, h),
+            tint,
+        )?;
+        overlay.set_blend_mode(Some(BlendMode::Multiply));
+        graphics::draw(ctx, &overlay, DrawParam::default())
+    }
+
+    //the controls along the bottom, unless hidden
+    pub fn draw_hint(&self, ctx: &mut Context) -> GameResult {
+        if self.hint_hidden {
+            return Ok(());
+        }
+        let (w, h) = graphics::drawable_size(ctx);
+        let mut hint = graphics::Text::new(format!(
+            "Arrows: pan   +/-: zoom   T: theme ({})   F: filter ({})\nEnter: capture   H: hide this   Esc: back",
+            self.theme.name(),
+            self.filter.name()
+        ));
+        hint.set_bounds(ggez::mint::Point2 { x: w, y: f32::INFINITY }, graphics::Align::Center);
+        let dims = hint.dimensions(ctx);
+        let band = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, h - dims.h - 16.0, w, dims.h + 16.0),
+            Color::new(0.0, 0.0, 0.0, 0.7),
+        )?;
+        graphics::draw(ctx, &band, DrawParam::default())?;
+        graphics::draw(
+            ctx,
+            &hint,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: 0.0, y: h - dims.h - 8.0 })
+                .color(Color::WHITE),
+        )
+    }
+}
+
+//renders `draw_scene` at CAPTURE_SCALE times the window's size and saves it to the gallery. The
+//scene is drawn in window coordinates, which the bigger canvas stretches over its pixels
+pub fn capture<F>(ctx: &mut Context, draw_scene: F) -> Result<PathBuf, String>
+where
+    F: FnOnce(&mut Context) -> GameResult,
+{
+    let (w, h) = graphics::drawable_size(ctx);
+    let canvas = Canvas::new(
+        ctx,
+        w as u16 * CAPTURE_SCALE,
+        h as u16 * CAPTURE_SCALE,
+        ggez::conf::NumSamples::One,
+        graphics::get_window_color_format(ctx),
+    )
+    .map_err(|e| e.to_string())?;
+    graphics::set_canvas(ctx, Some(&canvas));
+    let drawn = draw_scene(ctx);
+    graphics::set_canvas(ctx, None);
+    drawn.map_err(|e| e.to_string())?;
+
+    let path = paths::gallery_dir().join(format!("photo-{}.png", chrono::Local::now().format("%Y-%m-%d_%H-%M-%S")));
+    memento::write_png(ctx, &canvas, &path)?;
+    Ok(path)
+}
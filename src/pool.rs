@@ -0,0 +1,73 @@
+//a fixed-size pool for short-lived objects. Dead slots are kept and handed out again, along with
+//whatever buffers they own, so spawning and expiring effects doesn't touch the allocator once the
+//pool has warmed up
+pub struct Pool<T> {
+    //the first `live` slots are in use, the rest are free for reuse
+    slots: Vec<T>,
+    live: usize,
+    capacity: usize,
+    peak: usize,
+    //spawns turned away because every slot was taken
+    dropped: u64,
+}
+
+#[derive(Clone, Copy)]
+pub struct PoolStats {
+    pub live: usize,
+    pub allocated: usize,
+    pub capacity: usize,
+    pub peak: usize,
+    pub dropped: u64,
+}
+
+impl<T: Default> Pool<T> {
+    pub fn new(capacity: usize) -> Self {
+        Pool { slots: Vec::with_capacity(capacity), live: 0, capacity, peak: 0, dropped: 0 }
+    }
+
+    //fills a free slot with `init`. Returns false, dropping the spawn, when the pool is full
+    pub fn spawn(&mut self, init: impl FnOnce(&mut T)) -> bool {
+        if self.live == self.slots.len() {
+            if self.slots.len() == self.capacity {
+                self.dropped += 1;
+                return false;
+            }
+            self.slots.push(T::default());
+        }
+        init(&mut self.slots[self.live]);
+        self.live += 1;
+        self.peak = self.peak.max(self.live);
+        true
+    }
+
+    //keeps the objects `keep` returns true for. Order isn't preserved
+    pub fn retain(&mut self, mut keep: impl FnMut(&mut T) -> bool) {
+        let mut i = 0;
+        while i < self.live {
+            if keep(&mut self.slots[i]) {
+                i += 1;
+            } else {
+                self.live -= 1;
+                self.slots.swap(i, self.live);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.live = 0;
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.slots[..self.live].iter()
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        PoolStats {
+            live: self.live,
+            allocated: self.slots.len(),
+            capacity: self.capacity,
+            peak: self.peak,
+            dropped: self.dropped,
+        }
+    }
+}
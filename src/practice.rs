@@ -0,0 +1,151 @@
+//practice setup screen: pick a level, how many ghosts, infinite lives and a frozen power pellet
+//timer, then play without anything going into the history or the replays folder
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+
+//the arcade's speeds stop changing after this level
+const MAX_LEVEL: u32 = 21;
+
+const LEVEL_ROW: usize = 0;
+const GHOSTS_ROW: usize = 1;
+const LIVES_ROW: usize = 2;
+const PELLETS_ROW: usize = 3;
+const START_ROW: usize = 4;
+const ROW_COUNT: usize = START_ROW + 1;
+
+#[derive(Clone, Copy)]
+pub struct PracticeOptions {
+    pub level: u32,
+    pub ghosts: usize,
+    pub infinite_lives: bool,
+    //the power pellet and the ghosts' fright never run out
+    pub freeze_pellets: bool,
+}
+
+//what the caller should do after a key press
+pub enum PracticeAction {
+    None,
+    Close,
+    Start(PracticeOptions),
+}
+
+pub struct PracticeScreen {
+    selected: usize,
+    options: PracticeOptions,
+    //the most ghosts the maze has
+    max_ghosts: usize,
+}
+
+impl PracticeScreen {
+    pub fn new(max_ghosts: usize) -> Self {
+        PracticeScreen {
+            selected: START_ROW,
+            options: PracticeOptions {
+                level: 1,
+                ghosts: max_ghosts,
+                infinite_lives: true,
+                freeze_pellets: false,
+            },
+            max_ghosts,
+        }
+    }
+
+    //Left and Right step the numbers, Enter toggles the switches and starts
+    pub fn handle_key(&mut self, keycode: KeyCode) -> PracticeAction {
+        let options = &mut self.options;
+        match keycode {
+            KeyCode::Escape => return PracticeAction::Close,
+            KeyCode::Up => self.selected = (self.selected + ROW_COUNT - 1) % ROW_COUNT,
+            KeyCode::Down => self.selected = (self.selected + 1) % ROW_COUNT,
+            KeyCode::Left if self.selected == LEVEL_ROW => options.level = (options.level - 1).max(1),
+            KeyCode::Right | KeyCode::Return if self.selected == LEVEL_ROW => {
+                options.level = options.level % MAX_LEVEL + 1;
+            }
+            KeyCode::Left if self.selected == GHOSTS_ROW => options.ghosts = options.ghosts.saturating_sub(1),
+            KeyCode::Right | KeyCode::Return if self.selected == GHOSTS_ROW => {
+                options.ghosts = (options.ghosts + 1) % (self.max_ghosts + 1);
+            }
+            KeyCode::Left | KeyCode::Right | KeyCode::Return if self.selected == LIVES_ROW => {
+                options.infinite_lives = !options.infinite_lives;
+            }
+            KeyCode::Left | KeyCode::Right | KeyCode::Return if self.selected == PELLETS_ROW => {
+                options.freeze_pellets = !options.freeze_pellets;
+            }
+            KeyCode::Return if self.selected == START_ROW => return PracticeAction::Start(self.options),
+            _ => {}
+        }
+        PracticeAction::None
+    }
+
+    pub fn draw(&self, ctx: &mut Context) -> GameResult {
+        let (w, h) = graphics::drawable_size(ctx);
+
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, 0.0, w, h),
+            Color::new(0.0, 0.0, 0.0, 0.85),
+        )?;
+        graphics::draw(ctx, &background, DrawParam::default())?;
+
+        let menu_width = 360.0;
+        let menu_height = 300.0;
+        let menu_x = (w - menu_width) / 2.0;
+        let menu_y = (h - menu_height) / 2.0;
+        let menu_bg = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(menu_x, menu_y, menu_width, menu_height),
+            Color::new(0.2, 0.2, 0.2, 1.0),
+        )?;
+        graphics::draw(ctx, &menu_bg, DrawParam::default())?;
+
+        let title = graphics::Text::new("Practice");
+        let title_dims = title.dimensions(ctx);
+        graphics::draw(
+            ctx,
+            &title,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 {
+                    x: menu_x + (menu_width - title_dims.w * 2.0) / 2.0,
+                    y: menu_y + 20.0,
+                })
+                .color(Color::YELLOW)
+                .scale([2.0, 2.0]),
+        )?;
+
+        let options = &self.options;
+        let rows = [
+            format!("{:<10}{}", "Level", options.level),
+            format!("{:<10}{}", "Ghosts", options.ghosts),
+            format!("{:<10}{}", "Lives", if options.infinite_lives { "Infinite" } else { "3" }),
+            format!("{:<10}{}", "Pellets", if options.freeze_pellets { "Frozen" } else { "Normal" }),
+            "Start".to_string(),
+        ];
+        for (i, row) in rows.iter().enumerate() {
+            let color = if i == self.selected { Color::YELLOW } else { Color::WHITE };
+            let text = graphics::Text::new(row.as_str());
+            graphics::draw(
+                ctx,
+                &text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 {
+                        x: menu_x + 40.0,
+                        y: menu_y + 80.0 + i as f32 * 30.0,
+                    })
+                    .color(color),
+            )?;
+        }
+
+        let hint = graphics::Text::new("Left/Right: change   Esc: back");
+        graphics::draw(
+            ctx,
+            &hint,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: menu_x + 20.0, y: menu_y + 260.0 })
+                .color(Color::WHITE),
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+//timing scopes for measuring hotspots, built only with the `profiling` feature. Scopes go to
+//puffin, viewable live with puffin_viewer, and into a per-second summary for the in-game overlay
+#[cfg(feature = "profiling")]
+use std::collections::HashMap;
+#[cfg(feature = "profiling")]
+use std::sync::Mutex;
+#[cfg(feature = "profiling")]
+use std::time::{Duration, Instant};
+
+//port puffin_viewer connects to
+#[cfg(feature = "profiling")]
+const SERVER_ADDRESS: &str = "127.0.0.1:8585";
+
+//times the rest of the enclosing block under `name`, compiled out without the feature
+macro_rules! profile_scope {
+    ($name:expr) => {
+        #[cfg(feature = "profiling")]
+        puffin::profile_scope!($name);
+        #[cfg(feature = "profiling")]
+        let _timing = $crate::profiling::Timing::start($name);
+    };
+}
+pub(crate) use profile_scope;
+
+#[cfg(feature = "profiling")]
+#[derive(Clone, Copy, Default)]
+struct ScopeStats {
+    calls: u32,
+    total: Duration,
+    max: Duration,
+}
+
+#[cfg(feature = "profiling")]
+struct Summary {
+    started: Instant,
+    frames: u32,
+    //the scopes measured so far this second
+    current: HashMap<&'static str, ScopeStats>,
+    //the last full second, which is what the overlay shows
+    last: Vec<ScopeLine>,
+}
+
+#[cfg(feature = "profiling")]
+static SUMMARY: Mutex<Option<Summary>> = Mutex::new(None);
+
+//one row of the overlay, times in milliseconds
+#[cfg(feature = "profiling")]
+pub struct ScopeLine {
+    pub name: &'static str,
+    pub per_frame: f64,
+    pub max: f64,
+    pub calls_per_frame: f64,
+}
+
+#[cfg(feature = "profiling")]
+pub struct Timing {
+    name: &'static str,
+    start: Instant,
+}
+
+#[cfg(feature = "profiling")]
+impl Timing {
+    pub fn start(name: &'static str) -> Timing {
+        Timing { name, start: Instant::now() }
+    }
+}
+
+#[cfg(feature = "profiling")]
+impl Drop for Timing {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        if let Ok(mut summary) = SUMMARY.lock() {
+            if let Some(summary) = summary.as_mut() {
+                let stats = summary.current.entry(self.name).or_default();
+                stats.calls += 1;
+                stats.total += elapsed;
+                stats.max = stats.max.max(elapsed);
+            }
+        }
+    }
+}
+
+//turns the scopes on and serves them to puffin_viewer
+#[cfg(feature = "profiling")]
+pub fn start() {
+    puffin::set_scopes_on(true);
+    *SUMMARY.lock().unwrap() = Some(Summary {
+        started: Instant::now(),
+        frames: 0,
+        current: HashMap::new(),
+        last: Vec::new(),
+    });
+    match puffin_http::Server::new(SERVER_ADDRESS) {
+        //the server stops when dropped, and it should run for as long as the game does
+        Ok(server) => std::mem::forget(server),
+        Err(e) => eprintln!("Warning: could not start the profiler server: {}", e),
+    }
+}
+
+#[cfg(not(feature = "profiling"))]
+pub fn start() {}
+
+//marks the end of a drawn frame
+#[cfg(feature = "profiling")]
+pub fn end_frame() {
+    puffin::GlobalProfiler::lock().new_frame();
+    let mut summary = SUMMARY.lock().unwrap();
+    let summary = match summary.as_mut() {
+        Some(summary) => summary,
+        None => return,
+    };
+    summary.frames += 1;
+    if summary.started.elapsed() < Duration::from_secs(1) {
+        return;
+    }
+    let frames = summary.frames.max(1) as f64;
+    let mut lines: Vec<ScopeLine> = summary
+        .current
+        .drain()
+        .map(|(name, stats)| ScopeLine {
+            name,
+            per_frame: stats.total.as_secs_f64() * 1000.0 / frames,
+            max: stats.max.as_secs_f64() * 1000.0,
+            calls_per_frame: stats.calls as f64 / frames,
+        })
+        .collect();
+    lines.sort_by(|a, b| b.per_frame.total_cmp(&a.per_frame));
+    summary.last = lines;
+    summary.frames = 0;
+    summary.started = Instant::now();
+}
+
+#[cfg(not(feature = "profiling"))]
+pub fn end_frame() {}
+
+//the overlay text: the slowest scopes of the last second
+#[cfg(feature = "profiling")]
+pub fn summary_text() -> String {
+    let summary = SUMMARY.lock().unwrap();
+    let mut text = format!("{:<12}{:>8}{:>8}{:>7}\n", "scope", "ms/frm", "max", "calls");
+    if let Some(summary) = summary.as_ref() {
+        for line in &summary.last {
+            text.push_str(&format!(
+                "{:<12}{:>8.3}{:>8.3}{:>7.1}\n",
+                line.name, line.per_frame, line.max, line.calls_per_frame
+            ));
+        }
+    }
+    text
+}
+
+#[cfg(not(feature = "profiling"))]
+pub fn summary_text() -> String {
+    "Built without the profiling feature".to_string()
+}
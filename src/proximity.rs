@@ -0,0 +1,79 @@
+//the HUD's edge glow: which side of the board a ghost is closing in from while pacman can't see
+//it, and how near it is. Pacman sees down a clear row or column, the way a corridor looks, so a
+//ghost round a corner or behind a wall shows as a glow on the edge it's coming from, brighter
+//the closer it gets. Only drawn, so it can use floats
+use crate::collision::WallGrid;
+use crate::fixed::Fixed;
+use crate::{Direction, CELL};
+
+//ghosts further off than this many cells don't glow
+const GLOW_RANGE_CELLS: i32 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Glow {
+    pub edge: Edge,
+    //from 0 at the edge of the range to 1 on top of pacman
+    pub strength: f32,
+}
+
+//the glow for the nearest ghost heading towards pacman out of his sight, if any is in range.
+//Positions are centers, and each ghost comes with the way it's going
+pub fn edge_glow(
+    walls: &WallGrid,
+    pacman: (Fixed, Fixed),
+    ghosts: impl IntoIterator<Item = ((Fixed, Fixed), Direction)>,
+) -> Option<Glow> {
+    let range = CELL * GLOW_RANGE_CELLS;
+    let mut nearest: Option<(i64, Glow)> = None;
+    for (ghost, direction) in ghosts {
+        let (dx, dy) = walls.delta(pacman, ghost);
+        let distance = dx.squared() + dy.squared();
+        if distance >= range.squared() || nearest.is_some_and(|(best, _)| best <= distance) {
+            continue;
+        }
+        let approaching = match direction {
+            Direction::Up => dy > Fixed::ZERO,
+            Direction::Down => dy < Fixed::ZERO,
+            Direction::Left => dx > Fixed::ZERO,
+            Direction::Right => dx < Fixed::ZERO,
+            Direction::None => false,
+        };
+        if !approaching || in_sight(walls, pacman, (dx, dy)) {
+            continue;
+        }
+        let edge = if dx.abs() >= dy.abs() {
+            if dx > Fixed::ZERO { Edge::Right } else { Edge::Left }
+        } else if dy > Fixed::ZERO {
+            Edge::Bottom
+        } else {
+            Edge::Top
+        };
+        let strength = 1.0 - (distance as f32).sqrt() / range.raw() as f32;
+        nearest = Some((distance, Glow { edge, strength }));
+    }
+    nearest.map(|(_, glow)| glow)
+}
+
+//whether the cell `delta` away from `from` is on the same row or column with no wall between
+fn in_sight(walls: &WallGrid, from: (Fixed, Fixed), delta: (Fixed, Fixed)) -> bool {
+    let (col, row) = (from.0.div_floor(CELL) as i64, from.1.div_floor(CELL) as i64);
+    let (to_col, to_row) = ((from.0 + delta.0).div_floor(CELL) as i64, (from.1 + delta.1).div_floor(CELL) as i64);
+    //the far cell isn't wrapped back onto the maze, so this walks the way delta points, the short
+    //way round on a maze that wraps, and is_wall wraps each cell on the way
+    let (step, cells) = if row == to_row {
+        ((delta.0.raw().signum() as i64, 0), (to_col - col).abs())
+    } else if col == to_col {
+        ((0, delta.1.raw().signum() as i64), (to_row - row).abs())
+    } else {
+        return false;
+    };
+    (1..cells).all(|i| !walls.is_wall(col + step.0 * i, row + step.1 * i))
+}
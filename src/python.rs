@@ -0,0 +1,75 @@
+//Python bindings for the headless simulation, built with `--features python`
+//(e.g. `maturin develop --features python`):
+//
+//    import rust_pacman
+//    env = rust_pacman.Env()
+//    obs = env.reset(42)
+//    obs, reward, done = env.step(rust_pacman.LEFT)
+//    frame = env.render()  # numpy uint8 array, shape (height, width, 3)
+use numpy::{PyArray1, PyArray3};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::env::{Observation, PacmanEnv, ACTIONS};
+
+#[pyclass(name = "Env", unsendable)]
+struct PyEnv {
+    inner: PacmanEnv,
+}
+
+#[pymethods]
+impl PyEnv {
+    #[new]
+    fn new() -> PyResult<Self> {
+        let inner = PacmanEnv::new().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        Ok(PyEnv { inner })
+    }
+
+    fn reset(&mut self, py: Python<'_>, seed: u64) -> PyResult<PyObject> {
+        let observation = self.inner.reset(seed);
+        observation_to_py(py, &observation)
+    }
+
+    //returns (observation, reward, done)
+    fn step(&mut self, py: Python<'_>, action: usize) -> PyResult<(PyObject, f32, bool)> {
+        let direction = *ACTIONS
+            .get(action)
+            .ok_or_else(|| PyValueError::new_err(format!("action must be 0..{}", ACTIONS.len())))?;
+        let (observation, reward, done) = self.inner.step(direction);
+        Ok((observation_to_py(py, &observation)?, reward, done))
+    }
+
+    fn render<'py>(&self, py: Python<'py>) -> PyResult<&'py PyArray3<u8>> {
+        let frame = self.inner.render();
+        PyArray1::from_vec(py, frame.pixels).reshape([frame.height, frame.width, 3])
+    }
+}
+
+fn observation_to_py(py: Python<'_>, observation: &Observation) -> PyResult<PyObject> {
+    let dict = PyDict::new(py);
+    let tiles = PyArray1::from_vec(py, observation.tiles.clone()).reshape([observation.height, observation.width])?;
+    dict.set_item("tiles", tiles)?;
+    dict.set_item("pacman", observation.pacman)?;
+    let ghosts: Vec<(f32, f32, bool)> = observation
+        .ghosts
+        .iter()
+        .map(|ghost| (ghost.x, ghost.y, ghost.vulnerable))
+        .collect();
+    dict.set_item("ghosts", ghosts)?;
+    dict.set_item("power_pellet_timer", observation.power_pellet_timer)?;
+    dict.set_item("score", observation.score)?;
+    dict.set_item("lives", observation.lives)?;
+    Ok(dict.to_object(py))
+}
+
+#[pymodule]
+fn rust_pacman(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyEnv>()?;
+    m.add("NOOP", 0)?;
+    m.add("UP", 1)?;
+    m.add("DOWN", 2)?;
+    m.add("LEFT", 3)?;
+    m.add("RIGHT", 4)?;
+    Ok(())
+}
@@ -0,0 +1,89 @@
+//two players on one keyboard racing the same maze side by side. Each half of the window is a game
+//of its own with the same seed, so both mazes and both sets of ghosts start out alike. The first
+//to clear their maze wins; if nobody has when the clock runs out, or both are out of lives, the
+//higher score does
+use ggez::input::keyboard::KeyCode;
+
+use crate::input::Bindings;
+use crate::TICKS_PER_SECOND;
+
+const RACE_TICKS: u32 = 3 * 60 * TICKS_PER_SECOND;
+
+//the left player's keys. The right player uses the ones from the settings
+pub const LEFT_KEYS: Bindings = Bindings { up: KeyCode::W, down: KeyCode::S, left: KeyCode::A, right: KeyCode::D };
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RaceOutcome {
+    Winner(Side),
+    Draw,
+}
+
+impl RaceOutcome {
+    pub fn text(self) -> &'static str {
+        match self {
+            RaceOutcome::Winner(Side::Left) => "Player 1 wins",
+            RaceOutcome::Winner(Side::Right) => "Player 2 wins",
+            RaceOutcome::Draw => "Draw",
+        }
+    }
+}
+
+//how one side's game stands after a tick
+#[derive(Clone, Copy)]
+pub struct Standing {
+    pub score: u32,
+    pub cleared: bool,
+    //out of lives
+    pub out: bool,
+}
+
+pub struct Race {
+    ticks_left: u32,
+    //Some once the race is decided, when both games stop
+    outcome: Option<RaceOutcome>,
+}
+
+impl Race {
+    pub fn new() -> Self {
+        Race { ticks_left: RACE_TICKS, outcome: None }
+    }
+
+    pub fn outcome(&self) -> Option<RaceOutcome> {
+        self.outcome
+    }
+
+    pub fn seconds_left(&self) -> u32 {
+        self.ticks_left.div_ceil(TICKS_PER_SECOND)
+    }
+
+    //counts a tick of both games, deciding the race once either side clears their maze, the time
+    //is up or both are out
+    pub fn tick(&mut self, left: Standing, right: Standing) {
+        if self.outcome.is_some() {
+            return;
+        }
+        self.ticks_left = self.ticks_left.saturating_sub(1);
+        self.outcome = match (left.cleared, right.cleared) {
+            (true, false) => Some(RaceOutcome::Winner(Side::Left)),
+            (false, true) => Some(RaceOutcome::Winner(Side::Right)),
+            //cleared on the same tick, or nobody cleared in time
+            (true, true) => Some(by_score(left, right)),
+            (false, false) if self.ticks_left == 0 || (left.out && right.out) => Some(by_score(left, right)),
+            (false, false) => None,
+        };
+    }
+}
+
+fn by_score(left: Standing, right: Standing) -> RaceOutcome {
+    match left.score.cmp(&right.score) {
+        std::cmp::Ordering::Greater => RaceOutcome::Winner(Side::Left),
+        std::cmp::Ordering::Less => RaceOutcome::Winner(Side::Right),
+        std::cmp::Ordering::Equal => RaceOutcome::Draw,
+    }
+}
@@ -0,0 +1,96 @@
+//software renderer for headless use: draws the board into an RGB pixel buffer the
+//same way `draw` does on the GPU, minus the HUD and menus
+use ggez::graphics::Color;
+
+use crate::{door_bar, MainState, CELL_SIZE, DOOR_COLOR, DOT_SIZE, GHOST_SIZE, PACMAN_SIZE, POWER_PELLET_SIZE};
+
+//row-major RGB, three bytes per pixel
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Frame {
+    fn new(width: usize, height: usize) -> Frame {
+        Frame { width, height, pixels: vec![0; width * height * 3] }
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: Color) {
+        let (r, g, b) = color.to_rgb();
+        let x0 = x.max(0.0) as usize;
+        let y0 = y.max(0.0) as usize;
+        let x1 = ((x + w).max(0.0) as usize).min(self.width);
+        let y1 = ((y + h).max(0.0) as usize).min(self.height);
+        for py in y0..y1 {
+            for px in x0..x1 {
+                let i = (py * self.width + px) * 3;
+                self.pixels[i..i + 3].copy_from_slice(&[r, g, b]);
+            }
+        }
+    }
+
+    fn fill_circle(&mut self, cx: f32, cy: f32, radius: f32, color: Color) {
+        let (r, g, b) = color.to_rgb();
+        let x0 = (cx - radius).max(0.0) as usize;
+        let y0 = (cy - radius).max(0.0) as usize;
+        let x1 = ((cx + radius).max(0.0) as usize + 1).min(self.width);
+        let y1 = ((cy + radius).max(0.0) as usize + 1).min(self.height);
+        for py in y0..y1 {
+            for px in x0..x1 {
+                //sample at pixel centers
+                let dx = px as f32 + 0.5 - cx;
+                let dy = py as f32 + 0.5 - cy;
+                if dx * dx + dy * dy <= radius * radius {
+                    let i = (py * self.width + px) * 3;
+                    self.pixels[i..i + 3].copy_from_slice(&[r, g, b]);
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn render(state: &MainState) -> Frame {
+    let width = (CELL_SIZE * state.map.width as f32) as usize;
+    let height = (CELL_SIZE * state.map.height as f32) as usize;
+    let mut frame = Frame::new(width, height);
+
+    for wall in state.walls.rects() {
+        frame.fill_rect(wall.x, wall.y, wall.w, wall.h, Color::new(0.0, 0.0, 1.0, 1.0));
+    }
+    for (col, row) in state.layout.door_cells() {
+        let door = door_bar(&state.layout, col, row);
+        frame.fill_rect(door.x, door.y, door.w, door.h, DOOR_COLOR);
+    }
+    for dot in &state.dots {
+        frame.fill_circle(dot.x, dot.y, DOT_SIZE / 2.0, Color::WHITE);
+    }
+    for pellet in &state.power_pellets {
+        frame.fill_circle(pellet.x, pellet.y, POWER_PELLET_SIZE / 2.0, Color::WHITE);
+    }
+    frame.fill_circle(
+        state.pacman_x.to_f32() + PACMAN_SIZE / 2.0,
+        state.pacman_y.to_f32() + PACMAN_SIZE / 2.0,
+        PACMAN_SIZE / 2.0,
+        Color::YELLOW,
+    );
+    for ghost in &state.ghosts {
+        if ghost.in_play() {
+            let color = if state.is_flashing(ghost) {
+                Color::WHITE
+            } else if ghost.is_vulnerable() {
+                Color::BLUE
+            } else {
+                ghost.color
+            };
+            let (x, y) = ghost.position();
+            frame.fill_circle(x + GHOST_SIZE / 2.0, y + GHOST_SIZE / 2.0, GHOST_SIZE / 2.0, color);
+        } else if ghost.is_eyes() {
+            let (x, y) = ghost.position();
+            for side in [-1.0, 1.0] {
+                frame.fill_circle(x + GHOST_SIZE / 2.0 + side * 5.0, y + GHOST_SIZE / 2.0 - 2.0, 4.5, Color::WHITE);
+            }
+        }
+    }
+    frame
+}
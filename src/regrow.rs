@@ -0,0 +1,108 @@
+//the regrowth rule: an eaten dot grows back where it was after a while, unless pacman has eaten
+//every dot of its row or of its column. Clearing one locks it for the rest of the level, so the
+//maze becomes territory to win line by line instead of a count to run down
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::TICKS_PER_SECOND;
+
+const REGROW_TICKS: u32 = 45 * TICKS_PER_SECOND;
+
+//a dot waiting to grow back on (col, row), on the level's tick `due`
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct Pending {
+    due: u32,
+    col: usize,
+    row: usize,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Regrowth {
+    //ticks since the level started
+    tick: u32,
+    //the maze's dots in each row and column, and how many of them are eaten right now
+    row_dots: Vec<u32>,
+    col_dots: Vec<u32>,
+    row_eaten: Vec<u32>,
+    col_eaten: Vec<u32>,
+    row_locked: Vec<bool>,
+    col_locked: Vec<bool>,
+    //oldest first, and as every dot waits as long they come due in order
+    pending: VecDeque<Pending>,
+}
+
+impl Regrowth {
+    //a level of the maze with dots on `dot_cells`, none of them eaten yet
+    pub fn new(width: usize, height: usize, dot_cells: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        let mut row_dots = vec![0; height];
+        let mut col_dots = vec![0; width];
+        for (col, row) in dot_cells {
+            if col < width && row < height {
+                row_dots[row] += 1;
+                col_dots[col] += 1;
+            }
+        }
+        Regrowth {
+            tick: 0,
+            row_dots,
+            col_dots,
+            row_eaten: vec![0; height],
+            col_eaten: vec![0; width],
+            row_locked: vec![false; height],
+            col_locked: vec![false; width],
+            pending: VecDeque::new(),
+        }
+    }
+
+    //counts the dot on (col, row) as eaten and sets it growing back. True when that finished
+    //off its row or its column, locking it
+    pub fn eaten(&mut self, col: usize, row: usize) -> bool {
+        if row >= self.row_dots.len() || col >= self.col_dots.len() {
+            return false;
+        }
+        self.row_eaten[row] += 1;
+        self.col_eaten[col] += 1;
+        let mut locked = false;
+        if !self.row_locked[row] && self.row_eaten[row] >= self.row_dots[row] {
+            self.row_locked[row] = true;
+            locked = true;
+        }
+        if !self.col_locked[col] && self.col_eaten[col] >= self.col_dots[col] {
+            self.col_locked[col] = true;
+            locked = true;
+        }
+        self.pending.push_back(Pending { due: self.tick + REGROW_TICKS, col, row });
+        locked
+    }
+
+    //one tick on, handing every dot that grows back now to `regrow` as (col, row). Dots on a
+    //locked row or column stay eaten
+    pub fn tick(&mut self, mut regrow: impl FnMut(usize, usize)) {
+        self.tick += 1;
+        while let Some(&Pending { due, col, row }) = self.pending.front() {
+            if due > self.tick {
+                break;
+            }
+            self.pending.pop_front();
+            if self.is_locked(col, row) {
+                continue;
+            }
+            self.row_eaten[row] -= 1;
+            self.col_eaten[col] -= 1;
+            regrow(col, row);
+        }
+    }
+
+    pub fn is_locked(&self, col: usize, row: usize) -> bool {
+        self.row_locked.get(row).copied().unwrap_or(false) || self.col_locked.get(col).copied().unwrap_or(false)
+    }
+
+    pub fn locked_rows(&self) -> impl Iterator<Item = usize> + '_ {
+        self.row_locked.iter().enumerate().filter(|(_, &locked)| locked).map(|(row, _)| row)
+    }
+
+    pub fn locked_cols(&self) -> impl Iterator<Item = usize> + '_ {
+        self.col_locked.iter().enumerate().filter(|(_, &locked)| locked).map(|(col, _)| col)
+    }
+}
@@ -0,0 +1,117 @@
+//remote-control server: streams the game state to TCP clients as one JSON line per
+//tick and accepts direction commands back, see the README for the protocol
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+use crate::Direction;
+
+//a client that can't keep up for this long is dropped
+const WRITE_TIMEOUT: Duration = Duration::from_millis(200);
+
+#[derive(Serialize)]
+pub struct EntityState {
+    pub x: f32,
+    pub y: f32,
+    pub direction: Direction,
+}
+
+#[derive(Serialize)]
+pub struct GhostState {
+    pub x: f32,
+    pub y: f32,
+    pub direction: Direction,
+    pub vulnerable: bool,
+}
+
+#[derive(Serialize)]
+pub struct StateMessage {
+    pub tick: u32,
+    pub score: u32,
+    pub lives: i32,
+    pub game_over: bool,
+    pub pacman: EntityState,
+    pub ghosts: Vec<GhostState>,
+    pub dots_remaining: usize,
+    pub power_pellet_timer: f32,
+}
+
+#[derive(Deserialize)]
+struct Command {
+    direction: Direction,
+}
+
+pub struct RemoteServer {
+    states: Sender<String>,
+    commands: Receiver<Direction>,
+}
+
+impl RemoteServer {
+    pub fn start(addr: &str) -> io::Result<RemoteServer> {
+        let listener = TcpListener::bind(addr)?;
+        let (state_tx, state_rx) = mpsc::channel::<String>();
+        let (command_tx, command_rx) = mpsc::channel();
+        let (client_tx, client_rx) = mpsc::channel::<TcpStream>();
+
+        //accepts clients, handing the write half to the broadcaster and reading
+        //commands from the other half on a thread per client
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let reader = match stream.try_clone() {
+                    Ok(reader) => reader,
+                    Err(_) => continue,
+                };
+                let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+                if client_tx.send(stream).is_err() {
+                    return;
+                }
+                let commands = command_tx.clone();
+                thread::spawn(move || read_commands(reader, commands));
+            }
+        });
+
+        //writes every state line to every client so a slow socket never stalls the game
+        thread::spawn(move || {
+            let mut clients: Vec<TcpStream> = Vec::new();
+            for line in state_rx {
+                clients.extend(client_rx.try_iter());
+                clients.retain_mut(|client| client.write_all(line.as_bytes()).is_ok());
+            }
+        });
+
+        Ok(RemoteServer {
+            states: state_tx,
+            commands: command_rx,
+        })
+    }
+
+    pub fn broadcast(&self, state: &StateMessage) {
+        if let Ok(mut line) = serde_json::to_string(state) {
+            line.push('\n');
+            let _ = self.states.send(line);
+        }
+    }
+
+    //direction commands received since the last call
+    pub fn commands(&self) -> Vec<Direction> {
+        self.commands.try_iter().collect()
+    }
+}
+
+//one JSON command per line, e.g. {"direction":"Up"}; malformed lines are ignored
+fn read_commands(stream: TcpStream, commands: Sender<Direction>) {
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => return,
+        };
+        if let Ok(command) = serde_json::from_str::<Command>(&line) {
+            if commands.send(command.direction).is_err() {
+                return;
+            }
+        }
+    }
+}
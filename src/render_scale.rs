@@ -0,0 +1,133 @@
+//dynamic resolution: on a GPU that can't keep up, the board is drawn to an offscreen canvas smaller
+//than the window and stretched over it. The scale drops a step while frames run over budget and
+//climbs back a step at a time once they've been on time for a while. The HUD and menus are drawn
+//at full size over it, so text stays sharp
+use ggez::conf::NumSamples;
+use ggez::graphics::{self, Canvas, Color, DrawParam, FilterMode};
+use ggez::{Context, GameResult};
+use std::time::{Duration, Instant};
+
+//how far the scale moves at a time, and the bounds the settings can pick from
+pub const SCALE_STEP: f32 = 0.1;
+pub const LOWEST_SCALE: f32 = 0.3;
+//frames averaged before deciding whether to move
+const SAMPLE_FRAMES: u32 = 30;
+//an average this much over the budget counts as over it, so timer noise doesn't
+const SLACK: f32 = 1.1;
+//longer than this between frames is a stall, such as a window being dragged, not slow drawing
+const STALL: Duration = Duration::from_millis(250);
+//seconds of on-time frames before trying a step up. A step up that has to come straight back down
+//doubles the wait, so a scale on the edge doesn't flicker between two sizes
+const CLIMB_SECONDS: f32 = 2.0;
+const MAX_CLIMB_SECONDS: f32 = 32.0;
+
+pub struct RenderScale {
+    scale: f32,
+    last_frame: Option<Instant>,
+    //the frames since the last decision, and their total time in seconds
+    sampled: u32,
+    sampled_seconds: f32,
+    //seconds of frames on time at this scale, towards the next step up
+    on_time: f32,
+    climb_after: f32,
+    //stepped up and not yet seen to keep up at the new scale
+    climbed: bool,
+    //the offscreen canvas, with the size it was made at
+    canvas: Option<(Canvas, (u16, u16))>,
+}
+
+impl RenderScale {
+    pub fn new() -> Self {
+        RenderScale {
+            scale: 1.0,
+            last_frame: None,
+            sampled: 0,
+            sampled_seconds: 0.0,
+            on_time: 0.0,
+            climb_after: CLIMB_SECONDS,
+            climbed: false,
+            canvas: None,
+        }
+    }
+
+    //counts a frame that started now against `budget`, the time a frame has at the FPS cap, and
+    //moves the scale within min..=max when the last few frames call for it
+    pub fn frame(&mut self, budget: Duration, min: f32, max: f32) {
+        let (min, max) = (min.max(LOWEST_SCALE), max.min(1.0));
+        self.scale = self.scale.clamp(min, max.max(min));
+        let now = Instant::now();
+        let elapsed = match self.last_frame.replace(now) {
+            Some(last) => now - last,
+            None => return,
+        };
+        if elapsed > STALL {
+            self.sampled = 0;
+            self.sampled_seconds = 0.0;
+            return;
+        }
+        self.sampled += 1;
+        self.sampled_seconds += elapsed.as_secs_f32();
+        if self.sampled < SAMPLE_FRAMES {
+            return;
+        }
+        let average = self.sampled_seconds / self.sampled as f32;
+        let seconds = self.sampled_seconds;
+        self.sampled = 0;
+        self.sampled_seconds = 0.0;
+
+        if average > budget.as_secs_f32() * SLACK {
+            if self.climbed {
+                self.climb_after = (self.climb_after * 2.0).min(MAX_CLIMB_SECONDS);
+            }
+            self.climbed = false;
+            self.on_time = 0.0;
+            self.scale = (self.scale - SCALE_STEP).max(min);
+            return;
+        }
+        self.climbed = false;
+        self.on_time += seconds;
+        if self.on_time >= self.climb_after && self.scale < max {
+            self.on_time = 0.0;
+            self.climbed = true;
+            self.scale = (self.scale + SCALE_STEP).min(max);
+        }
+    }
+
+    //points drawing at the offscreen canvas when the scale is below full, made at the scale's
+    //share of the window. Draw in window coordinates as usual and call finish after
+    pub fn begin(&mut self, ctx: &mut Context) -> GameResult {
+        if self.scale >= 1.0 {
+            return Ok(());
+        }
+        let (w, h) = graphics::drawable_size(ctx);
+        let size = (((w * self.scale) as u16).max(1), ((h * self.scale) as u16).max(1));
+        if self.canvas.as_ref().is_none_or(|(_, canvas_size)| *canvas_size != size) {
+            let format = graphics::get_window_color_format(ctx);
+            let mut canvas = Canvas::new(ctx, size.0, size.1, NumSamples::One, format)?;
+            canvas.set_filter(FilterMode::Linear);
+            self.canvas = Some((canvas, size));
+        }
+        if let Some((canvas, _)) = &self.canvas {
+            graphics::set_canvas(ctx, Some(canvas));
+            graphics::clear(ctx, Color::BLACK);
+        }
+        Ok(())
+    }
+
+    //back to drawing on the window, with what was drawn since begin stretched over it
+    pub fn finish(&mut self, ctx: &mut Context) -> GameResult {
+        if self.scale >= 1.0 {
+            return Ok(());
+        }
+        graphics::set_canvas(ctx, None);
+        let (w, h) = graphics::drawable_size(ctx);
+        match &self.canvas {
+            Some((canvas, (cw, ch))) => graphics::draw(
+                ctx,
+                canvas,
+                DrawParam::default().scale([w / f32::from(*cw), h / f32::from(*ch)]),
+            ),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//replay recording and playback, see the README for the file format
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::map::Map;
+use crate::paths;
+use crate::settings::{Difficulty, PelletStacking};
+use crate::Direction;
+
+pub const REPLAY_FORMAT: &str = "rust-pacman-replay";
+pub const REPLAY_VERSION: u32 = 2;
+//v1 replays were recorded on the floating point simulation, which the fixed point one doesn't
+//follow tick for tick, so they can't be played back any more
+const OLDEST_REPLAY_VERSION: u32 = 2;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct InputChange {
+    pub tick: u32,
+    pub direction: Direction,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+    pub format: String,
+    pub version: u32,
+    pub game_version: String,
+    pub map_hash: String,
+    pub seed: u64,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub pellet_stacking: PelletStacking,
+    #[serde(default)]
+    pub prestige: u32,
+    #[serde(default)]
+    pub start_score: u32,
+    #[serde(default)]
+    pub intersection_pause: bool,
+    #[serde(default)]
+    pub moving_fruit: bool,
+    pub ticks: u32,
+    pub inputs: Vec<InputChange>,
+    //the ticks a held power-up was set off on
+    #[serde(default)]
+    pub item_uses: Vec<u32>,
+}
+
+//just enough of the header to decide whether the rest can be read
+#[derive(Deserialize)]
+struct VersionProbe {
+    format: String,
+    version: u32,
+    #[serde(default)]
+    game_version: String,
+}
+
+impl Replay {
+    //`map_hash` is the maze it's going to be played on
+    pub fn parse(json: &str, map_hash: &str) -> Result<Replay, String> {
+        let probe: VersionProbe = serde_json::from_str(json).map_err(|e| format!("not a replay file: {}", e))?;
+        if probe.format != REPLAY_FORMAT {
+            return Err(format!("not a replay file (format `{}`)", probe.format));
+        }
+        if probe.version > REPLAY_VERSION {
+            return Err(format!(
+                "replay format v{} is newer than this game supports (v{}), please update",
+                probe.version, REPLAY_VERSION
+            ));
+        }
+        if probe.version < OLDEST_REPLAY_VERSION {
+            return Err(format!(
+                "replay format v{} (recorded by version {}) is too old to play back, this game needs v{} or newer",
+                probe.version, probe.game_version, OLDEST_REPLAY_VERSION
+            ));
+        }
+        let replay: Replay = serde_json::from_str(json).map_err(|e| format!("corrupt replay: {}", e))?;
+        if replay.map_hash != map_hash {
+            return Err("replay was recorded on a different maze".to_string());
+        }
+        Ok(replay)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P, map_hash: &str) -> Result<Replay, String> {
+        let json = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.as_ref().display(), e))?;
+        Replay::parse(&json, map_hash)
+    }
+
+    //writes the replay into the replays folder, named by the time it was saved
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let dir = paths::replays_dir();
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let path = dir.join(format!("replay-{}.json", stamp));
+        fs::write(&path, json).map_err(|e| e.to_string())?;
+        Ok(path)
+    }
+}
+
+//what besides the seed and the inputs decides how a recorded game plays out
+#[derive(Clone, Copy)]
+pub struct ReplayOptions {
+    pub difficulty: Difficulty,
+    pub pellet_stacking: PelletStacking,
+    pub prestige: u32,
+    pub start_score: u32,
+    pub intersection_pause: bool,
+    pub moving_fruit: bool,
+}
+
+//collects the requested direction every tick, storing only the changes
+pub struct ReplayRecorder {
+    map_hash: String,
+    ticks: u32,
+    inputs: Vec<InputChange>,
+    last: Option<Direction>,
+    item_uses: Vec<u32>,
+}
+
+impl ReplayRecorder {
+    pub fn new(map_hash: String) -> Self {
+        ReplayRecorder {
+            map_hash,
+            ticks: 0,
+            inputs: Vec::new(),
+            last: None,
+            item_uses: Vec::new(),
+        }
+    }
+
+    //the held power-up is set off this tick. Call before `record`, which ends the tick
+    pub fn record_item_use(&mut self) {
+        self.item_uses.push(self.ticks);
+    }
+
+    pub fn record(&mut self, direction: Direction) {
+        if self.last != Some(direction) {
+            self.inputs.push(InputChange { tick: self.ticks, direction });
+            self.last = Some(direction);
+        }
+        self.ticks += 1;
+    }
+
+    pub fn finish(self, seed: u64, options: ReplayOptions) -> Replay {
+        Replay {
+            format: REPLAY_FORMAT.to_string(),
+            version: REPLAY_VERSION,
+            game_version: env!("CARGO_PKG_VERSION").to_string(),
+            map_hash: self.map_hash,
+            seed,
+            difficulty: options.difficulty,
+            pellet_stacking: options.pellet_stacking,
+            prestige: options.prestige,
+            start_score: options.start_score,
+            intersection_pause: options.intersection_pause,
+            moving_fruit: options.moving_fruit,
+            ticks: self.ticks,
+            inputs: self.inputs,
+            item_uses: self.item_uses,
+        }
+    }
+}
+
+//feeds a replay's inputs back one tick at a time
+pub struct ReplayPlayer {
+    replay: Replay,
+    tick: u32,
+    cursor: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayer { replay, tick: 0, cursor: 0 }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.tick >= self.replay.ticks
+    }
+
+    //whether the held power-up goes off on the next tick. Ask before `next_input`
+    pub fn uses_item(&self) -> bool {
+        self.replay.item_uses.binary_search(&self.tick).is_ok()
+    }
+
+    //the input for the next tick, or None once the recording has run out
+    pub fn next_input(&mut self) -> Option<Direction> {
+        if self.tick >= self.replay.ticks {
+            return None;
+        }
+        while self.cursor + 1 < self.replay.inputs.len() && self.replay.inputs[self.cursor + 1].tick <= self.tick {
+            self.cursor += 1;
+        }
+        self.tick += 1;
+        self.replay.inputs.get(self.cursor).map(|input| input.direction)
+    }
+}
+
+//FNV-1a over the maze as its file would be written, rows and rules; stable across platforms and
+//Rust versions
+pub fn map_hash(map: &Map) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in map.to_string().bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
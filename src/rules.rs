@@ -0,0 +1,150 @@
+//the rules a maze plays by. A map file can change them in a `[rules]` block after its rows, in
+//TOML, and anything it leaves out keeps the arcade value:
+//
+//    WWWWW
+//    WP.GW
+//    WWWWW
+//    [rules]
+//    ghosts = 2
+//    pellet_seconds = 8.0
+//    fruit_dots = [30, 60]
+//    ghost_speed = 1.2
+//    house_arrest = true
+//    wrap = true
+//    regrow = true
+//    scatter_chase = [7.0, 20.0, 7.0, 20.0]
+//
+//    [rules.power_ups]
+//    slow_pacman = false
+//
+//    [[rules.aggression]]
+//    from_level = 1
+//    wander = 0.5
+use serde::{Deserialize, Serialize};
+
+use crate::mods::MAX_SPEED_FACTOR;
+use crate::spawn::ROSTER;
+
+//the line a map's rules block starts with
+pub const RULES_HEADER: &str = "[rules]";
+//past this many the pen is nothing but ghosts
+const MAX_GHOSTS: usize = 16;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Ruleset {
+    //ghosts in play. Past the roster's three they repeat it in order
+    pub ghosts: usize,
+    //how long a power pellet frightens the ghosts
+    pub pellet_seconds: f32,
+    //a fruit comes out on pacman's start cell once this many of a level's dots are eaten, for
+    //each number. The arcade's are 70 and 170
+    pub fruit_dots: Vec<u32>,
+    //on top of the speed table
+    pub pacman_speed: f32,
+    pub ghost_speed: f32,
+    //a ghost eaten again on the same level spends half as long again in the pen each time
+    pub house_arrest: bool,
+    //all four edges join up, so leaving the maze on one side comes back in on the other
+    pub wrap: bool,
+    //eaten dots grow back after a while, unless their whole row or column was cleared first
+    pub regrow: bool,
+    //seconds the ghosts spend scattering and chasing in turn, starting with scatter. After the
+    //last they chase for good
+    pub scatter_chase: Vec<f32>,
+    pub power_ups: PowerUps,
+    //how the ghosts get keener over a level as its dots go, by level. Empty keeps them the same
+    //from the first dot to the last
+    pub aggression: Vec<AggressionRow>,
+}
+
+//the ghosts' ramp from `from_level` on, until the next row's level. Each is the share taken off
+//by the time the last dot goes, rising evenly with the dots eaten: `wander` off the odds of a
+//ghost roaming instead of chasing, `retarget` off the time before it looks for pacman again
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AggressionRow {
+    pub from_level: u32,
+    #[serde(default)]
+    pub wander: f32,
+    #[serde(default)]
+    pub retarget: f32,
+}
+
+//which power-ups the maze has
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerUps {
+    pub power_pellets: bool,
+    //the effects chat votes and other outside events can set off
+    pub scatter: bool,
+    pub slow_pacman: bool,
+    pub fruit: bool,
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Ruleset {
+            ghosts: ROSTER.len(),
+            pellet_seconds: 5.0,
+            fruit_dots: vec![70, 170],
+            pacman_speed: 1.0,
+            ghost_speed: 1.0,
+            house_arrest: false,
+            wrap: false,
+            regrow: false,
+            //the arcade's first level
+            scatter_chase: vec![7.0, 20.0, 7.0, 20.0, 5.0, 20.0, 5.0],
+            power_ups: PowerUps::default(),
+            aggression: Vec::new(),
+        }
+    }
+}
+
+impl Default for PowerUps {
+    fn default() -> Self {
+        PowerUps { power_pellets: true, scatter: true, slow_pacman: true, fruit: true }
+    }
+}
+
+impl Ruleset {
+    //the TOML under a map's `[rules]` header
+    pub fn parse(text: &str) -> Result<Ruleset, String> {
+        let rules: Ruleset = toml::from_str(text).map_err(|e| format!("bad rules block: {}", e))?;
+        rules.check()?;
+        Ok(rules)
+    }
+
+    //whether the values make a playable maze, for rules that didn't come from a map file too
+    pub fn check(&self) -> Result<(), String> {
+        if self.ghosts > MAX_GHOSTS {
+            return Err(format!("the rules ask for {} ghosts, the limit is {}", self.ghosts, MAX_GHOSTS));
+        }
+        if !self.pellet_seconds.is_finite() || self.pellet_seconds < 0.0 {
+            return Err("pellet_seconds can't be negative".to_string());
+        }
+        //the same cap as a mod's, so a step stays well short of a cell and can't skip a wall
+        let speeds = [self.pacman_speed, self.ghost_speed];
+        if speeds.iter().any(|speed| !speed.is_finite() || *speed <= 0.0 || *speed > MAX_SPEED_FACTOR) {
+            return Err(format!("speed multipliers have to be above zero and at most {}", MAX_SPEED_FACTOR));
+        }
+        if self.scatter_chase.iter().any(|seconds| !seconds.is_finite() || *seconds < 0.0) {
+            return Err("scatter_chase can't have negative phases".to_string());
+        }
+        for row in &self.aggression {
+            if [row.wander, row.retarget].iter().any(|share| !(0.0..=1.0).contains(share)) {
+                return Err(format!("the aggression row from level {} has a share outside 0 to 1", row.from_level));
+            }
+        }
+        Ok(())
+    }
+
+    //the aggression row for `level`, if any row starts at or before it
+    pub fn aggression_for(&self, level: u32) -> Option<AggressionRow> {
+        self.aggression.iter().filter(|row| row.from_level <= level).max_by_key(|row| row.from_level).copied()
+    }
+
+    //the block `parse` reads, without the header
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap_or_default()
+    }
+}
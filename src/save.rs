@@ -0,0 +1,114 @@
+//mid-game save slots, stored as JSON snapshots of the full game
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::entities::{FruitKind, SavedEntity};
+use crate::ghost_mode::GhostModeTimer;
+use crate::inventory::PowerUp;
+use crate::paths;
+use crate::regrow::Regrowth;
+use crate::settings::Assists;
+use crate::{Direction, Ghost, Round};
+
+pub const SLOT_COUNT: usize = 3;
+
+//everything needed to resume a game exactly where it was left
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub pacman: (f32, f32),
+    pub current_direction: Direction,
+    pub requested_direction: Direction,
+    pub dots: Vec<(f32, f32)>,
+    pub power_pellets: Vec<(f32, f32)>,
+    pub power_pellet_active: bool,
+    pub power_pellet_timer: f32,
+    #[serde(default)]
+    pub slow_timer: f32,
+    #[serde(default)]
+    pub invulnerable_ticks: u32,
+    #[serde(default)]
+    pub round: Round,
+    #[serde(default)]
+    pub ghost_chain: u32,
+    #[serde(default)]
+    pub pellet_ghosts: u32,
+    #[serde(default)]
+    pub assists: Assists,
+    #[serde(default)]
+    pub prestige: u32,
+    #[serde(default)]
+    pub moving_fruit: bool,
+    #[serde(default)]
+    pub global_dots: Option<u32>,
+    #[serde(default)]
+    pub pen_idle_ticks: u32,
+    #[serde(default)]
+    pub level_ticks: u32,
+    //the level's eaten dots for the fruit. None in saves from before it was kept
+    #[serde(default)]
+    pub level_dots_eaten: Option<u32>,
+    //saves from before levels were all on the first
+    #[serde(default = "first_level")]
+    pub level: u32,
+    #[serde(default)]
+    pub held_item: Option<PowerUp>,
+    #[serde(default)]
+    pub freeze_timer: f32,
+    //saves from before the schedule have the ghosts chasing for good
+    #[serde(default)]
+    pub ghost_mode: GhostModeTimer,
+    #[serde(default)]
+    pub regrowth: Option<Regrowth>,
+    #[serde(default)]
+    pub fruit_eaten: Vec<FruitKind>,
+    //the fruit, items and hazards out in the maze. Saves from before these were kept come back
+    //without any
+    #[serde(default)]
+    pub entities: Vec<SavedEntity>,
+    pub ghosts: Vec<Ghost>,
+    pub score: u32,
+    pub lives: i32,
+    pub rng: Pcg32,
+}
+
+fn first_level() -> u32 {
+    1
+}
+
+fn slot_path(slot: usize) -> PathBuf {
+    paths::saves_dir().join(format!("slot{}.json", slot + 1))
+}
+
+pub fn save_slot(slot: usize, snapshot: &Snapshot) -> Result<(), String> {
+    let json = serde_json::to_string(snapshot).map_err(|e| e.to_string())?;
+    fs::create_dir_all(paths::saves_dir()).map_err(|e| e.to_string())?;
+    fs::write(slot_path(slot), json).map_err(|e| e.to_string())
+}
+
+//None if the slot is empty or the file can't be read
+pub fn load_slot(slot: usize) -> Option<Snapshot> {
+    let json = fs::read_to_string(slot_path(slot)).ok()?;
+    match serde_json::from_str(&json) {
+        Ok(snapshot) => Some(snapshot),
+        Err(e) => {
+            eprintln!("Save slot {} is unreadable: {}", slot + 1, e);
+            None
+        }
+    }
+}
+
+//(de)serializes a ggez Color as an [r, g, b, a] array
+pub mod color_serde {
+    use ggez::graphics::Color;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        <[f32; 4]>::from(*color).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        Ok(Color::from(<[f32; 4]>::deserialize(deserializer)?))
+    }
+}
@@ -0,0 +1,132 @@
+//local history of every completed game, kept in an embedded SQLite database
+use rusqlite::{params, Connection};
+
+use crate::paths;
+
+pub struct GameRecord {
+    pub date: String,
+    pub map: String,
+    pub seed: u64,
+    pub score: u32,
+    pub level: u32,
+    //New Game+ rounds taken before this game
+    pub prestige: u32,
+    //played with an assist on
+    pub assisted: bool,
+    pub duration_secs: f32,
+}
+
+//columns the history screen can sort by, newest/highest first
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortKey {
+    Date,
+    Score,
+    Level,
+    Duration,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 4] = [SortKey::Date, SortKey::Score, SortKey::Level, SortKey::Duration];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SortKey::Date => "date",
+            SortKey::Score => "score",
+            SortKey::Level => "level",
+            SortKey::Duration => "duration",
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            SortKey::Date => "date",
+            SortKey::Score => "score",
+            SortKey::Level => "level",
+            SortKey::Duration => "duration_secs",
+        }
+    }
+}
+
+pub struct ScoreDb {
+    conn: Connection,
+}
+
+impl ScoreDb {
+    pub fn open() -> Result<ScoreDb, String> {
+        let conn = Connection::open(paths::scores_db()).map_err(|e| e.to_string())?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS games (
+                id INTEGER PRIMARY KEY,
+                date TEXT NOT NULL,
+                map TEXT NOT NULL,
+                seed INTEGER NOT NULL,
+                score INTEGER NOT NULL,
+                level INTEGER NOT NULL,
+                duration_secs REAL NOT NULL,
+                prestige INTEGER NOT NULL DEFAULT 0,
+                assisted INTEGER NOT NULL DEFAULT 0
+            )",
+            [],
+        )
+        .map_err(|e| e.to_string())?;
+        //databases from before New Game+ and assists get the columns, which fails harmlessly once
+        //they're there
+        let _ = conn.execute("ALTER TABLE games ADD COLUMN prestige INTEGER NOT NULL DEFAULT 0", []);
+        let _ = conn.execute("ALTER TABLE games ADD COLUMN assisted INTEGER NOT NULL DEFAULT 0", []);
+        Ok(ScoreDb { conn })
+    }
+
+    pub fn insert(&self, record: &GameRecord) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO games (date, map, seed, score, level, duration_secs, prestige, assisted) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    record.date,
+                    record.map,
+                    record.seed as i64,
+                    record.score,
+                    record.level,
+                    record.duration_secs as f64,
+                    record.prestige,
+                    record.assisted
+                ],
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn games(&self, sort: SortKey, limit: usize) -> Result<Vec<GameRecord>, String> {
+        let sql = format!(
+            "SELECT date, map, seed, score, level, duration_secs, prestige, assisted FROM games ORDER BY {} DESC LIMIT ?1",
+            sort.column()
+        );
+        let mut stmt = self.conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![limit as i64], |row| {
+                Ok(GameRecord {
+                    date: row.get(0)?,
+                    map: row.get(1)?,
+                    seed: row.get::<_, i64>(2)? as u64,
+                    score: row.get(3)?,
+                    level: row.get(4)?,
+                    duration_secs: row.get::<_, f64>(5)? as f32,
+                    prestige: row.get(6)?,
+                    assisted: row.get(7)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+    }
+
+    //best score on each map
+    pub fn map_bests(&self) -> Result<Vec<(String, u32)>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT map, MAX(score) FROM games GROUP BY map ORDER BY map")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,243 @@
+//user options persisted as TOML in the platform config directory
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::input::Bindings;
+use crate::paths;
+use crate::stats::StatsFormat;
+
+//bump when a field is renamed or changes meaning, and add a step to MIGRATIONS
+pub const SETTINGS_VERSION: u32 = 1;
+
+//MIGRATIONS[i] upgrades a version i + 1 table to version i + 2
+const MIGRATIONS: [fn(&mut toml::value::Table); 0] = [];
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub fullscreen: bool,
+    //export stats of every finished game to the stats folder
+    pub stats_export: Option<StatsFormat>,
+    //write a local session log to the logs folder
+    pub telemetry: bool,
+    //look for a newer release on GitHub at startup
+    pub check_updates: bool,
+    //wait for the display's refresh before showing a frame. Read at startup
+    pub vsync: bool,
+    //draw pacman and the ghosts between ticks instead of only where each tick leaves them
+    pub smooth_motion: bool,
+    //the least and most of the window's resolution the board is drawn at. Between the two it
+    //drops while frames run over budget and climbs back once they're on time
+    pub render_scale_min: f32,
+    pub render_scale_max: f32,
+    //most frames drawn per second, 0 for no limit
+    pub fps_cap: u32,
+    //takes effect from the next game
+    pub difficulty: Difficulty,
+    //what a power pellet eaten while another is active does, from the next game
+    pub pellet_stacking: PelletStacking,
+    //bonus fruit wanders the maze instead of sitting still, from the next game
+    pub moving_fruit: bool,
+    //finished or skipped the tutorial, so it no longer opens at startup
+    pub tutorial_done: bool,
+    //the high-contrast palette with outlined pacman and ghosts
+    pub high_contrast: bool,
+    //score milestones, power pellets and lost lives captioned and, with the tts feature, spoken
+    pub announcements: bool,
+    //frightened ghosts flash once their fright has less than this many seconds left, 0 for never
+    pub frightened_flash_seconds: f32,
+    //a moment of slow motion when a ghost only just misses pacman
+    pub close_call_slowmo: bool,
+    //tables have to come after plain values in TOML, so keep these last
+    pub hud: HudWidgets,
+    pub assists: Assists,
+    pub bindings: Bindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            version: SETTINGS_VERSION,
+            fullscreen: false,
+            stats_export: None,
+            telemetry: false,
+            check_updates: false,
+            vsync: true,
+            smooth_motion: true,
+            render_scale_min: 0.5,
+            render_scale_max: 1.0,
+            fps_cap: 60,
+            difficulty: Difficulty::Normal,
+            pellet_stacking: PelletStacking::Refresh,
+            moving_fruit: false,
+            tutorial_done: false,
+            high_contrast: false,
+            announcements: false,
+            frightened_flash_seconds: 2.0,
+            close_call_slowmo: true,
+            hud: HudWidgets::default(),
+            assists: Assists::default(),
+            bindings: Bindings::default(),
+        }
+    }
+}
+
+//how forgiving the ghosts are. Replays remember the one they were played on
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    pub fn next(self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    //how long an eaten ghost stays out of play
+    pub fn ghost_respawn_seconds(self) -> f32 {
+        match self {
+            Difficulty::Easy => 6.0,
+            Difficulty::Normal => 4.0,
+            Difficulty::Hard => 2.0,
+        }
+    }
+}
+
+//a power pellet eaten while another is active either starts the full time over and begins a new
+//ghost-eating chain, or adds its time to what's left and keeps the chain going
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum PelletStacking {
+    #[default]
+    Refresh,
+    Extend,
+}
+
+impl PelletStacking {
+    pub fn name(self) -> &'static str {
+        match self {
+            PelletStacking::Refresh => "Refresh",
+            PelletStacking::Extend => "Extend",
+        }
+    }
+
+    pub fn next(self) -> PelletStacking {
+        match self {
+            PelletStacking::Refresh => PelletStacking::Extend,
+            PelletStacking::Extend => PelletStacking::Refresh,
+        }
+    }
+}
+
+//extra HUD lines, each switched on by itself. They only show what the game already knows, so
+//unlike assists they don't mark anything in the history
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HudWidgets {
+    //time spent on the current level, to the tenth of a second
+    pub level_time: bool,
+    pub dots_left: bool,
+    //what the ghosts are doing, and for how much longer
+    pub ghost_mode: bool,
+    //the edge of the board a ghost is coming from out of pacman's sight glows
+    pub edge_glow: bool,
+}
+
+//accessibility assists, copied into each new game and kept in its saves. Games played with any of
+//them on are marked in the history
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Assists {
+    //percent of the normal speed the whole game runs at, from 25 to 100
+    pub game_speed: u32,
+    //everything stops for a moment whenever pacman reaches a junction he could turn at
+    pub intersection_pause: bool,
+}
+
+impl Default for Assists {
+    fn default() -> Self {
+        Assists {
+            game_speed: 100,
+            intersection_pause: false,
+        }
+    }
+}
+
+impl Assists {
+    pub fn any(self) -> bool {
+        self != Assists::default()
+    }
+
+    //100% down to 25% in quarters, then back to 100%
+    pub fn next_speed(self) -> u32 {
+        match self.game_speed {
+            speed if speed > 25 => (speed - 25).max(25),
+            _ => 100,
+        }
+    }
+}
+
+impl Settings {
+    //reads settings from disk, falling back to defaults if the file is missing or unreadable
+    pub fn load() -> Settings {
+        let path = paths::settings_file();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(_) => return Settings::default(),
+        };
+        match Settings::parse(&contents) {
+            Ok(settings) => settings,
+            Err(e) => {
+                eprintln!("Ignoring settings file {}: {}", path.display(), e);
+                Settings::default()
+            }
+        }
+    }
+
+    //parses a settings file, migrating older versions forward first
+    pub fn parse(contents: &str) -> Result<Settings, String> {
+        let mut table: toml::value::Table = toml::from_str(contents).map_err(|e| e.to_string())?;
+        let version = table
+            .get("version")
+            .and_then(|v| v.as_integer())
+            .unwrap_or(1) as u32;
+        if version > SETTINGS_VERSION {
+            return Err(format!("written by a newer version (settings v{})", version));
+        }
+        for migrate in MIGRATIONS.iter().skip(version.saturating_sub(1) as usize) {
+            migrate(&mut table);
+        }
+        table.insert("version".to_string(), toml::Value::Integer(SETTINGS_VERSION as i64));
+        toml::Value::Table(table).try_into().map_err(|e: toml::de::Error| e.to_string())
+    }
+
+    pub fn save(&self) {
+        let path = paths::settings_file();
+        let result = toml::to_string_pretty(self)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+                }
+                fs::write(&path, contents).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            eprintln!("Could not save settings to {}: {}", path.display(), e);
+        }
+    }
+}
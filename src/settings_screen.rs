@@ -0,0 +1,366 @@
+//settings screen with the "press the key for Up..." capture flow
+use ggez::conf::FullscreenType;
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+
+use crate::input::{self, BINDABLE_DIRECTIONS};
+use crate::paths;
+use crate::render_scale::{LOWEST_SCALE, SCALE_STEP};
+use crate::settings::Settings;
+use crate::stats::StatsFormat;
+use crate::Direction;
+
+//binding rows come first, followed by one row per option
+const WINDOW_ROW: usize = BINDABLE_DIRECTIONS.len();
+const STATS_ROW: usize = WINDOW_ROW + 1;
+const TELEMETRY_ROW: usize = STATS_ROW + 1;
+const UPDATES_ROW: usize = TELEMETRY_ROW + 1;
+const FPS_ROW: usize = UPDATES_ROW + 1;
+const VSYNC_ROW: usize = FPS_ROW + 1;
+const MOTION_ROW: usize = VSYNC_ROW + 1;
+const MIN_SCALE_ROW: usize = MOTION_ROW + 1;
+const MAX_SCALE_ROW: usize = MIN_SCALE_ROW + 1;
+const DIFFICULTY_ROW: usize = MAX_SCALE_ROW + 1;
+const PELLETS_ROW: usize = DIFFICULTY_ROW + 1;
+const FRUIT_ROW: usize = PELLETS_ROW + 1;
+const SPEED_ROW: usize = FRUIT_ROW + 1;
+const TURNS_ROW: usize = SPEED_ROW + 1;
+const ACCESS_ROW: usize = TURNS_ROW + 1;
+const SLOWMO_ROW: usize = ACCESS_ROW + 1;
+const TIMER_ROW: usize = SLOWMO_ROW + 1;
+const DOTS_ROW: usize = TIMER_ROW + 1;
+const GHOSTS_ROW: usize = DOTS_ROW + 1;
+const GLOW_ROW: usize = GHOSTS_ROW + 1;
+const ROW_COUNT: usize = GLOW_ROW + 1;
+//close enough together that every row fits in the smallest window
+const ROW_SPACING: f32 = 21.0;
+
+//the FPS caps to cycle through, 0 being unlimited
+const FPS_CAPS: [u32; 4] = [30, 60, 120, 0];
+
+pub struct SettingsScreen {
+    selected: usize,
+    capturing: Option<Direction>,
+    message: Option<String>,
+}
+
+impl SettingsScreen {
+    pub fn new() -> Self {
+        SettingsScreen {
+            selected: 0,
+            capturing: None,
+            message: None,
+        }
+    }
+
+    //handles a key press while the screen is open. Returns false once the screen should close
+    pub fn handle_key(&mut self, ctx: &mut Context, keycode: KeyCode, settings: &mut Settings) -> bool {
+        //waiting for the next key to bind
+        if let Some(direction) = self.capturing.take() {
+            if keycode == KeyCode::Escape {
+                self.message = Some("Cancelled".to_string());
+                return true;
+            }
+            match input::key_name(keycode) {
+                Some(name) => {
+                    self.message = settings.bindings.rebind(direction, keycode).map(|other| format!(
+                            "{} was already bound to {}, keys swapped",
+                            name,
+                            input::direction_name(other)
+                        ));
+                }
+                None => {
+                    //keep listening, this key can't be shown or saved
+                    self.message = Some("That key can't be bound, try another".to_string());
+                    self.capturing = Some(direction);
+                }
+            }
+            return true;
+        }
+
+        match keycode {
+            KeyCode::Escape | KeyCode::F1 => return false,
+            KeyCode::Up => {
+                self.selected = (self.selected + ROW_COUNT - 1) % ROW_COUNT;
+            }
+            KeyCode::Down => {
+                self.selected = (self.selected + 1) % ROW_COUNT;
+            }
+            KeyCode::Return if self.selected == WINDOW_ROW => {
+                settings.fullscreen = !settings.fullscreen;
+                let mode = if settings.fullscreen {
+                    FullscreenType::Desktop
+                } else {
+                    FullscreenType::Windowed
+                };
+                if let Err(e) = graphics::set_fullscreen(ctx, mode) {
+                    self.message = Some(format!("Couldn't change window mode: {}", e));
+                }
+            }
+            KeyCode::Return if self.selected == STATS_ROW => {
+                settings.stats_export = match settings.stats_export {
+                    None => Some(StatsFormat::Json),
+                    Some(StatsFormat::Json) => Some(StatsFormat::Csv),
+                    Some(StatsFormat::Csv) => None,
+                };
+            }
+            KeyCode::Return if self.selected == TELEMETRY_ROW => {
+                settings.telemetry = !settings.telemetry;
+            }
+            KeyCode::Return if self.selected == UPDATES_ROW => {
+                settings.check_updates = !settings.check_updates;
+                if settings.check_updates {
+                    self.message = Some("Checked on the next start".to_string());
+                }
+            }
+            KeyCode::Return if self.selected == FPS_ROW => {
+                let current = FPS_CAPS.iter().position(|&cap| cap == settings.fps_cap).unwrap_or(0);
+                settings.fps_cap = FPS_CAPS[(current + 1) % FPS_CAPS.len()];
+            }
+            KeyCode::Return if self.selected == VSYNC_ROW => {
+                settings.vsync = !settings.vsync;
+                self.message = Some("Vsync changes on the next start".to_string());
+            }
+            KeyCode::Return if self.selected == MOTION_ROW => {
+                settings.smooth_motion = !settings.smooth_motion;
+            }
+            //each steps up to full resolution then wraps to the lowest, pushing the other along so
+            //the least never passes the most
+            KeyCode::Return if self.selected == MIN_SCALE_ROW => {
+                settings.render_scale_min = next_scale(settings.render_scale_min);
+                settings.render_scale_max = settings.render_scale_max.max(settings.render_scale_min);
+            }
+            KeyCode::Return if self.selected == MAX_SCALE_ROW => {
+                settings.render_scale_max = next_scale(settings.render_scale_max);
+                settings.render_scale_min = settings.render_scale_min.min(settings.render_scale_max);
+            }
+            KeyCode::Return if self.selected == DIFFICULTY_ROW => {
+                settings.difficulty = settings.difficulty.next();
+                self.message = Some("Difficulty changes from the next game".to_string());
+            }
+            KeyCode::Return if self.selected == PELLETS_ROW => {
+                settings.pellet_stacking = settings.pellet_stacking.next();
+                self.message = Some("Pellet stacking changes from the next game".to_string());
+            }
+            KeyCode::Return if self.selected == FRUIT_ROW => {
+                settings.moving_fruit = !settings.moving_fruit;
+                self.message = Some("Fruit changes from the next game".to_string());
+            }
+            KeyCode::Return if self.selected == SPEED_ROW => {
+                settings.assists.game_speed = settings.assists.next_speed();
+                self.message = Some("Assists change from the next game".to_string());
+            }
+            KeyCode::Return if self.selected == TURNS_ROW => {
+                settings.assists.intersection_pause = !settings.assists.intersection_pause;
+                self.message = Some("Assists change from the next game".to_string());
+            }
+            KeyCode::Return if self.selected == ACCESS_ROW => {
+                //off, contrast, announcements, both
+                let (high_contrast, announcements) = match (settings.high_contrast, settings.announcements) {
+                    (false, false) => (true, false),
+                    (true, false) => (false, true),
+                    (false, true) => (true, true),
+                    (true, true) => (false, false),
+                };
+                settings.high_contrast = high_contrast;
+                settings.announcements = announcements;
+            }
+            KeyCode::Return if self.selected == SLOWMO_ROW => {
+                settings.close_call_slowmo = !settings.close_call_slowmo;
+            }
+            KeyCode::Return if self.selected == TIMER_ROW => {
+                settings.hud.level_time = !settings.hud.level_time;
+            }
+            KeyCode::Return if self.selected == DOTS_ROW => {
+                settings.hud.dots_left = !settings.hud.dots_left;
+            }
+            KeyCode::Return if self.selected == GHOSTS_ROW => {
+                settings.hud.ghost_mode = !settings.hud.ghost_mode;
+            }
+            KeyCode::Return if self.selected == GLOW_ROW => {
+                settings.hud.edge_glow = !settings.hud.edge_glow;
+            }
+            KeyCode::Return => {
+                self.capturing = Some(BINDABLE_DIRECTIONS[self.selected]);
+                self.message = None;
+            }
+            _ => {}
+        }
+        true
+    }
+
+    pub fn draw(&self, ctx: &mut Context, settings: &Settings) -> GameResult {
+        let (w, h) = graphics::drawable_size(ctx);
+
+        //dim the game behind the screen
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, 0.0, w, h),
+            Color::new(0.0, 0.0, 0.0, 0.7),
+        )?;
+        graphics::draw(ctx, &background, DrawParam::default())?;
+
+        let menu_width = 360.0;
+        let menu_height = 690.0;
+        let menu_x = (w - menu_width) / 2.0;
+        let menu_y = (h - menu_height) / 2.0;
+
+        let menu_bg = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(menu_x, menu_y, menu_width, menu_height),
+            Color::new(0.2, 0.2, 0.2, 1.0),
+        )?;
+        graphics::draw(ctx, &menu_bg, DrawParam::default())?;
+
+        let title = graphics::Text::new("Controls");
+        let title_dims = title.dimensions(ctx);
+        graphics::draw(
+            ctx,
+            &title,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 {
+                    x: menu_x + (menu_width - title_dims.w * 2.0) / 2.0,
+                    y: menu_y + 20.0,
+                })
+                .color(Color::YELLOW)
+                .scale([2.0, 2.0]),
+        )?;
+
+        //one row per bindable direction
+        for (i, &direction) in BINDABLE_DIRECTIONS.iter().enumerate() {
+            let key = settings
+                .bindings
+                .key_for(direction)
+                .and_then(input::key_name)
+                .unwrap_or("?");
+            let label = if self.capturing == Some(direction) {
+                format!("Press the key for {}...", input::direction_name(direction))
+            } else {
+                format!("{:<8}{}", input::direction_name(direction), key)
+            };
+            let color = if i == self.selected { Color::YELLOW } else { Color::WHITE };
+            let row_text = graphics::Text::new(label);
+            graphics::draw(
+                ctx,
+                &row_text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 {
+                        x: menu_x + 40.0,
+                        y: menu_y + 70.0 + i as f32 * ROW_SPACING,
+                    })
+                    .color(color),
+            )?;
+        }
+
+        //option rows below the bindings
+        let window_label = if settings.fullscreen { "Fullscreen" } else { "Windowed" };
+        let stats_label = match settings.stats_export {
+            None => "Off",
+            Some(StatsFormat::Json) => "JSON",
+            Some(StatsFormat::Csv) => "CSV",
+        };
+        let telemetry_label = if settings.telemetry { "On" } else { "Off" };
+        let fps_label = match settings.fps_cap {
+            0 => "Unlimited".to_string(),
+            cap => cap.to_string(),
+        };
+        let speed_label = format!("{}%", settings.assists.game_speed);
+        let min_scale_label = format!("{:.0}%", settings.render_scale_min * 100.0);
+        let max_scale_label = format!("{:.0}%", settings.render_scale_max * 100.0);
+        let access_label = match (settings.high_contrast, settings.announcements) {
+            (false, false) => "Off",
+            (true, false) => "Contrast",
+            (false, true) => "Announce",
+            (true, true) => "Both",
+        };
+        let shown = |on: bool| if on { "Shown" } else { "Off" };
+        let options = [
+            (WINDOW_ROW, "Window", window_label),
+            (STATS_ROW, "Stats", stats_label),
+            (TELEMETRY_ROW, "Log", telemetry_label),
+            (UPDATES_ROW, "Updates", if settings.check_updates { "Check" } else { "Off" }),
+            (FPS_ROW, "FPS cap", fps_label.as_str()),
+            (VSYNC_ROW, "Vsync", if settings.vsync { "On" } else { "Off" }),
+            (MOTION_ROW, "Motion", if settings.smooth_motion { "Smooth" } else { "Raw" }),
+            (MIN_SCALE_ROW, "Min res", min_scale_label.as_str()),
+            (MAX_SCALE_ROW, "Max res", max_scale_label.as_str()),
+            (DIFFICULTY_ROW, "Skill", settings.difficulty.name()),
+            (PELLETS_ROW, "Pellets", settings.pellet_stacking.name()),
+            (FRUIT_ROW, "Fruit", if settings.moving_fruit { "Wander" } else { "Still" }),
+            (SPEED_ROW, "Speed", speed_label.as_str()),
+            (TURNS_ROW, "Turns", if settings.assists.intersection_pause { "Pause" } else { "Off" }),
+            (ACCESS_ROW, "Access", access_label),
+            (SLOWMO_ROW, "Slow-mo", if settings.close_call_slowmo { "On" } else { "Off" }),
+            (TIMER_ROW, "Timer", shown(settings.hud.level_time)),
+            (DOTS_ROW, "Dots", shown(settings.hud.dots_left)),
+            (GHOSTS_ROW, "Ghosts", shown(settings.hud.ghost_mode)),
+            (GLOW_ROW, "Glow", shown(settings.hud.edge_glow)),
+        ];
+        for &(row, name, value) in options.iter() {
+            let color = if self.selected == row { Color::YELLOW } else { Color::WHITE };
+            let option_text = graphics::Text::new(format!("{:<8}{}", name, value));
+            graphics::draw(
+                ctx,
+                &option_text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 {
+                        x: menu_x + 40.0,
+                        y: menu_y + 70.0 + row as f32 * ROW_SPACING,
+                    })
+                    .color(color),
+            )?;
+        }
+
+        //where the session log goes, so players can attach it to bug reports
+        if self.selected == TELEMETRY_ROW {
+            let mut location = graphics::Text::new(format!("Session logs: {}", paths::logs_dir().display()));
+            location.set_bounds(
+                ggez::mint::Point2 { x: menu_width - 40.0, y: f32::INFINITY },
+                graphics::Align::Left,
+            );
+            graphics::draw(
+                ctx,
+                &location,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 { x: menu_x + 20.0, y: menu_y + 575.0 })
+                    .color(Color::new(0.7, 0.7, 0.7, 1.0)),
+            )?;
+        }
+
+        //duplicate warnings and other feedback
+        if let Some(message) = &self.message {
+            let message_text = graphics::Text::new(message.as_str());
+            graphics::draw(
+                ctx,
+                &message_text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 { x: menu_x + 20.0, y: menu_y + 637.0 })
+                    .color(Color::RED),
+            )?;
+        }
+
+        let hint = graphics::Text::new("Enter: change   Esc: close");
+        graphics::draw(
+            ctx,
+            &hint,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: menu_x + 20.0, y: menu_y + 662.0 })
+                .color(Color::WHITE),
+        )?;
+        Ok(())
+    }
+}
+
+//the render scale a step up from `scale`, or the lowest after full resolution. Rounded to the
+//step, so a scale loaded from the settings file lands back on one
+fn next_scale(scale: f32) -> f32 {
+    let next = ((scale / SCALE_STEP).round() + 1.0) * SCALE_STEP;
+    if next > 1.0 + SCALE_STEP / 2.0 {
+        LOWEST_SCALE
+    } else {
+        next.min(1.0)
+    }
+}
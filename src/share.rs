@@ -0,0 +1,269 @@
+//share codes: a short code for a game that someone else can type into the main menu to play the
+//same challenge. It holds which maze, the seed and the rules, and as the game is deterministic
+//that's enough for the same ghosts, the same fruit and the same everything else, move for move.
+//The code is base 32 of a few bytes: rules only take up room where they differ from the arcade's
+use crate::lobby::{code_digit, CODE_ALPHABET};
+use crate::map::Map;
+use crate::replay;
+use crate::rules::{AggressionRow, Ruleset};
+use crate::settings::{Difficulty, PelletStacking};
+
+//digits between the dashes
+const GROUP: usize = 4;
+
+//which of the ruleset's fields follow the header, one bit each
+const GHOSTS: u8 = 1;
+const PELLET_SECONDS: u8 = 1 << 1;
+const FRUIT_DOTS: u8 = 1 << 2;
+const PACMAN_SPEED: u8 = 1 << 3;
+const GHOST_SPEED: u8 = 1 << 4;
+const SWITCHES: u8 = 1 << 5;
+const SCATTER_CHASE: u8 = 1 << 6;
+const AGGRESSION: u8 = 1 << 7;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Challenge {
+    //see map_id
+    pub map_id: u32,
+    pub seed: u64,
+    pub ruleset: Ruleset,
+    //the settings a game copies when it starts that change what happens in it, as replays keep
+    pub difficulty: Difficulty,
+    pub pellet_stacking: PelletStacking,
+    pub moving_fruit: bool,
+}
+
+//the maze's rows, hashed down to 32 bits. The rules are left out as the code carries them itself
+pub fn map_id(map: &Map) -> u32 {
+    let mut rows = map.clone();
+    rows.rules = Ruleset::default();
+    u64::from_str_radix(&replay::map_hash(&rows), 16).map_or(0, |hash| (hash >> 32) as u32)
+}
+
+impl Challenge {
+    //groups of four digits split by dashes. None if a list in the rules is too long to fit
+    pub fn encode(&self) -> Option<String> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.map_id.to_be_bytes());
+        bytes.extend_from_slice(&self.seed.to_be_bytes());
+        let difficulty = match self.difficulty {
+            Difficulty::Easy => 0,
+            Difficulty::Normal => 1,
+            Difficulty::Hard => 2,
+        };
+        let extend = (self.pellet_stacking == PelletStacking::Extend) as u8;
+        bytes.push(difficulty | (extend << 2) | ((self.moving_fruit as u8) << 3));
+        encode_rules(&self.ruleset, &mut bytes)?;
+        bytes.push(checksum(&bytes));
+
+        let mut code = String::new();
+        for (i, digit) in to_base32(&bytes).chars().enumerate() {
+            if i > 0 && i % GROUP == 0 {
+                code.push('-');
+            }
+            code.push(digit);
+        }
+        Some(code)
+    }
+
+    //reads a code back, ignoring case, dashes and spaces. None if it was mistyped or its rules
+    //wouldn't make a playable maze
+    pub fn decode(code: &str) -> Option<Challenge> {
+        let digits: Vec<u8> =
+            code.chars().filter(|&c| !matches!(c, '-' | ' ')).map(code_digit).collect::<Option<_>>()?;
+        let mut bytes = from_base32(&digits);
+        let check = bytes.pop()?;
+        if checksum(&bytes) != check {
+            return None;
+        }
+
+        let mut reader = Reader { bytes: &bytes, at: 0 };
+        let map_id = reader.u32()?;
+        let seed = ((reader.u32()? as u64) << 32) | reader.u32()? as u64;
+        let options = reader.u8()?;
+        let difficulty = match options & 3 {
+            0 => Difficulty::Easy,
+            1 => Difficulty::Normal,
+            2 => Difficulty::Hard,
+            _ => return None,
+        };
+        let pellet_stacking = if options & (1 << 2) != 0 { PelletStacking::Extend } else { PelletStacking::Refresh };
+        let ruleset = decode_rules(&mut reader)?;
+        if reader.at != bytes.len() || ruleset.check().is_err() {
+            return None;
+        }
+        Some(Challenge { map_id, seed, ruleset, difficulty, pellet_stacking, moving_fruit: options & (1 << 3) != 0 })
+    }
+}
+
+//a byte saying which fields differ from the default rules, then just those, in the bits' order.
+//Floats go in whole so the rules come back exactly
+fn encode_rules(rules: &Ruleset, bytes: &mut Vec<u8>) -> Option<()> {
+    let default = Ruleset::default();
+    let switches = |rules: &Ruleset| {
+        [
+            rules.house_arrest,
+            rules.wrap,
+            rules.regrow,
+            rules.power_ups.power_pellets,
+            rules.power_ups.scatter,
+            rules.power_ups.slow_pacman,
+            rules.power_ups.fruit,
+        ]
+        .iter()
+        .enumerate()
+        .fold(0u8, |bits, (i, &on)| bits | ((on as u8) << i))
+    };
+    let mut fields = 0;
+    let header = bytes.len();
+    bytes.push(0);
+    if rules.ghosts != default.ghosts {
+        fields |= GHOSTS;
+        bytes.push(u8::try_from(rules.ghosts).ok()?);
+    }
+    if rules.pellet_seconds != default.pellet_seconds {
+        fields |= PELLET_SECONDS;
+        bytes.extend_from_slice(&rules.pellet_seconds.to_bits().to_be_bytes());
+    }
+    if rules.fruit_dots != default.fruit_dots {
+        fields |= FRUIT_DOTS;
+        bytes.push(u8::try_from(rules.fruit_dots.len()).ok()?);
+        for dots in &rules.fruit_dots {
+            bytes.extend_from_slice(&dots.to_be_bytes());
+        }
+    }
+    if rules.pacman_speed != default.pacman_speed {
+        fields |= PACMAN_SPEED;
+        bytes.extend_from_slice(&rules.pacman_speed.to_bits().to_be_bytes());
+    }
+    if rules.ghost_speed != default.ghost_speed {
+        fields |= GHOST_SPEED;
+        bytes.extend_from_slice(&rules.ghost_speed.to_bits().to_be_bytes());
+    }
+    if switches(rules) != switches(&default) {
+        fields |= SWITCHES;
+        bytes.push(switches(rules));
+    }
+    if rules.scatter_chase != default.scatter_chase {
+        fields |= SCATTER_CHASE;
+        bytes.push(u8::try_from(rules.scatter_chase.len()).ok()?);
+        for seconds in &rules.scatter_chase {
+            bytes.extend_from_slice(&seconds.to_bits().to_be_bytes());
+        }
+    }
+    if rules.aggression != default.aggression {
+        fields |= AGGRESSION;
+        bytes.push(u8::try_from(rules.aggression.len()).ok()?);
+        for row in &rules.aggression {
+            bytes.extend_from_slice(&row.from_level.to_be_bytes());
+            bytes.extend_from_slice(&row.wander.to_bits().to_be_bytes());
+            bytes.extend_from_slice(&row.retarget.to_bits().to_be_bytes());
+        }
+    }
+    bytes[header] = fields;
+    Some(())
+}
+
+fn decode_rules(reader: &mut Reader) -> Option<Ruleset> {
+    let mut rules = Ruleset::default();
+    let fields = reader.u8()?;
+    if fields & GHOSTS != 0 {
+        rules.ghosts = reader.u8()? as usize;
+    }
+    if fields & PELLET_SECONDS != 0 {
+        rules.pellet_seconds = reader.f32()?;
+    }
+    if fields & FRUIT_DOTS != 0 {
+        let count = reader.u8()?;
+        rules.fruit_dots = (0..count).map(|_| reader.u32()).collect::<Option<_>>()?;
+    }
+    if fields & PACMAN_SPEED != 0 {
+        rules.pacman_speed = reader.f32()?;
+    }
+    if fields & GHOST_SPEED != 0 {
+        rules.ghost_speed = reader.f32()?;
+    }
+    if fields & SWITCHES != 0 {
+        let bits = reader.u8()?;
+        let on = |i: u8| bits & (1 << i) != 0;
+        rules.house_arrest = on(0);
+        rules.wrap = on(1);
+        rules.regrow = on(2);
+        rules.power_ups.power_pellets = on(3);
+        rules.power_ups.scatter = on(4);
+        rules.power_ups.slow_pacman = on(5);
+        rules.power_ups.fruit = on(6);
+    }
+    if fields & SCATTER_CHASE != 0 {
+        let count = reader.u8()?;
+        rules.scatter_chase = (0..count).map(|_| reader.f32()).collect::<Option<_>>()?;
+    }
+    if fields & AGGRESSION != 0 {
+        let count = reader.u8()?;
+        rules.aggression = (0..count)
+            .map(|_| Some(AggressionRow { from_level: reader.u32()?, wander: reader.f32()?, retarget: reader.f32()? }))
+            .collect::<Option<_>>()?;
+    }
+    Some(rules)
+}
+
+//catches most typos, so a mistyped code is turned down instead of starting some other game
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |sum, &byte| sum.wrapping_mul(31).wrapping_add(byte))
+}
+
+//five bits a digit, most significant first, with the last digit padded out with zeros
+fn to_base32(bytes: &[u8]) -> String {
+    let mut digits = String::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            digits.push(CODE_ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        digits.push(CODE_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    digits
+}
+
+//the padding bits left over at the end are dropped
+fn from_base32(digits: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for &digit in digits {
+        buffer = (buffer << 5) | digit as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    bytes
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl Reader<'_> {
+    fn u8(&mut self) -> Option<u8> {
+        let byte = *self.bytes.get(self.at)?;
+        self.at += 1;
+        Some(byte)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let bytes = self.bytes.get(self.at..self.at + 4)?;
+        self.at += 4;
+        Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        self.u32().map(f32::from_bits)
+    }
+}
@@ -0,0 +1,148 @@
+//`--simulate N`: plays N games headlessly with a scripted pacman against the ghost AI, spread over
+//every core, and prints how they went. Game i uses seed i, so runs are comparable between builds
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+use crate::env::{Observation, PacmanEnv, TILE_DOT, TILE_POWER_PELLET, TILE_WALL};
+use crate::{Direction, CELL_SIZE, GHOST_SIZE, PACMAN_SIZE, TICKS_PER_SECOND};
+
+//games that don't end are called off after ten minutes of play
+const MAX_TICKS: u32 = TICKS_PER_SECOND * 60 * 10;
+
+struct Outcome {
+    score: u32,
+    ticks: u32,
+    //the level the game reached, from 1
+    level: u32,
+}
+
+//runs the games and prints a summary to stdout
+pub fn run(games: usize) -> Result<(), String> {
+    if games == 0 {
+        return Err("--simulate needs at least one game".to_string());
+    }
+    let workers = thread::available_parallelism().map_or(1, |p| p.get()).min(games);
+    let next_game = AtomicUsize::new(0);
+    let outcomes = Mutex::new(Vec::with_capacity(games));
+    let started = Instant::now();
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| -> Result<(), String> {
+                    let mut env = PacmanEnv::new().map_err(|e| e.to_string())?;
+                    loop {
+                        let game = next_game.fetch_add(1, Ordering::Relaxed);
+                        if game >= games {
+                            return Ok(());
+                        }
+                        let outcome = play(&mut env, game as u64);
+                        outcomes.lock().unwrap().push(outcome);
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter().try_for_each(|handle| handle.join().unwrap_or_else(|_| Err("a simulation thread panicked".to_string())))
+    })?;
+
+    let elapsed = started.elapsed().as_secs_f64();
+    let outcomes = outcomes.into_inner().unwrap();
+    let count = outcomes.len() as f64;
+    let total_ticks: u64 = outcomes.iter().map(|o| o.ticks as u64).sum();
+    let average = |value: fn(&Outcome) -> f64| outcomes.iter().map(value).sum::<f64>() / count;
+    let min_score = outcomes.iter().map(|o| o.score).min().unwrap_or(0);
+    let max_score = outcomes.iter().map(|o| o.score).max().unwrap_or(0);
+
+    println!("Games:          {}", outcomes.len());
+    println!("Threads:        {}", workers);
+    println!("Average score:  {:.1} (min {}, max {})", average(|o| o.score as f64), min_score, max_score);
+    println!("Average level:  {:.2}", average(|o| o.level as f64));
+    println!("Average length: {:.1} s", average(|o| o.ticks as f64) / TICKS_PER_SECOND as f64);
+    println!("Ticks:          {}", total_ticks);
+    println!("Ticks/sec:      {:.0}", total_ticks as f64 / elapsed.max(f64::EPSILON));
+    println!("Wall time:      {:.2} s", elapsed);
+    Ok(())
+}
+
+fn play(env: &mut PacmanEnv, seed: u64) -> Outcome {
+    let mut observation = env.reset(seed);
+    let mut ticks = 0;
+    loop {
+        let (next, _, done) = env.step(choose_direction(&observation));
+        observation = next;
+        ticks += 1;
+        if done || ticks >= MAX_TICKS {
+            return Outcome { score: observation.score, ticks, level: observation.level };
+        }
+    }
+}
+
+//the cell holding the center of an entity `size` wide whose top-left is at `position` tiles
+fn cell_of(position: (f32, f32), size: f32) -> (i64, i64) {
+    let offset = size / 2.0 / CELL_SIZE;
+    ((position.0 + offset).floor() as i64, (position.1 + offset).floor() as i64)
+}
+
+//heads for the nearest dot along a path that keeps a cell away from dangerous ghosts. When
+//cornered it ignores the ghosts, and with nothing left to eat it stops
+pub fn choose_direction(observation: &Observation) -> Direction {
+    let start = cell_of(observation.pacman, PACMAN_SIZE);
+    let danger: Vec<(i64, i64)> = observation
+        .ghosts
+        .iter()
+        .filter(|ghost| !ghost.vulnerable)
+        .map(|ghost| cell_of((ghost.x, ghost.y), GHOST_SIZE))
+        .collect();
+    let near_ghost = |(col, row): (i64, i64)| danger.iter().any(|&(gc, gr)| (gc - col).abs() + (gr - row).abs() <= 1);
+    first_step_to_food(observation, start, &near_ghost)
+        .or_else(|| first_step_to_food(observation, start, &|_| false))
+        .unwrap_or(Direction::None)
+}
+
+//breadth-first search from `start` to the closest dot or power pellet, returning the first move
+fn first_step_to_food(observation: &Observation, start: (i64, i64), blocked: &dyn Fn((i64, i64)) -> bool) -> Option<Direction> {
+    let (width, height) = (observation.width as i64, observation.height as i64);
+    let tile = |(col, row): (i64, i64)| {
+        if col < 0 || row < 0 || col >= width || row >= height {
+            TILE_WALL
+        } else {
+            observation.tiles[(row * width + col) as usize]
+        }
+    };
+    let mut first_step = vec![None; observation.tiles.len()];
+    let mut queue = VecDeque::new();
+    for (direction, (dc, dr)) in [
+        (Direction::Up, (0, -1)),
+        (Direction::Down, (0, 1)),
+        (Direction::Left, (-1, 0)),
+        (Direction::Right, (1, 0)),
+    ] {
+        let cell = (start.0 + dc, start.1 + dr);
+        if tile(cell) != TILE_WALL && !blocked(cell) {
+            first_step[(cell.1 * width + cell.0) as usize] = Some(direction);
+            queue.push_back(cell);
+        }
+    }
+    while let Some(cell) = queue.pop_front() {
+        let direction = first_step[(cell.1 * width + cell.0) as usize];
+        if matches!(tile(cell), TILE_DOT | TILE_POWER_PELLET) {
+            return direction;
+        }
+        for (dc, dr) in [(0, -1), (0, 1), (-1, 0), (1, 0)] {
+            let next = (cell.0 + dc, cell.1 + dr);
+            if next == start || tile(next) == TILE_WALL || blocked(next) {
+                continue;
+            }
+            let index = (next.1 * width + next.0) as usize;
+            if first_step[index].is_none() {
+                first_step[index] = direction;
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
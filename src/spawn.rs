@@ -0,0 +1,153 @@
+//where pacman and the ghosts start. The spots a map marks with 'P' and 'G' are collected first and
+//the ghost roster is built from them afterwards, so how many ghosts play doesn't depend on how
+//many 'G's a map happens to have
+use ggez::graphics::Color;
+use std::collections::VecDeque;
+
+use crate::fixed::Fixed;
+use crate::maze::Layout;
+use crate::{Ghost, Personality, CELL, GHOST, PACMAN, TICKS_PER_SECOND};
+
+//ghosts leave the pen one after another, this far apart
+const RELEASE_INTERVAL_TICKS: u32 = 3 * TICKS_PER_SECOND;
+
+//where pacman and each ghost of the roster start, as (column, row). All of them are open tiles
+pub struct SpawnPoints {
+    pacman_tile: (usize, usize),
+    ghost_tiles: Vec<(usize, usize)>,
+    //the maze's size in cells, for the ghosts' scatter corners
+    size: (usize, usize),
+}
+
+//one ghost of the roster
+pub struct GhostSpec {
+    pub name: &'static str,
+    pub nickname: &'static str,
+    pub personality: Personality,
+    pub color: Color,
+}
+
+//every game plays these ghosts, in this order, starting over for mazes whose rules want more
+pub const ROSTER: [GhostSpec; 3] = [
+    GhostSpec { name: "Blinky", nickname: "Shadow", personality: Personality::Chaser, color: Color::RED },
+    GhostSpec { name: "Inky", nickname: "Bashful", personality: Personality::Drifter, color: Color::CYAN },
+    GhostSpec { name: "Pinky", nickname: "Speedy", personality: Personality::Wanderer, color: Color::MAGENTA },
+];
+
+//reads the spawn tiles off the maze once it's loaded. Anyone who would start inside a wall or
+//the void is moved to the nearest open tile, with a warning
+pub fn collect(rows: &[&str], layout: &Layout, ghost_count: usize) -> SpawnPoints {
+    let mut pacman = None;
+    let mut ghosts = Vec::new();
+    for (y, row) in rows.iter().enumerate() {
+        for (x, cell) in row.chars().enumerate() {
+            match cell {
+                //the first 'P' wins
+                'P' if pacman.is_none() => pacman = Some((x, y)),
+                'G' => ghosts.push((x, y)),
+                _ => {}
+            }
+        }
+    }
+
+    //maps that don't say start everyone in the middle
+    let width = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+    let center = (width / 2, rows.len() / 2);
+    if pacman.is_none() {
+        eprintln!("The maze has no 'P' cell, pacman starts in the middle");
+    }
+    if ghosts.is_empty() {
+        eprintln!("The maze has no 'G' cell, the ghosts start in the middle");
+    }
+    let pacman_tile = checked(layout, pacman.unwrap_or(center), "pacman");
+    let ghost_tiles = spread(layout, &ghosts, checked(layout, center, "the ghosts"), ghost_count);
+    SpawnPoints { pacman_tile, ghost_tiles, size: (width, rows.len()) }
+}
+
+//`tile` if it's open, otherwise the open tile nearest to it
+fn checked(layout: &Layout, tile: (usize, usize), who: &str) -> (usize, usize) {
+    if layout.is_open(tile.0 as i64, tile.1 as i64) {
+        return tile;
+    }
+    match layout.nearest_open(tile) {
+        Some(open) => {
+            eprintln!(
+                "The spawn for {} at ({}, {}) is blocked, using ({}, {}) instead",
+                who, tile.0, tile.1, open.0, open.1
+            );
+            open
+        }
+        None => {
+            eprintln!("The maze has no open cell for {} to start on", who);
+            tile
+        }
+    }
+}
+
+//`count` open tiles to start ghosts on: the 'G' tiles first, then the open tiles next to them,
+//sideways before up and down, so ghosts fill out the pen instead of piling onto one spot.
+//Without 'G' tiles they start around `fallback`
+fn spread(layout: &Layout, marked: &[(usize, usize)], fallback: (usize, usize), count: usize) -> Vec<(usize, usize)> {
+    let starts: Vec<(usize, usize)> = if marked.is_empty() { vec![fallback] } else { marked.to_vec() };
+    let mut tiles: Vec<(usize, usize)> = Vec::new();
+    let mut queue: VecDeque<(usize, usize)> = starts.iter().copied().collect();
+    while let Some(tile) = queue.pop_front() {
+        if tiles.len() == count {
+            break;
+        }
+        if tiles.contains(&tile) || !layout.is_open(tile.0 as i64, tile.1 as i64) {
+            continue;
+        }
+        tiles.push(tile);
+        let (col, row) = (tile.0 as i64, tile.1 as i64);
+        for (next_col, next_row) in [(col - 1, row), (col + 1, row), (col, row - 1), (col, row + 1)] {
+            if next_col >= 0 && next_row >= 0 {
+                queue.push_back((next_col as usize, next_row as usize));
+            }
+        }
+    }
+    //a pen too small for everyone gets shared
+    if tiles.is_empty() {
+        tiles.push(fallback);
+    }
+    (0..count).map(|index| tiles[index % tiles.len()]).collect()
+}
+
+impl SpawnPoints {
+    pub fn pacman_tile(&self) -> (usize, usize) {
+        self.pacman_tile
+    }
+
+    //pacman's top-left corner, centered in his tile
+    pub fn pacman_start(&self) -> (Fixed, Fixed) {
+        let (x, y) = self.pacman_tile;
+        (CELL * x as i32 + (CELL - PACMAN).half(), CELL * y as i32 + (CELL - PACMAN).half())
+    }
+
+    //the corner the `index`th ghost scatters to: Blinky's top right, Inky's bottom right, Pinky's
+    //top left, and the bottom left for a fourth
+    fn scatter_corner(&self, index: usize) -> (Fixed, Fixed) {
+        let (right, bottom) = (self.size.0.saturating_sub(1), self.size.1.saturating_sub(1));
+        let (col, row) = [(right, 0), (right, bottom), (0, 0), (0, bottom)][index % 4];
+        (CELL * col as i32, CELL * row as i32)
+    }
+
+    //the ghosts a game starts with, each on its own tile and released from the pen in turn
+    pub fn roster(&self) -> Vec<Ghost> {
+        ROSTER
+            .iter()
+            .cycle()
+            .zip(&self.ghost_tiles)
+            .enumerate()
+            .map(|(index, (spec, &(x, y)))| {
+                let left = CELL * x as i32 + (CELL - GHOST).half();
+                let top = CELL * y as i32 + (CELL - GHOST).half();
+                let mut ghost = Ghost::new(left.to_f32(), top.to_f32(), spec.color);
+                ghost.personality = spec.personality;
+                ghost.scatter_target = self.scatter_corner(index);
+                ghost.hold_in_pen(index as u32 * RELEASE_INTERVAL_TICKS);
+                ghost
+            })
+            .collect()
+    }
+}
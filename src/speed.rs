@@ -0,0 +1,123 @@
+//how fast everyone moves, as fractions of a base speed that change with the level like the
+//arcade's table. The table can be tuned in `speeds.toml` in the config folder
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::fixed::Fixed;
+use crate::paths;
+
+//the speeds from `from_level` on, until the next row's level
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct LevelSpeeds {
+    pub from_level: u32,
+    pub pacman: f32,
+    //while a power pellet is active
+    pub pacman_frightened: f32,
+    pub ghost: f32,
+    pub ghost_frightened: f32,
+    //in a side tunnel, frightened or not
+    pub ghost_tunnel: f32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeedTable {
+    //pixels per tick at 100%
+    pub base: f32,
+    pub levels: Vec<LevelSpeeds>,
+}
+
+impl Default for SpeedTable {
+    fn default() -> Self {
+        let row = |from_level, pacman, pacman_frightened, ghost, ghost_frightened, ghost_tunnel| LevelSpeeds {
+            from_level,
+            pacman,
+            pacman_frightened,
+            ghost,
+            ghost_frightened,
+            ghost_tunnel,
+        };
+        SpeedTable {
+            //pacman's 80% on the first level comes out at a pixel a tick
+            base: 1.25,
+            levels: vec![
+                row(1, 0.80, 0.90, 0.75, 0.50, 0.40),
+                row(2, 0.90, 0.95, 0.85, 0.55, 0.45),
+                row(5, 1.00, 1.00, 0.95, 0.60, 0.50),
+                //the arcade stops frightening ghosts here, the frightened speeds only matter to mods
+                row(21, 0.90, 0.90, 0.95, 0.60, 0.50),
+            ],
+        }
+    }
+}
+
+//one level's speeds in fixed point, per tick
+#[derive(Clone, Copy)]
+pub struct Speeds {
+    pub pacman: Fixed,
+    pub pacman_frightened: Fixed,
+    pub ghost: GhostSpeeds,
+}
+
+//kept on each ghost, so a ghost moves the same however it's updated
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct GhostSpeeds {
+    pub normal: Fixed,
+    pub frightened: Fixed,
+    //saves from before tunnels go by the first level's
+    #[serde(default = "first_level_tunnel")]
+    pub tunnel: Fixed,
+}
+
+fn first_level_tunnel() -> Fixed {
+    GhostSpeeds::default().tunnel
+}
+
+impl Default for GhostSpeeds {
+    fn default() -> Self {
+        SpeedTable::default().for_level(1).ghost
+    }
+}
+
+impl SpeedTable {
+    //`speeds.toml` from the config folder, or the arcade table without one
+    pub fn load() -> SpeedTable {
+        let path = paths::speeds_file();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(_) => return SpeedTable::default(),
+        };
+        match toml::from_str::<SpeedTable>(&contents) {
+            Ok(table) if !table.levels.is_empty() => table,
+            Ok(_) => {
+                eprintln!("Ignoring {}: it has no levels", path.display());
+                SpeedTable::default()
+            }
+            Err(e) => {
+                eprintln!("Ignoring {}: {}", path.display(), e);
+                SpeedTable::default()
+            }
+        }
+    }
+
+    //the last row starting at or before `level`, or the first row for earlier levels
+    pub fn for_level(&self, level: u32) -> Speeds {
+        let fallback = SpeedTable::default();
+        let levels = if self.levels.is_empty() { &fallback.levels } else { &self.levels };
+        let row = levels
+            .iter()
+            .filter(|row| row.from_level <= level)
+            .max_by_key(|row| row.from_level)
+            .unwrap_or(&levels[0]);
+        let step = |fraction: f32| Fixed::from_f32(self.base * fraction);
+        Speeds {
+            pacman: step(row.pacman),
+            pacman_frightened: step(row.pacman_frightened),
+            ghost: GhostSpeeds {
+                normal: step(row.ghost),
+                frightened: step(row.ghost_frightened),
+                tunnel: step(row.ghost_tunnel),
+            },
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//images shared by batched drawing, so entities cost one draw call per batch instead of a mesh each
+use ggez::graphics::{self, Image};
+use ggez::{Context, GameResult};
+
+//the circle texture's side in pixels, scaled down to entity size when drawn
+pub const CIRCLE_SIZE: u16 = 64;
+
+//a white filled circle with a soft edge. Drawing it with a colour tints it
+pub fn circle(ctx: &mut Context) -> GameResult<Image> {
+    let size = CIRCLE_SIZE as usize;
+    let radius = size as f32 / 2.0;
+    let mut pixels = Vec::with_capacity(size * size * 4);
+    for y in 0..size {
+        for x in 0..size {
+            let dx = x as f32 + 0.5 - radius;
+            let dy = y as f32 + 0.5 - radius;
+            //one pixel of antialiasing around the edge
+            let coverage = (radius - (dx * dx + dy * dy).sqrt() + 0.5).clamp(0.0, 1.0);
+            pixels.extend_from_slice(&[255, 255, 255, (coverage * 255.0) as u8]);
+        }
+    }
+    Image::from_rgba8(ctx, CIRCLE_SIZE, CIRCLE_SIZE, &pixels)
+}
+
+//where to draw the circle so it fills the `size` square at (x, y), tinted `color`
+pub fn circle_param(x: f32, y: f32, size: f32, color: graphics::Color) -> graphics::DrawParam {
+    let scale = size / CIRCLE_SIZE as f32;
+    graphics::DrawParam::default()
+        .dest(ggez::mint::Point2 { x, y })
+        .scale(ggez::mint::Vector2 { x: scale, y: scale })
+        .color(color)
+}
@@ -0,0 +1,158 @@
+//per-game statistics and per-tick traces, exported as JSON or CSV for analysis
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::paths;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum StatsFormat {
+    Json,
+    Csv,
+}
+
+impl StatsFormat {
+    //picks the format from a file extension, defaulting to JSON
+    pub fn from_path(path: &Path) -> StatsFormat {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => StatsFormat::Csv,
+            _ => StatsFormat::Json,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            StatsFormat::Json => "json",
+            StatsFormat::Csv => "csv",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize)]
+pub struct TraceRow {
+    pub tick: u32,
+    pub score: u32,
+    pub lives: i32,
+    pub pacman_x: f32,
+    pub pacman_y: f32,
+}
+
+#[derive(Default, Serialize)]
+pub struct GameStats {
+    pub seed: u64,
+    pub ticks: u32,
+    pub final_score: u32,
+    pub lives_left: i32,
+    pub dots_eaten: u32,
+    pub power_pellets_eaten: u32,
+    pub ghosts_eaten: u32,
+    pub deaths: u32,
+    #[serde(skip)]
+    pub trace: Vec<TraceRow>,
+}
+
+#[derive(Clone, Copy)]
+pub struct StatsMark {
+    ticks: u32,
+    final_score: u32,
+    lives_left: i32,
+    dots_eaten: u32,
+    power_pellets_eaten: u32,
+    ghosts_eaten: u32,
+    deaths: u32,
+}
+
+#[derive(Serialize)]
+struct JsonExport<'a> {
+    summary: &'a GameStats,
+    trace: &'a [TraceRow],
+}
+
+impl GameStats {
+    pub fn new(seed: u64) -> Self {
+        GameStats { seed, ..GameStats::default() }
+    }
+
+    pub fn record_tick(&mut self, score: u32, lives: i32, pacman_x: f32, pacman_y: f32) {
+        self.trace.push(TraceRow { tick: self.ticks, score, lives, pacman_x, pacman_y });
+        self.ticks += 1;
+        self.final_score = score;
+        self.lives_left = lives;
+    }
+
+    //the counters so far, cheap enough to keep one per tick for rollback
+    pub fn mark(&self) -> StatsMark {
+        StatsMark {
+            ticks: self.ticks,
+            final_score: self.final_score,
+            lives_left: self.lives_left,
+            dots_eaten: self.dots_eaten,
+            power_pellets_eaten: self.power_pellets_eaten,
+            ghosts_eaten: self.ghosts_eaten,
+            deaths: self.deaths,
+        }
+    }
+
+    //undoes every tick recorded since `mark` was taken
+    pub fn rewind(&mut self, mark: StatsMark) {
+        self.ticks = mark.ticks;
+        self.final_score = mark.final_score;
+        self.lives_left = mark.lives_left;
+        self.dots_eaten = mark.dots_eaten;
+        self.power_pellets_eaten = mark.power_pellets_eaten;
+        self.ghosts_eaten = mark.ghosts_eaten;
+        self.deaths = mark.deaths;
+        self.trace.truncate(mark.ticks as usize);
+    }
+
+    //writes the stats to `path`. CSV puts the trace there and the summary next to it
+    //in `<name>_summary.csv`
+    pub fn export(&self, path: &Path, format: StatsFormat) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            if !dir.as_os_str().is_empty() {
+                fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+        }
+        match format {
+            StatsFormat::Json => {
+                let export = JsonExport { summary: self, trace: &self.trace };
+                let json = serde_json::to_string_pretty(&export).map_err(|e| e.to_string())?;
+                fs::write(path, json).map_err(|e| e.to_string())
+            }
+            StatsFormat::Csv => {
+                let mut trace = String::from("tick,score,lives,pacman_x,pacman_y\n");
+                for row in &self.trace {
+                    trace.push_str(&format!(
+                        "{},{},{},{:.2},{:.2}\n",
+                        row.tick, row.score, row.lives, row.pacman_x, row.pacman_y
+                    ));
+                }
+                fs::write(path, trace).map_err(|e| e.to_string())?;
+
+                let summary = format!(
+                    "seed,ticks,final_score,lives_left,dots_eaten,power_pellets_eaten,ghosts_eaten,deaths\n{},{},{},{},{},{},{},{}\n",
+                    self.seed,
+                    self.ticks,
+                    self.final_score,
+                    self.lives_left,
+                    self.dots_eaten,
+                    self.power_pellets_eaten,
+                    self.ghosts_eaten,
+                    self.deaths
+                );
+                let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("stats");
+                fs::write(path.with_file_name(format!("{}_summary.csv", stem)), summary).map_err(|e| e.to_string())
+            }
+        }
+    }
+}
+
+//default export location for finished games when the settings option is on
+pub fn default_export_path(format: StatsFormat) -> PathBuf {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    paths::stats_dir().join(format!("game-{}.{}", stamp, format.extension()))
+}
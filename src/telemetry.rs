@@ -0,0 +1,156 @@
+//opt-in session log for diagnosing bug reports: one JSON event per line in the logs
+//folder, one file per session. Nothing is sent anywhere
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{LineWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::paths;
+
+//frame times are summarized every this many frames
+const FRAME_WINDOW: usize = 600;
+//older session logs are deleted when a new one starts
+const MAX_SESSION_LOGS: usize = 10;
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    SessionStart { version: &'a str, os: &'a str },
+    SessionEnd,
+    StateChange { from: &'a str, to: &'a str },
+    Death { tick: u32, lives_left: i32, score: u32 },
+    LevelComplete { level: u32, tick: u32, score: u32 },
+    GameOver { tick: u32, score: u32 },
+    FrameTimes { frames: usize, p50_ms: f32, p95_ms: f32, p99_ms: f32, max_ms: f32 },
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    time: String,
+    #[serde(flatten)]
+    event: &'a Event<'a>,
+}
+
+struct SessionLog {
+    path: PathBuf,
+    writer: LineWriter<File>,
+}
+
+pub struct Telemetry {
+    log: Option<SessionLog>,
+    frame_times: Vec<f32>,
+    state: &'static str,
+}
+
+impl Telemetry {
+    pub fn disabled() -> Telemetry {
+        Telemetry {
+            log: None,
+            frame_times: Vec::new(),
+            state: "",
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.log.is_some()
+    }
+
+    //starts a new session file or ends the current one
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled == self.enabled() {
+            return;
+        }
+        if !enabled {
+            self.flush_frame_times();
+            self.log(Event::SessionEnd);
+            self.log = None;
+            return;
+        }
+
+        let dir = paths::logs_dir();
+        remove_old_logs(&dir);
+        let path = dir.join(format!("session-{}.jsonl", chrono::Local::now().format("%Y-%m-%d_%H-%M-%S")));
+        match File::create(&path) {
+            Ok(file) => {
+                self.log = Some(SessionLog { path, writer: LineWriter::new(file) });
+                self.state = "";
+                self.log(Event::SessionStart {
+                    version: env!("CARGO_PKG_VERSION"),
+                    os: std::env::consts::OS,
+                });
+            }
+            Err(e) => eprintln!("Could not start session log {}: {}", path.display(), e),
+        }
+    }
+
+    pub fn log(&mut self, event: Event) {
+        let log = match &mut self.log {
+            Some(log) => log,
+            None => return,
+        };
+        let line = Line {
+            time: chrono::Local::now().to_rfc3339(),
+            event: &event,
+        };
+        if let Ok(json) = serde_json::to_string(&line) {
+            //a failing log must never take the game down with it
+            if writeln!(log.writer, "{}", json).is_err() {
+                eprintln!("Session log stopped: could not write to {}", log.path.display());
+                self.log = None;
+            }
+        }
+    }
+
+    //records which screen the game is on, logging changes
+    pub fn set_state(&mut self, state: &'static str) {
+        if state != self.state {
+            let from = self.state;
+            self.state = state;
+            self.log(Event::StateChange { from, to: state });
+        }
+    }
+
+    pub fn record_frame(&mut self, seconds: f32) {
+        if !self.enabled() {
+            return;
+        }
+        self.frame_times.push(seconds * 1000.0);
+        if self.frame_times.len() >= FRAME_WINDOW {
+            self.flush_frame_times();
+        }
+    }
+
+    fn flush_frame_times(&mut self) {
+        if self.frame_times.is_empty() {
+            return;
+        }
+        let mut times = std::mem::take(&mut self.frame_times);
+        times.sort_by(|a, b| a.total_cmp(b));
+        let percentile = |p: f32| times[((times.len() - 1) as f32 * p).round() as usize];
+        let event = Event::FrameTimes {
+            frames: times.len(),
+            p50_ms: percentile(0.5),
+            p95_ms: percentile(0.95),
+            p99_ms: percentile(0.99),
+            max_ms: times[times.len() - 1],
+        };
+        self.log(event);
+    }
+}
+
+//keeps the newest logs so the folder doesn't grow forever; the timestamped names sort by age
+fn remove_old_logs(dir: &Path) {
+    let mut logs: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+            .collect(),
+        Err(_) => return,
+    };
+    logs.sort();
+    let excess = (logs.len() + 1).saturating_sub(MAX_SESSION_LOGS);
+    for path in logs.iter().take(excess) {
+        let _ = fs::remove_file(path);
+    }
+}
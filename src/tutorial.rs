@@ -0,0 +1,114 @@
+//the guided first game: a prompt at a time over a practice game, each one cleared by doing what
+//it asks, as seen in the same hooks the mods get
+use ggez::graphics::{self, Color, DrawParam};
+use ggez::{Context, GameResult};
+
+use crate::input::{self, Bindings, BINDABLE_DIRECTIONS};
+use crate::mods::Hook;
+use crate::TICKS_PER_SECOND;
+
+//dots to eat before moving on to the power pellet
+const DOTS_TO_EAT: u32 = 10;
+//how long the last message stays up
+const DONE_TICKS: u32 = 3 * TICKS_PER_SECOND;
+
+enum Step {
+    Move,
+    Dots { eaten: u32 },
+    PowerPellet,
+    CatchGhost,
+    Done { ticks_left: u32 },
+}
+
+pub struct Tutorial {
+    step: Step,
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Tutorial { step: Step::Move }
+    }
+
+    //moves on when this tick's hooks show the current objective was met
+    pub fn observe(&mut self, hooks: &[Hook], moving: bool, power_active: bool) {
+        let eaten_now = |power: bool| {
+            hooks.iter().filter(|hook| matches!(hook, Hook::PelletEaten { power: p, .. } if *p == power)).count()
+        };
+        let next = match self.step {
+            Step::Move if moving => Some(Step::Dots { eaten: 0 }),
+            Step::Dots { eaten } => {
+                let eaten = eaten + eaten_now(false) as u32;
+                Some(if eaten >= DOTS_TO_EAT { Step::PowerPellet } else { Step::Dots { eaten } })
+            }
+            Step::PowerPellet if eaten_now(true) > 0 => Some(Step::CatchGhost),
+            Step::CatchGhost if hooks.iter().any(|hook| matches!(hook, Hook::GhostEaten(_))) => {
+                Some(Step::Done { ticks_left: DONE_TICKS })
+            }
+            //the ghosts turned back before one was caught, so it's another pellet
+            Step::CatchGhost if !power_active => Some(Step::PowerPellet),
+            Step::Done { ticks_left } => Some(Step::Done { ticks_left: ticks_left.saturating_sub(1) }),
+            _ => None,
+        };
+        if let Some(next) = next {
+            self.step = next;
+        }
+    }
+
+    //every objective met
+    pub fn completed(&self) -> bool {
+        matches!(self.step, Step::Done { .. })
+    }
+
+    //the last message has been up long enough
+    pub fn over(&self) -> bool {
+        matches!(self.step, Step::Done { ticks_left: 0 })
+    }
+
+    fn prompt(&self, bindings: &Bindings) -> String {
+        match self.step {
+            Step::Move => {
+                let keys: Vec<&str> = BINDABLE_DIRECTIONS
+                    .iter()
+                    .map(|&direction| bindings.key_for(direction).and_then(input::key_name).unwrap_or("?"))
+                    .collect();
+                format!("Press {} to move", keys.join(" / "))
+            }
+            Step::Dots { eaten } => format!("Eat the dots ({}/{})", eaten, DOTS_TO_EAT),
+            Step::PowerPellet => "Eat a large pellet in a corner".to_string(),
+            Step::CatchGhost => "Now catch a blue ghost before it turns back!".to_string(),
+            Step::Done { .. } => "Well done! Clear the maze to win".to_string(),
+        }
+    }
+
+    //the prompt in a band across the top of the board
+    pub fn draw(&self, ctx: &mut Context, bindings: &Bindings, top: f32) -> GameResult {
+        let (w, _) = graphics::drawable_size(ctx);
+        let band = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, top, w, 50.0),
+            Color::new(0.0, 0.0, 0.0, 0.7),
+        )?;
+        graphics::draw(ctx, &band, DrawParam::default())?;
+
+        let prompt = graphics::Text::new(self.prompt(bindings));
+        let dims = prompt.dimensions(ctx);
+        graphics::draw(
+            ctx,
+            &prompt,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: (w - dims.w) / 2.0, y: top + 8.0 })
+                .color(Color::YELLOW),
+        )?;
+
+        let hint = graphics::Text::new("Backspace: skip tutorial");
+        let hint_dims = hint.dimensions(ctx);
+        graphics::draw(
+            ctx,
+            &hint,
+            DrawParam::default()
+                .dest(ggez::mint::Point2 { x: (w - hint_dims.w) / 2.0, y: top + 28.0 })
+                .color(Color::new(0.7, 0.7, 0.7, 1.0)),
+        )
+    }
+}
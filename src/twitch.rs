@@ -0,0 +1,140 @@
+//Twitch chat interaction: viewers vote with chat commands and the winning effect
+//is injected into the game every voting round
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::events::Effect;
+
+const IRC_ADDR: &str = "irc.chat.twitch.tv:6667";
+const ROUND_SECS: u64 = 60;
+const RECONNECT_SECS: u64 = 10;
+
+//chat command for every effect chat can vote on
+const COMMANDS: [(&str, Effect); 3] = [
+    ("!scatter", Effect::Scatter),
+    ("!slow", Effect::SlowPacman),
+    ("!fruit", Effect::SpawnFruit),
+];
+
+//what the HUD shows about the current round
+#[derive(Clone, Default)]
+pub struct VoteStatus {
+    pub connected: bool,
+    pub seconds_left: u64,
+    pub tallies: Vec<(&'static str, u32)>,
+}
+
+pub struct TwitchChat {
+    status: Arc<Mutex<VoteStatus>>,
+}
+
+impl TwitchChat {
+    //joins the channel anonymously on a background thread
+    pub fn connect(channel: &str, effects: Sender<Effect>) -> TwitchChat {
+        let status = Arc::new(Mutex::new(VoteStatus::default()));
+        let thread_status = Arc::clone(&status);
+        let channel = channel.trim_start_matches('#').to_lowercase();
+        thread::spawn(move || loop {
+            if let Err(e) = run_session(&channel, &effects, &thread_status) {
+                eprintln!("Twitch chat disconnected: {}", e);
+            }
+            if let Ok(mut status) = thread_status.lock() {
+                status.connected = false;
+            }
+            thread::sleep(Duration::from_secs(RECONNECT_SECS));
+        });
+        TwitchChat { status }
+    }
+
+    pub fn status(&self) -> VoteStatus {
+        self.status.lock().map(|status| status.clone()).unwrap_or_default()
+    }
+}
+
+fn run_session(channel: &str, effects: &Sender<Effect>, status: &Mutex<VoteStatus>) -> std::io::Result<()> {
+    let mut stream = TcpStream::connect(IRC_ADDR)?;
+    //short timeout so the round timer keeps running while chat is quiet
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+    //anonymous read-only login
+    write!(stream, "PASS SCHMOOPIIE\r\nNICK justinfan{}\r\nJOIN #{}\r\n", 10000 + std::process::id() % 80000, channel)?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    let mut votes: HashMap<String, Effect> = HashMap::new();
+    let mut round_start = Instant::now();
+
+    loop {
+        match reader.read_line(&mut line) {
+            Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+            Ok(_) => {
+                handle_line(line.trim_end(), &mut stream, &mut votes)?;
+                line.clear();
+            }
+            //a timeout keeps any partial line in the buffer for the next read
+            Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {}
+            Err(e) => return Err(e),
+        }
+
+        let elapsed = round_start.elapsed().as_secs();
+        if elapsed >= ROUND_SECS {
+            if let Some(winner) = winning_effect(&votes) {
+                //the game has gone away, nothing left to do
+                if effects.send(winner).is_err() {
+                    return Ok(());
+                }
+            }
+            votes.clear();
+            round_start = Instant::now();
+        }
+
+        if let Ok(mut status) = status.lock() {
+            status.connected = true;
+            status.seconds_left = ROUND_SECS.saturating_sub(round_start.elapsed().as_secs());
+            status.tallies = COMMANDS
+                .iter()
+                .map(|&(command, effect)| (command, votes.values().filter(|&&vote| vote == effect).count() as u32))
+                .collect();
+        }
+    }
+}
+
+//answers keep-alive pings and records one vote per chatter per round
+fn handle_line(line: &str, stream: &mut TcpStream, votes: &mut HashMap<String, Effect>) -> std::io::Result<()> {
+    if let Some(server) = line.strip_prefix("PING ") {
+        write!(stream, "PONG {}\r\n", server)?;
+        return Ok(());
+    }
+
+    //:user!user@user.tmi.twitch.tv PRIVMSG #channel :message
+    let mut parts = line.splitn(4, ' ');
+    let (prefix, command, _target, message) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(prefix), Some(command), Some(target), Some(message)) => (prefix, command, target, message),
+        _ => return Ok(()),
+    };
+    if command != "PRIVMSG" {
+        return Ok(());
+    }
+    let user = prefix.trim_start_matches(':').split('!').next().unwrap_or("");
+    let message = message.trim_start_matches(':').trim().to_lowercase();
+    if let Some(&(_, effect)) = COMMANDS.iter().find(|(command, _)| message == *command) {
+        votes.insert(user.to_string(), effect);
+    }
+    Ok(())
+}
+
+//most voted effect; ties go to the one listed first
+fn winning_effect(votes: &HashMap<String, Effect>) -> Option<Effect> {
+    let mut best: Option<(Effect, usize)> = None;
+    for &(_, effect) in COMMANDS.iter() {
+        let count = votes.values().filter(|&&vote| vote == effect).count();
+        if count > 0 && best.is_none_or(|(_, best_count)| count > best_count) {
+            best = Some((effect, count));
+        }
+    }
+    best.map(|(effect, _)| effect)
+}
@@ -0,0 +1,65 @@
+//optional check for a newer release on GitHub, run on a background thread so a slow or
+//missing network never holds up the game
+use serde::Deserialize;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+const RELEASES_URL: &str = "https://api.github.com/repos/riyazrabbani/Rust-Pacman/releases/latest";
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+}
+
+pub struct UpdateCheck {
+    result: Receiver<String>,
+}
+
+impl UpdateCheck {
+    pub fn start() -> UpdateCheck {
+        let (sender, result) = mpsc::channel();
+        thread::spawn(move || match latest_release() {
+            Ok(tag) => {
+                if is_newer(&tag, env!("CARGO_PKG_VERSION")) {
+                    let _ = sender.send(tag);
+                }
+            }
+            //not worth bothering the player about
+            Err(e) => eprintln!("Update check failed: {}", e),
+        });
+        UpdateCheck { result }
+    }
+
+    //the newer version's tag, once the check has found one
+    pub fn poll(&self) -> Option<String> {
+        self.result.try_recv().ok()
+    }
+}
+
+fn latest_release() -> Result<String, String> {
+    let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+    let body = agent
+        .get(RELEASES_URL)
+        //GitHub's API rejects requests without one
+        .set("User-Agent", concat!("rust-pacman/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    let release: Release = serde_json::from_str(&body).map_err(|e| e.to_string())?;
+    Ok(release.tag_name)
+}
+
+//compares dotted version numbers, ignoring a leading "v" and anything that isn't a number
+fn is_newer(tag: &str, current: &str) -> bool {
+    let parse = |version: &str| -> Vec<u64> {
+        version
+            .trim_start_matches('v')
+            .split(['.', '-'])
+            .map_while(|part| part.parse().ok())
+            .collect()
+    };
+    parse(tag) > parse(current)
+}
@@ -0,0 +1,95 @@
+//buttons shared by the overlay menus. A grid of them is moved through with the arrow keys,
+//pressed with Enter and clicked with the mouse, and hands back whatever action the button holds
+use ggez::graphics::{self, Color, DrawParam, Rect};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+
+const BUTTON_WIDTH: f32 = 120.0;
+const BUTTON_HEIGHT: f32 = 40.0;
+const GAP: f32 = 10.0;
+
+pub struct ButtonGrid<A: Copy> {
+    buttons: Vec<(A, &'static str)>,
+    columns: usize,
+    selected: usize,
+}
+
+impl<A: Copy> ButtonGrid<A> {
+    //buttons fill the rows left to right, `columns` to a row
+    pub fn new(buttons: Vec<(A, &'static str)>, columns: usize) -> Self {
+        ButtonGrid { buttons, columns: columns.max(1), selected: 0 }
+    }
+
+    //width and height of the whole grid
+    pub fn size(&self) -> (f32, f32) {
+        let columns = self.columns.min(self.buttons.len()) as f32;
+        let rows = self.rows() as f32;
+        (columns * BUTTON_WIDTH + (columns - 1.0).max(0.0) * GAP, rows * BUTTON_HEIGHT + (rows - 1.0).max(0.0) * GAP)
+    }
+
+    fn rows(&self) -> usize {
+        self.buttons.len().div_ceil(self.columns)
+    }
+
+    //the button's box with the grid's top-left corner at `origin`
+    fn button_rect(&self, index: usize, origin: (f32, f32)) -> Rect {
+        let (col, row) = (index % self.columns, index / self.columns);
+        Rect::new(
+            origin.0 + col as f32 * (BUTTON_WIDTH + GAP),
+            origin.1 + row as f32 * (BUTTON_HEIGHT + GAP),
+            BUTTON_WIDTH,
+            BUTTON_HEIGHT,
+        )
+    }
+
+    //arrows move the selection, Enter presses the selected button
+    pub fn handle_key(&mut self, keycode: KeyCode) -> Option<A> {
+        let count = self.buttons.len();
+        if count == 0 {
+            return None;
+        }
+        match keycode {
+            KeyCode::Left => self.selected = (self.selected + count - 1) % count,
+            KeyCode::Right => self.selected = (self.selected + 1) % count,
+            KeyCode::Up if self.selected >= self.columns => self.selected -= self.columns,
+            KeyCode::Down if self.selected + self.columns < count => self.selected += self.columns,
+            KeyCode::Return => return Some(self.buttons[self.selected].0),
+            _ => {}
+        }
+        None
+    }
+
+    //the button under (x, y), which also becomes the selected one
+    pub fn click(&mut self, origin: (f32, f32), x: f32, y: f32) -> Option<A> {
+        let index = (0..self.buttons.len()).find(|&index| self.button_rect(index, origin).contains([x, y]))?;
+        self.selected = index;
+        Some(self.buttons[index].0)
+    }
+
+    pub fn draw(&self, ctx: &mut Context, origin: (f32, f32)) -> GameResult {
+        for (index, (_, label)) in self.buttons.iter().enumerate() {
+            let rect = self.button_rect(index, origin);
+            let selected = index == self.selected;
+            let fill = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, Color::new(0.35, 0.35, 0.35, 1.0))?;
+            graphics::draw(ctx, &fill, DrawParam::default())?;
+            if selected {
+                let outline = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(2.0), rect, Color::YELLOW)?;
+                graphics::draw(ctx, &outline, DrawParam::default())?;
+            }
+
+            let text = graphics::Text::new(*label);
+            let dims = text.dimensions(ctx);
+            graphics::draw(
+                ctx,
+                &text,
+                DrawParam::default()
+                    .dest(ggez::mint::Point2 {
+                        x: rect.x + (rect.w - dims.w) / 2.0,
+                        y: rect.y + (rect.h - dims.h) / 2.0,
+                    })
+                    .color(if selected { Color::YELLOW } else { Color::WHITE }),
+            )?;
+        }
+        Ok(())
+    }
+}
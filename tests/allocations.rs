@@ -0,0 +1,86 @@
+//checks that the per-tick ghost update doesn't touch the heap, with a global allocator that counts
+//the allocations made on the test's own thread
+use ggez::graphics::{Color, Rect};
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use rust_pacman::collision::WallGrid;
+use rust_pacman::{Ghost, CELL_SIZE, PACMAN_SIZE};
+
+struct CountingAllocator;
+
+thread_local! {
+    //only the test thread counts, so the harness allocating elsewhere doesn't show up
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.with(|counting| counting.get()) {
+            ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if COUNTING.with(|counting| counting.get()) {
+            ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+//the allocations `f` made on this thread
+fn count_allocations(f: impl FnOnce()) -> usize {
+    ALLOCATIONS.with(|count| count.set(0));
+    COUNTING.with(|counting| counting.set(true));
+    f();
+    COUNTING.with(|counting| counting.set(false));
+    ALLOCATIONS.with(|count| count.get())
+}
+
+//a bordered maze with pillars on every other cell, so ghosts keep meeting junctions
+fn maze_walls(size: usize) -> WallGrid {
+    let mut walls = Vec::new();
+    for y in 0..size {
+        for x in 0..size {
+            let border = x == 0 || y == 0 || x == size - 1 || y == size - 1;
+            if border || (x % 2 == 0 && y % 2 == 0) {
+                walls.push(Rect::new(x as f32 * CELL_SIZE, y as f32 * CELL_SIZE, CELL_SIZE, CELL_SIZE));
+            }
+        }
+    }
+    WallGrid::new(&walls)
+}
+
+#[test]
+fn ghost_update_does_not_allocate() {
+    let walls = maze_walls(20);
+    let offset = (CELL_SIZE - PACMAN_SIZE) / 2.0;
+    let mut ghosts: Vec<Ghost> = [Color::RED, Color::CYAN, Color::MAGENTA, Color::YELLOW]
+        .iter()
+        .enumerate()
+        .map(|(i, &color)| Ghost::new(CELL_SIZE * (1 + 2 * i) as f32 + offset, CELL_SIZE + offset, color))
+        .collect();
+    let mut rng = Pcg32::seed_from_u64(7);
+    let (pacman_x, pacman_y) = (CELL_SIZE * 9.0 + offset, CELL_SIZE * 9.0 + offset);
+
+    let allocations = count_allocations(|| {
+        for _ in 0..10_000 {
+            for ghost in &mut ghosts {
+                ghost.update(&walls, pacman_x, pacman_y, 1.0, &mut rng);
+            }
+        }
+    });
+    assert_eq!(allocations, 0, "Ghost::update allocated {} times in 10000 ticks", allocations);
+}
@@ -0,0 +1,130 @@
+//golden-state regression tests: runs the deterministic sim from fixed seeds with a fixed
+//input pattern and compares a hash of the resulting state with tests/golden/states.txt.
+//
+//A mismatch means movement, AI or scoring behaves differently than before. If that change is
+//intended, regenerate the file with `GOLDEN_BLESS=1 cargo test --test golden` and commit it.
+//A missing file fails the test too, so a checkout can't quietly bless whatever it computes.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use rust_pacman::env::{Observation, PacmanEnv, ACTIONS};
+use rust_pacman::Direction;
+
+const SEEDS: [u64; 3] = [1, 42, 1234];
+const TICK_COUNTS: [u32; 3] = [60, 600, 3000];
+
+fn golden_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden/states.txt")
+}
+
+//turns every 45 ticks and mixes in idle stretches, so pacman explores the maze and ghosts
+//get to catch him
+fn action(tick: u32) -> Direction {
+    ACTIONS[((tick / 45) * 7 % 5) as usize]
+}
+
+//FNV-1a over the observation; floats are hashed by their bits so any drift shows up
+fn hash_observation(observation: &Observation) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut write = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    };
+    write(&observation.tiles);
+    write(&observation.pacman.0.to_bits().to_le_bytes());
+    write(&observation.pacman.1.to_bits().to_le_bytes());
+    for ghost in &observation.ghosts {
+        write(&ghost.x.to_bits().to_le_bytes());
+        write(&ghost.y.to_bits().to_le_bytes());
+        write(&[ghost.vulnerable as u8]);
+    }
+    write(&observation.power_pellet_timer.to_bits().to_le_bytes());
+    write(&observation.score.to_le_bytes());
+    write(&observation.lives.to_le_bytes());
+    hash
+}
+
+fn run(seed: u64, ticks: u32) -> u64 {
+    let mut env = PacmanEnv::new().unwrap();
+    let mut observation = env.reset(seed);
+    for tick in 0..ticks {
+        let (next, _, done) = env.step(action(tick));
+        observation = next;
+        if done {
+            break;
+        }
+    }
+    hash_observation(&observation)
+}
+
+fn current_states() -> BTreeMap<(u64, u32), u64> {
+    let mut states = BTreeMap::new();
+    for &seed in &SEEDS {
+        for &ticks in &TICK_COUNTS {
+            states.insert((seed, ticks), run(seed, ticks));
+        }
+    }
+    states
+}
+
+//one `seed ticks hash` line per case
+fn parse_golden(text: &str) -> BTreeMap<(u64, u32), u64> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            assert_eq!(fields.len(), 3, "malformed golden line: {}", line);
+            let seed = fields[0].parse().unwrap();
+            let ticks = fields[1].parse().unwrap();
+            let hash = u64::from_str_radix(fields[2], 16).unwrap();
+            ((seed, ticks), hash)
+        })
+        .collect()
+}
+
+fn write_golden(states: &BTreeMap<(u64, u32), u64>) {
+    let mut text = String::from("# seed ticks state-hash, see tests/golden.rs\n");
+    for (&(seed, ticks), hash) in states {
+        text.push_str(&format!("{} {} {:016x}\n", seed, ticks, hash));
+    }
+    let path = golden_path();
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, text).unwrap();
+}
+
+#[test]
+fn same_seed_same_state() {
+    assert_eq!(run(42, 600), run(42, 600));
+}
+
+#[test]
+fn states_match_golden_values() {
+    let states = current_states();
+    let path = golden_path();
+    if std::env::var_os("GOLDEN_BLESS").is_some() {
+        write_golden(&states);
+        eprintln!("Wrote golden states to {}", path.display());
+        return;
+    }
+
+    let golden = fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!("could not read {}: {}\nrun with GOLDEN_BLESS=1 to create it", path.display(), e)
+    });
+    let golden = parse_golden(&golden);
+    let mismatches: Vec<String> = states
+        .iter()
+        .filter(|(case, hash)| golden.get(case) != Some(hash))
+        .map(|(&(seed, ticks), hash)| {
+            let expected = golden.get(&(seed, ticks)).map_or("missing".to_string(), |h| format!("{:016x}", h));
+            format!("seed {} after {} ticks: expected {}, got {:016x}", seed, ticks, expected, hash)
+        })
+        .collect();
+    assert!(
+        mismatches.is_empty(),
+        "simulation state changed:\n{}\nrerun with GOLDEN_BLESS=1 if this is intended",
+        mismatches.join("\n")
+    );
+}
@@ -0,0 +1,197 @@
+//property tests for grid movement, ghost collision and pathfinding, run against randomly generated
+//mazes
+use ggez::graphics::{Color, Rect};
+use proptest::prelude::*;
+use std::collections::VecDeque;
+use proptest::sample::Index;
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+
+use rust_pacman::map::{self, Map};
+use rust_pacman::collision::{self, Tunnels, WallGrid};
+use rust_pacman::fixed::Fixed;
+use rust_pacman::{movement, Direction, Ghost, CELL_SIZE, GHOST_SIZE, PACMAN_SIZE};
+
+const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+//pacman's speed on the first level, a pixel a tick
+const STEP: f32 = 1.0;
+
+//random mazes: a solid border around randomly placed walls and dots, with pacman's start
+//on one of the inner cells
+fn arb_map() -> impl Strategy<Value = Map> {
+    (5usize..20, 5usize..20)
+        .prop_flat_map(|(width, height)| {
+            (
+                Just(width),
+                Just(height),
+                prop::collection::vec(prop::bool::weighted(0.3), width * height),
+                (1..width - 1, 1..height - 1),
+            )
+        })
+        .prop_map(|(width, height, walls, (start_col, start_row))| {
+            let mut text = String::new();
+            for row in 0..height {
+                for col in 0..width {
+                    let border = col == 0 || row == 0 || col == width - 1 || row == height - 1;
+                    let cell = if (col, row) == (start_col, start_row) {
+                        'P'
+                    } else if border || walls[row * width + col] {
+                        'W'
+                    } else {
+                        '.'
+                    };
+                    text.push(cell);
+                }
+                text.push('\n');
+            }
+            map::parse(&text).expect("generated maps are valid")
+        })
+}
+
+fn open_cells(map: &Map) -> Vec<(usize, usize)> {
+    let mut cells = Vec::new();
+    for row in 0..map.height {
+        for col in 0..map.width {
+            if map.cell(col, row) != Some('W') {
+                cells.push((col, row));
+            }
+        }
+    }
+    cells
+}
+
+//top-left corner of an entity of `size` centered in a cell
+fn cell_position(col: usize, row: usize, size: f32) -> (f32, f32) {
+    (
+        col as f32 * CELL_SIZE + (CELL_SIZE - size) / 2.0,
+        row as f32 * CELL_SIZE + (CELL_SIZE - size) / 2.0,
+    )
+}
+
+//steps from every open cell to `goal` along open cells, None where it can't be reached
+fn distances_to(map: &Map, goal: (usize, usize)) -> Vec<Option<u32>> {
+    let mut distances = vec![None; map.width * map.height];
+    distances[goal.1 * map.width + goal.0] = Some(0);
+    let mut queue = VecDeque::from([goal]);
+    while let Some((col, row)) = queue.pop_front() {
+        let distance = distances[row * map.width + col].unwrap_or(0);
+        for (next_col, next_row) in [(col.wrapping_sub(1), row), (col + 1, row), (col, row.wrapping_sub(1)), (col, row + 1)] {
+            if map.cell(next_col, next_row).is_none_or(|cell| cell == 'W') {
+                continue;
+            }
+            let next = &mut distances[next_row * map.width + next_col];
+            if next.is_none() {
+                *next = Some(distance + 1);
+                queue.push_back((next_col, next_row));
+            }
+        }
+    }
+    distances
+}
+
+proptest! {
+    #[test]
+    fn snap_to_grid_is_idempotent(x in -100.0f32..1000.0, y in -100.0f32..1000.0) {
+        let once = movement::snap_to_grid(Fixed::from_f32(x), Fixed::from_f32(y));
+        prop_assert_eq!(movement::snap_to_grid(once.0, once.1), once);
+        prop_assert!(movement::is_at_grid_center(once.0, once.1));
+    }
+
+    #[test]
+    fn can_move_never_ends_inside_a_wall(map in arb_map(), start in any::<Index>()) {
+        let walls = WallGrid::new(&map.walls());
+        let cells = open_cells(&map);
+        let (col, row) = cells[start.index(cells.len())];
+        let (x, y) = cell_position(col, row, PACMAN_SIZE);
+
+        for &direction in &DIRECTIONS {
+            if !movement::can_move(&walls, Fixed::from_f32(x), Fixed::from_f32(y), direction) {
+                continue;
+            }
+            let (dx, dy) = match direction {
+                Direction::Up => (0.0, -STEP),
+                Direction::Down => (0.0, STEP),
+                Direction::Left => (-STEP, 0.0),
+                Direction::Right => (STEP, 0.0),
+                Direction::None => (0.0, 0.0),
+            };
+            //walk the whole way to the next cell one tick at a time
+            let steps = (CELL_SIZE / STEP) as usize;
+            for step in 1..=steps {
+                let rect = Rect::new(x + dx * step as f32, y + dy * step as f32, PACMAN_SIZE, PACMAN_SIZE);
+                prop_assert!(
+                    !collision::hits_wall(&walls, &rect),
+                    "moving {:?} from ({}, {}) hit a wall after {} steps", direction, col, row, step
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn ghosts_never_overlap_walls(map in arb_map(), start in any::<Index>(), seed in any::<u64>(), ticks in 1usize..300) {
+        let walls = WallGrid::new(&map.walls());
+        let cells = open_cells(&map);
+        let (col, row) = cells[start.index(cells.len())];
+        let (x, y) = cell_position(col, row, GHOST_SIZE);
+        let (pacman_col, pacman_row) = map.pacman_start();
+        let (pacman_x, pacman_y) = cell_position(pacman_col, pacman_row, PACMAN_SIZE);
+
+        let mut ghost = Ghost::new(x, y, Color::RED);
+        let mut rng = Pcg32::seed_from_u64(seed);
+        for tick in 0..ticks {
+            ghost.update(&walls, pacman_x, pacman_y, 1.0, &mut rng);
+            let (gx, gy) = ghost.position();
+            prop_assert!(
+                !collision::hits_wall(&walls, &Rect::new(gx, gy, GHOST_SIZE, GHOST_SIZE)),
+                "ghost entered a wall at ({}, {}) on tick {}", gx, gy, tick
+            );
+        }
+    }
+
+    #[test]
+    fn first_step_is_on_a_shortest_path(map in arb_map(), start in any::<Index>(), goal in any::<Index>()) {
+        let walls = WallGrid::new(&map.walls());
+        let cells = open_cells(&map);
+        let (start, goal) = (cells[start.index(cells.len())], cells[goal.index(cells.len())]);
+        let distances = distances_to(&map, goal);
+        let from = (start.0 as i64, start.1 as i64);
+        let step = walls.paths().first_step(from, (goal.0 as i64, goal.1 as i64));
+        match distances[start.1 * map.width + start.0] {
+            Some(distance) if distance > 0 => {
+                let step = step.expect("a reachable goal has a first step");
+                let (col, row) = match step {
+                    Direction::Up => (start.0, start.1 - 1),
+                    Direction::Down => (start.0, start.1 + 1),
+                    Direction::Left => (start.0 - 1, start.1),
+                    Direction::Right => (start.0 + 1, start.1),
+                    Direction::None => panic!("no way is not a step"),
+                };
+                prop_assert_eq!(distances[row * map.width + col], Some(distance - 1));
+            }
+            Some(_) => prop_assert!(step.is_none(), "already at the goal but told to go {:?}", step),
+            None => {}
+        }
+    }
+}
+
+//the way through a tunnel is the short way round, and a box whose center goes past its end comes
+//in at the other end of the same row
+#[test]
+fn tunnels_lead_to_the_opposite_edge() {
+    let map = map::parse("WWWWW\nT.P.T\nWWWWW\n").expect("the tunnel maze is valid");
+    let mut walls = WallGrid::new(&map.walls());
+    walls.set_tunnels(Tunnels::new(map.width, map.height, &map.tunnel_cells()));
+
+    //two steps left through the tunnel beat three to the right
+    assert_eq!(walls.paths().first_step((1, 1), (4, 1)), Some(Direction::Left));
+    let center = |col: usize| Fixed::from_f32(col as f32 * CELL_SIZE + CELL_SIZE / 2.0);
+    let (dx, _) = walls.delta((center(0), center(1)), (center(map.width - 1), center(1)));
+    assert_eq!(dx, Fixed::from_f32(-CELL_SIZE), "the far end is a cell away through the tunnel");
+    let size = Fixed::from_f32(GHOST_SIZE);
+    let (x, y) = cell_position(0, 1, GHOST_SIZE);
+    let (past_x, past_y) = (Fixed::from_f32(x - CELL_SIZE / 2.0 - 1.0), Fixed::from_f32(y));
+    let (wrapped_x, wrapped_y) = walls.wrap_box(past_x, past_y, size);
+    assert_eq!(wrapped_y, past_y);
+    assert_eq!(wrapped_x, past_x + Fixed::from_f32(CELL_SIZE * map.width as f32));
+    assert!(walls.in_tunnel((0, 1)) && !walls.in_tunnel((1, 1)));
+}
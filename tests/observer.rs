@@ -0,0 +1,58 @@
+//observers registered on the environment hear about every tick and what happened in it
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rust_pacman::env::PacmanEnv;
+use rust_pacman::observer::{GameEvent, GameView, Observer};
+use rust_pacman::Direction;
+
+#[derive(Default)]
+struct Log {
+    ticks: Vec<u32>,
+    events: Vec<GameEvent>,
+    last_score: u32,
+}
+
+struct Recorder(Rc<RefCell<Log>>);
+
+impl Observer for Recorder {
+    fn on_tick(&mut self, view: &GameView) {
+        let mut log = self.0.borrow_mut();
+        log.ticks.push(view.tick);
+        log.last_score = view.score;
+    }
+
+    fn on_event(&mut self, event: &GameEvent) {
+        self.0.borrow_mut().events.push(event.clone());
+    }
+}
+
+#[test]
+fn observer_sees_every_tick_and_event() {
+    let log = Rc::new(RefCell::new(Log::default()));
+    let mut env = PacmanEnv::new().expect("environment");
+    env.add_observer(Box::new(Recorder(log.clone())));
+    env.reset(42);
+
+    let mut score = 0;
+    for _ in 0..120 {
+        let (observation, _, done) = env.step(Direction::Left);
+        score = observation.score;
+        if done {
+            break;
+        }
+    }
+
+    let log = log.borrow();
+    assert_eq!(log.ticks.len(), 120);
+    assert!(log.ticks.windows(2).all(|pair| pair[1] == pair[0] + 1));
+    assert_eq!(log.events.first(), Some(&GameEvent::LevelStart { level: 1 }));
+    //pacman runs along a row of dots, each one an event
+    let dots = log
+        .events
+        .iter()
+        .filter(|event| matches!(event, GameEvent::PelletEaten { power: false, .. }))
+        .count();
+    assert!(dots > 0);
+    assert_eq!(log.last_score, score);
+}